@@ -0,0 +1,160 @@
+// Generated by gamemstr-common with the `typescript` feature. Do not edit.
+
+export type Action = { id: string, action: ActionType, };
+
+export type ActionType = { "Attack": Attack } | { "Special": SpecialAction } | { "Reaction": SpecialAction } | { "LegendaryAction": SpecialAction };
+
+export type Alignment = "AnyAlignment" | "AnyChaotic" | "AnyEvil" | "AnyGood" | "AnyLawful" | "AnyNeutral" | "AnyNonChaotic" | "AnyNonEvil" | "AnyNonGood" | "AnyNonLawful" | "AnyNonNeutral" | "ChaoticEvil" | "ChaoticNeutral" | "ChaoticGood" | "LawfulEvil" | "LawfulNeutral" | "LawfulGood" | "NeutralEvil" | "TrueNeutral" | "NeutralGood" | "TypicallyChaoticEvil" | "TypicallyChaoticNeutral" | "TypicallyChaoticGood" | "TypicallyLawfulEvil" | "TypicallyLawfulNeutral" | "TypicallyLawfulGood" | "TypicallyNeutralEvil" | "TypicallyTrueNeutral" | "TypicallyNeutralGood" | "Unaligned";
+
+export type Area = { "Cube": number } | { "Sphere": number } | { "Cone": number } | { "Line": number } | { "Cylinder": number } | { "Wall": number };
+
+export type ArmorType = "Shield";
+
+export type Attack = { "MeleeWeaponAttack": Melee } | { "RangedWeaponAttack": Ranged } | { "MeleeOrRangedWeaponAttack": MeleeOrRanged } | { "MeleeSpellAttack": Melee } | { "RangedSpellAttack": Ranged };
+
+export type Attendance = "Present" | "Absent";
+
+export type Attuneable = { alignments: Array<Alignment> | null, };
+
+export type Award = { player_id: CreatureId, experience: number, };
+
+export type Campaign = { id: CampaignId, name: string, description: string, world_id: WorldId, 
+/**
+ * The ids of the players in the campaign, each of which refers back by its `campaign_id`.
+ */
+player_ids: Array<CreatureId>, };
+
+export type CampaignId = string;
+
+export type CastingTime = "Action" | "BonusAction" | "Reaction" | "Minute" | "Hour";
+
+export type Charge = { num: number, time: TimeDivision, };
+
+export type Class = "Barbarian" | "Bard" | "Cleric" | "Druid" | "Fighter" | "Monk" | "Paladin" | "Ranger" | "Rogue" | "Sorcerer" | "Warlock" | "Wizard";
+
+export type Components = "V" | "S" | "M" | "VS" | "VM" | "SM" | "VSM";
+
+export type ConditionType = "Blinded" | "Charmed" | "Deafened" | "Exhaustion" | "Frightened" | "Grappled" | "Incapacitated" | "Invisible" | "Paralyzed" | "Petrified" | "Poisoned" | "Prone" | "Restrained" | "Stunned" | "Unconscious";
+
+export type Creature = { id: CreatureId, name: string, size: Size, creature_type: CreatureType, alignment: Alignment, armor_class: number, health_points: Health, speed: MovementSpeed, stats: Array<Stat>, saving_throws: Array<Stat> | null, damage_resistances: Array<DamageType> | null, damage_immunities: Array<DamageType> | null, damage_vulnerabilities: Array<DamageType> | null, condition_immunities: Array<ConditionType> | null, skills: Array<Skill> | null, senses: Array<Sense> | null, languages: Array<Language> | null, challenge_rating: string, racial_traits: Array<RacialTrait> | null, description: string | null, actions: Array<Action> | null, lair: Lair | null, others: Array<OtherAttribute> | null, };
+
+export type CreatureId = string;
+
+export type CreatureType = { "Monster": MonsterType } | "Player" | "NPC";
+
+export type Damage = { damage: DieStat, damage_type: DamageType, condition: string | null, };
+
+export type DamageType = "Slashing" | "Piercing" | "Bludgeoning" | "Poison" | "Acid" | "Fire" | "Cold" | "Radiant" | "Necrotic" | "Lightning" | "Thunder" | "Force" | "Psychic";
+
+export type Die = "D4" | "D6" | "D8" | "D10" | "D12" | "D20" | "D100";
+
+export type DieStat = { die_count: number, die_type: Die, extra: number, };
+
+export type Duration = "Instantaneous" | "Concentration" | "Minute" | "Hour" | "Day" | "UntilDispelled" | "UntilDispelledOrTriggered" | "UntilTriggered";
+
+export type Health = { health: DieStat, };
+
+export type Item = { id: string, name: string, item_type: ItemType, rarity: ItemRarity, attunement: Attuneable | null, weapon_type: WeaponType | null, armor_type: ArmorType | null, conditions: Array<ConditionType> | null, attached_spell: Spell | null, has_charges: Charge | null, inventory: Array<Item> | null, others: Array<OtherAttribute> | null, actions: Array<Action> | null, };
+
+export type ItemRarity = "Common" | "Uncommon" | "Rare" | "VeryRare" | "Legendary" | "Artifact" | "Varies" | "Unknown";
+
+export type ItemType = "Armor" | "Potion" | "Ring" | "Rod" | "Scroll" | "Staff" | "Wand" | "Weapon" | "WondrousItem";
+
+export type Lair = { name: string, description: string, lair_actions: Array<Paragraph>, regional_effects: Array<Paragraph>, };
+
+export type Language = "Abanasinia" | "Abyssal" | "Aquan" | "Auran" | "Celestial" | "Common" | "DeepSpeech" | "Draconic" | "Dwarvish" | "Elvish" | "Ergot" | "Giant" | "Gnomish" | "Goblin" | "Hadozee" | "Halfling" | "Ignan" | "Infernal" | "Istarian" | "Kenderspeak" | "Kharolian" | "Khur" | "Kothian" | "Kraul" | "Leonin" | "Loxodon" | "Marquesian" | "Merfolk" | "Minotaur" | "Naush" | "Narakese" | "Nordmaarian" | "Orc" | "Primordial" | "Quori" | "Riedran" | "Solamnic" | "Sphinx" | "Sylvan" | "Terran" | "ThriKreen" | "Undercommon" | "Vedalken" | "Zemnian";
+
+export type Location = { id: LocationId, name: string, description: string, world_id: WorldId, map_coordinates: MapCoordinates, npcs: Array<Creature>, };
+
+export type LocationId = string;
+
+export type Loot = { item: string, player_id: CreatureId | null, };
+
+export type Map = { id: MapId, name: string, description: string, world_id: WorldId, width: number, height: number, map: Array<Array<string>>, locations: Array<Location>, };
+
+export type MapCoordinates = { map_id: MapId, x: number, y: number, };
+
+export type MapId = string;
+
+export type Melee = { name: string, modifier: number, reach: number | null, target_type: TargetType, damage: Array<Damage>, description: string, };
+
+export type MeleeOrRanged = { name: string, modifier: number, reach: number | null, range: Range | null, target_type: TargetType, damage: Array<Damage>, description: string, };
+
+export type MonsterType = "Aberration" | "Beast" | "Celestial" | "Construct" | "Dragon" | "Elemental" | "Fey" | "Fiend" | "Giant" | "Humanoid" | "Monstrosity" | "Ooze" | "Plant" | "Undead";
+
+export type MovementSpeed = { "Walk": number } | { "Swim": number } | { "Fly": { speed: number, hover: boolean, } } | { "Burrow": number } | { "Climb": number };
+
+export type Note = { id: NoteId, name: string, description: string, };
+
+export type NoteId = string;
+
+export type OtherAttribute = { title: string, description: string, value: string, };
+
+export type Paragraph = { paragraph: string, bullet: boolean, };
+
+export type Plan = { summary: string, scenes: Array<Scene>, encounters: Array<string>, 
+/**
+ * The non-player characters to introduce.
+ */
+npcs: Array<string>, locations: Array<LocationId>, };
+
+export type Player = { creature: Creature, race: Race, class: Class, level: number, experience: number, inventory: Array<Item>, spells: Array<Spell>, campaign_id: CampaignId, };
+
+export type Race = "Human" | "Elf" | "Dwarf" | "Halfling" | "Gnome" | "HalfElf" | "HalfOrc" | "Tiefling";
+
+export type RacialTrait = { name: string, description: string, };
+
+export type Range = { close_range: number, long_range: number, };
+
+export type Ranged = { name: string, modifier: number, range: Range | null, target_type: TargetType, damage: Array<Damage>, description: string, };
+
+export type Recap = { summary: string, events: Array<string>, loot: Array<Loot>, experience: Array<Award>, open_threads: Array<string>, };
+
+export type Save = { "Strength": number | null } | { "Dexterity": number | null } | { "Constitution": number | null } | { "Intelligence": number | null } | { "Wisdom": number | null } | { "Charisma": number | null };
+
+export type Scene = { title: string, description: string, };
+
+export type Schedule = { planned: bigint | null, started: bigint | null, ended: bigint | null, 
+/**
+ * The IANA name of the time zone the table plays in, such as `Europe/Berlin`, for
+ * showing the times in local time.
+ */
+timezone: string, };
+
+export type Sense = { "Blindsight": number } | { "Darkvision": number } | { "Tremorsense": number } | { "Truesight": number };
+
+export type Session = { id: SessionId, name: string, description: string, campaign_id: CampaignId, notes: Array<Note>, plan: Plan, recap: Recap, schedule: Schedule, 
+/**
+ * Whether each player of the campaign was at the session, once it has been recorded.
+ */
+attendance: { [key in CreatureId]?: Attendance }, };
+
+export type SessionId = string;
+
+export type Size = "Tiny" | "Small" | "Medium" | "Large" | "Huge" | "Gargantuan";
+
+export type Skill = { skill_type: SkillType, modifier: number, };
+
+export type SkillType = "Acrobatics" | "AnimalHandling" | "Arcana" | "Athletics" | "Deception" | "History" | "Insight" | "Intimidation" | "Investigation" | "Medicine" | "Nature" | "Perception" | "Performance" | "Persuasion" | "Religion" | "SleightOfHand" | "Stealth" | "Survival";
+
+export type SpecialAction = { name: string, description: string, };
+
+export type Spell = { id: string, name: string, description: string, level: SpellLevel, casting_time: CastingTime, duration: Duration, damage: DieStat | null, range: SpellRange, area: Area | null, damage_type: DamageType | null, components: Components, attack_bonus: number | null, save: Save | null, };
+
+export type SpellLevel = "Cantrip" | "Level1" | "Level2" | "Level3" | "Level4" | "Level5" | "Level6" | "Level7" | "Level8" | "Level9";
+
+export type SpellRange = "S" | "Touch" | { "Range": number };
+
+export type Stat = { stat_type: StatType, value: number, modifier: number, };
+
+export type StatType = "Strength" | "Dexterity" | "Constitution" | "Intelligence" | "Wisdom" | "Charisma";
+
+export type TargetType = "OneTarget" | { "MultipleTargets": number } | { "Cone": number } | { "Line": number } | { "Cube": number } | { "Sphere": number };
+
+export type TimeDivision = "Round" | "Second" | "Minute" | "Hour" | "Day" | "Month" | "Year";
+
+export type WeaponType = "Sword";
+
+export type World = { id: WorldId, name: string, description: string, };
+
+export type WorldId = string;
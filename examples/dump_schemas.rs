@@ -0,0 +1,16 @@
+//! Writes the JSON Schema of every serialized type to a directory.
+//!
+//! `cargo run --example dump_schemas --features schema -- <dir>`
+
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("schemas"));
+    for file in gamemstr_common::schema::write_schemas(&dir)? {
+        println!("{}", file.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! Writes the TypeScript declarations of every serialized type to `bindings/gamemstr.d.ts`, or
+//! to the directory given as the first argument.
+//!
+//! `cargo run --example dump_typescript --features typescript -- <dir>`
+
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("bindings"));
+    gamemstr_common::typescript::write_declarations(&dir)?;
+    println!("{}", dir.join("gamemstr.d.ts").display());
+    Ok(())
+}
@@ -9,6 +9,8 @@ use self::attack::Attack;
 pub mod attack;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Action {
     pub id: String,
     pub action: ActionType,
@@ -24,14 +26,37 @@ impl Action {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum ActionType {
     Attack(Attack),
+    Special(SpecialAction),
+    Reaction(SpecialAction),
+    LegendaryAction(SpecialAction),
 }
 
 impl fmt::Display for ActionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
             ActionType::Attack(x) => write!(f, "{}", x),
+            ActionType::Special(x) => write!(f, "{}", x),
+            ActionType::Reaction(x) => write!(f, "{}", x),
+            ActionType::LegendaryAction(x) => write!(f, "{}", x),
         }
     }
 }
+
+/// An action described only by text, such as Multiattack or a dragon's Wing Attack.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct SpecialAction {
+    pub name: String,
+    pub description: String,
+}
+
+impl fmt::Display for SpecialAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. {}", self.name, self.description)
+    }
+}
@@ -4,7 +4,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{DamageType, DieStat};
 
+pub mod parse;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Attack {
     MeleeWeaponAttack(Melee),
     RangedWeaponAttack(Ranged),
@@ -19,6 +23,38 @@ impl Default for Attack {
     }
 }
 
+impl Attack {
+    pub fn damage(&self) -> &Vec<Damage> {
+        match &self {
+            Attack::MeleeWeaponAttack(x) | Attack::MeleeSpellAttack(x) => &x.damage,
+            Attack::RangedWeaponAttack(x) | Attack::RangedSpellAttack(x) => &x.damage,
+            Attack::MeleeOrRangedWeaponAttack(x) => &x.damage,
+        }
+    }
+
+    pub fn modifier(&self) -> i32 {
+        match &self {
+            Attack::MeleeWeaponAttack(x) | Attack::MeleeSpellAttack(x) => x.modifier,
+            Attack::RangedWeaponAttack(x) | Attack::RangedSpellAttack(x) => x.modifier,
+            Attack::MeleeOrRangedWeaponAttack(x) => x.modifier,
+        }
+    }
+
+    /// Average damage dealt on a hit, counting only the components without a condition.
+    pub fn average_damage(&self) -> i32 {
+        self.damage()
+            .iter()
+            .filter(|x| x.condition.is_none())
+            .map(|x| x.damage.value())
+            .sum()
+    }
+
+    /// Average damage dealt on a hit when every conditional component also applies.
+    pub fn max_average_damage(&self) -> i32 {
+        self.damage().iter().map(|x| x.damage.value()).sum()
+    }
+}
+
 impl fmt::Display for Attack {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
@@ -33,14 +69,49 @@ impl fmt::Display for Attack {
     }
 }
 
+/// A single damage component of an attack, e.g. the "plus 7 (2d6) poison damage" part of a hit.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Damage {
+    pub damage: DieStat,
+    pub damage_type: DamageType,
+    pub condition: Option<String>,
+}
+
+impl fmt::Display for Damage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.damage, self.damage_type)?;
+        if let Some(condition) = &self.condition {
+            write!(f, " {}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+struct DamageList<'a>(&'a [Damage]);
+
+impl fmt::Display for DamageList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, damage) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " plus ")?;
+            }
+            write!(f, "{}", damage)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Melee {
     pub name: String,
     pub modifier: i32,
     pub reach: Option<i32>,
     pub target_type: TargetType,
-    pub damage: DieStat,
-    pub damage_type: DamageType,
+    pub damage: Vec<Damage>,
     pub description: String,
 }
 
@@ -48,27 +119,27 @@ impl fmt::Display for Melee {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}. Melee %s Attack: {} to hit, reach {}, {}. Hit: {} {}. {}",
+            "{}. Melee %s Attack: {} to hit, reach {}, {}. Hit: {}. {}",
             self.name,
             self.modifier,
             self.reach.unwrap_or(5),
             self.target_type,
-            self.damage,
-            self.damage_type,
+            DamageList(&self.damage),
             self.description
         )
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct MeleeOrRanged {
     pub name: String,
     pub modifier: i32,
     pub reach: Option<i32>,
     pub range: Option<Range>,
     pub target_type: TargetType,
-    pub damage: DieStat,
-    pub damage_type: DamageType,
+    pub damage: Vec<Damage>,
     pub description: String,
 }
 
@@ -76,33 +147,30 @@ impl fmt::Display for MeleeOrRanged {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}. Melee or Ranged %s Attack: {} to hit, reach {}, range {}, {}. Hit: {} {}. {}",
+            "{}. Melee or Ranged %s Attack: {} to hit, reach {}, range {}, {}. Hit: {}. {}",
             self.name,
             self.modifier,
             self.reach.unwrap_or(5),
-            self.range
-                .as_ref()
-                .unwrap_or(&Range {
-                    close_range: 0,
-                    long_range: 0
-                })
-                ,
+            self.range.as_ref().unwrap_or(&Range {
+                close_range: 0,
+                long_range: 0
+            }),
             self.target_type,
-            self.damage,
-            self.damage_type,
+            DamageList(&self.damage),
             self.description
         )
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Ranged {
     pub name: String,
     pub modifier: i32,
     pub range: Option<Range>,
     pub target_type: TargetType,
-    pub damage: DieStat,
-    pub damage_type: DamageType,
+    pub damage: Vec<Damage>,
     pub description: String,
 }
 
@@ -110,25 +178,142 @@ impl fmt::Display for Ranged {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{}. Ranged %s Attack: {} to hit, range {}, {}. Hit: {} {}. {}",
+            "{}. Ranged %s Attack: {} to hit, range {}, {}. Hit: {}. {}",
             self.name,
             self.modifier,
-            self.range
-                .as_ref()
-                .unwrap_or(&Range {
-                    close_range: 0,
-                    long_range: 0
-                })
-                ,
+            self.range.as_ref().unwrap_or(&Range {
+                close_range: 0,
+                long_range: 0
+            }),
             self.target_type,
-            self.damage,
-            self.damage_type,
+            DamageList(&self.damage),
             self.description
         )
     }
 }
 
+/// Deserialization shapes that also accept attacks stored before damage became a list, when
+/// each attack had a single `damage` roll and a separate `damage_type`.
+mod legacy {
+    use serde::Deserialize;
+
+    use super::{Damage, Range, TargetType};
+    use crate::{DamageType, DieStat};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DamageField {
+        List(Vec<Damage>),
+        Single(DieStat),
+    }
+
+    fn into_list(damage: DamageField, damage_type: Option<DamageType>) -> Vec<Damage> {
+        match damage {
+            DamageField::List(list) => list,
+            DamageField::Single(damage) => vec![Damage {
+                damage,
+                damage_type: damage_type.unwrap_or_default(),
+                condition: None,
+            }],
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct Melee {
+        name: String,
+        modifier: i32,
+        reach: Option<i32>,
+        target_type: TargetType,
+        damage: DamageField,
+        damage_type: Option<DamageType>,
+        description: String,
+    }
+
+    impl From<Melee> for super::Melee {
+        fn from(x: Melee) -> Self {
+            Self {
+                name: x.name,
+                modifier: x.modifier,
+                reach: x.reach,
+                target_type: x.target_type,
+                damage: into_list(x.damage, x.damage_type),
+                description: x.description,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct MeleeOrRanged {
+        name: String,
+        modifier: i32,
+        reach: Option<i32>,
+        range: Option<Range>,
+        target_type: TargetType,
+        damage: DamageField,
+        damage_type: Option<DamageType>,
+        description: String,
+    }
+
+    impl From<MeleeOrRanged> for super::MeleeOrRanged {
+        fn from(x: MeleeOrRanged) -> Self {
+            Self {
+                name: x.name,
+                modifier: x.modifier,
+                reach: x.reach,
+                range: x.range,
+                target_type: x.target_type,
+                damage: into_list(x.damage, x.damage_type),
+                description: x.description,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct Ranged {
+        name: String,
+        modifier: i32,
+        range: Option<Range>,
+        target_type: TargetType,
+        damage: DamageField,
+        damage_type: Option<DamageType>,
+        description: String,
+    }
+
+    impl From<Ranged> for super::Ranged {
+        fn from(x: Ranged) -> Self {
+            Self {
+                name: x.name,
+                modifier: x.modifier,
+                range: x.range,
+                target_type: x.target_type,
+                damage: into_list(x.damage, x.damage_type),
+                description: x.description,
+            }
+        }
+    }
+
+    // Written out instead of `#[serde(from)]` so the schema and TypeScript derives on the
+    // attacks only see the current shape.
+    macro_rules! deserialize_from {
+        ($($name:ident),*) => {
+            $(
+                impl<'de> Deserialize<'de> for super::$name {
+                    fn deserialize<D: serde::Deserializer<'de>>(
+                        deserializer: D,
+                    ) -> Result<Self, D::Error> {
+                        $name::deserialize(deserializer).map(Self::from)
+                    }
+                }
+            )*
+        };
+    }
+
+    deserialize_from!(Melee, MeleeOrRanged, Ranged);
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum TargetType {
     #[default]
     OneTarget,
@@ -143,36 +328,23 @@ impl fmt::Display for TargetType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
             TargetType::OneTarget => write!(f, "one target"),
-            TargetType::MultipleTargets(x) => write!(
-                f,
-                "{} targets",
-                num_to_words::integer_to_en_us(*x as i64).expect("Number of Targets")
-            ),
-            TargetType::Cone(x) => write!(
-                f,
-                "{} ft. Cone",
-                num_to_words::integer_to_en_us(*x as i64).expect("Number of Feet")
-            ),
-            TargetType::Line(x) => write!(
-                f,
-                "{} ft. Line",
-                num_to_words::integer_to_en_us(*x as i64).expect("Number of Feet")
-            ),
-            TargetType::Cube(x) => write!(
-                f,
-                "{} ft. Cube",
-                num_to_words::integer_to_en_us(*x as i64).expect("Number of Feet")
-            ),
-            TargetType::Sphere(x) => write!(
-                f,
-                "{} ft. Sphere",
-                num_to_words::integer_to_en_us(*x as i64).expect("Number of Feet")
-            ),
+            TargetType::MultipleTargets(x) => write!(f, "{} targets", words(*x)),
+            TargetType::Cone(x) => write!(f, "{} ft. Cone", words(*x)),
+            TargetType::Line(x) => write!(f, "{} ft. Line", words(*x)),
+            TargetType::Cube(x) => write!(f, "{} ft. Cube", words(*x)),
+            TargetType::Sphere(x) => write!(f, "{} ft. Sphere", words(*x)),
         }
     }
 }
 
+/// Spells out a number, falling back to digits for numbers that have no English words.
+fn words(x: i32) -> String {
+    num_to_words::integer_to_en_us(x as i64).unwrap_or_else(|_| x.to_string())
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Range {
     pub close_range: i32,
     pub long_range: i32,
@@ -183,3 +355,65 @@ impl fmt::Display for Range {
         write!(f, "{} ft. / {} ft.", self.close_range, self.long_range)
     }
 }
+
+mod test {
+    #[test]
+    fn test_attack_display_multiple_damage() {
+        use crate::{DamageType, Die, DieStat};
+
+        let attack = super::Attack::MeleeWeaponAttack(super::Melee {
+            name: String::from("Bite"),
+            modifier: 5,
+            reach: Some(5),
+            target_type: super::TargetType::OneTarget,
+            damage: vec![
+                super::Damage {
+                    damage: DieStat {
+                        die_count: 1,
+                        die_type: Die::D8,
+                        extra: 3,
+                    },
+                    damage_type: DamageType::Piercing,
+                    condition: None,
+                },
+                super::Damage {
+                    damage: DieStat {
+                        die_count: 2,
+                        die_type: Die::D6,
+                        extra: 0,
+                    },
+                    damage_type: DamageType::Poison,
+                    condition: Some(String::from("on a failed DC 13 Constitution saving throw")),
+                },
+            ],
+            description: String::new(),
+        });
+        assert_eq!(
+            attack.to_string(),
+            "Bite. Melee Weapon Attack: 5 to hit, reach 5, one target. Hit: 1d8 + 3 Piercing plus 2d6 + 0 Poison on a failed DC 13 Constitution saving throw. "
+        );
+        assert_eq!(attack.average_damage(), 7);
+        assert_eq!(attack.max_average_damage(), 14);
+        assert_eq!(super::TargetType::Cone(15).to_string(), "fifteen ft. Cone");
+        assert_eq!(
+            super::TargetType::Cone(-5).to_string(),
+            "minus five ft. Cone"
+        );
+    }
+
+    #[test]
+    fn test_attack_deserialize_legacy_damage() {
+        let legacy = r#"{"RangedWeaponAttack":{"name":"Shortbow","modifier":4,"range":{"close_range":80,"long_range":320},"target_type":"OneTarget","damage":{"die_count":1,"die_type":"D6","extra":2},"damage_type":"Piercing","description":""}}"#;
+        let attack: super::Attack = serde_json::from_str(legacy).unwrap();
+        assert_eq!(attack.damage().len(), 1);
+        assert_eq!(attack.damage()[0].damage_type, crate::DamageType::Piercing);
+        assert_eq!(attack.damage()[0].damage.extra, 2);
+
+        let json = serde_json::to_string(&attack).unwrap();
+        assert!(json.contains("\"damage\":[{"));
+        assert_eq!(
+            serde_json::from_str::<super::Attack>(&json).unwrap(),
+            attack
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use core::str::FromStr;
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{DamageType, DieStat, ParseError};
+
+use super::{Attack, Damage, Melee, MeleeOrRanged, Range, Ranged, TargetType};
+
+/// A line of attack text that could not be turned into an [`Attack`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub text: String,
+    pub error: ParseError,
+}
+
+/// Parses every non-empty line of `text` as an attack, collecting a [`Diagnostic`] for each
+/// line that isn't understood instead of failing the whole input.
+pub fn parse_attacks(text: &str) -> (Vec<Attack>, Vec<Diagnostic>) {
+    let mut attacks = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse::<Attack>() {
+            Ok(attack) => attacks.push(attack),
+            Err(error) => diagnostics.push(Diagnostic {
+                line: i + 1,
+                text: line.to_string(),
+                error,
+            }),
+        }
+    }
+    (attacks, diagnostics)
+}
+
+enum Kind {
+    MeleeWeapon,
+    RangedWeapon,
+    MeleeOrRangedWeapon,
+    MeleeSpell,
+    RangedSpell,
+}
+
+const KINDS: [(&str, Kind); 5] = [
+    ("Melee or Ranged Weapon Attack:", Kind::MeleeOrRangedWeapon),
+    ("Melee Weapon Attack:", Kind::MeleeWeapon),
+    ("Ranged Weapon Attack:", Kind::RangedWeapon),
+    ("Melee Spell Attack:", Kind::MeleeSpell),
+    ("Ranged Spell Attack:", Kind::RangedSpell),
+];
+
+/// Parses a stat-block attack such as
+/// `Bite. Melee Weapon Attack: +5 to hit, reach 5 ft., one target. Hit: 10 (2d6 + 3) piercing damage.`
+impl FromStr for Attack {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.replace(['*', '_'], "");
+        let line = line.trim();
+        let (start, header, kind) = KINDS
+            .iter()
+            .find_map(|(header, kind)| line.find(header).map(|i| (i, *header, kind)))
+            .ok_or_else(|| ParseError::new(s, "no attack type found"))?;
+
+        let name = line[..start]
+            .trim()
+            .trim_end_matches('.')
+            .trim()
+            .to_string();
+        if name.is_empty() {
+            return Err(ParseError::new(s, "missing attack name"));
+        }
+        let body = &line[start + header.len()..];
+        let (stats, hit) = match body.find("Hit:") {
+            Some(i) => (&body[..i], body[i + "Hit:".len()..].trim()),
+            None => return Err(ParseError::new(s, "missing \"Hit:\" section")),
+        };
+
+        let mut modifier = None;
+        let mut reach = None;
+        let mut range = None;
+        let mut target_type = None;
+        for part in stats.trim().trim_end_matches('.').split(", ") {
+            let part = part.trim();
+            if let Some(x) = part.strip_suffix("to hit") {
+                modifier = Some(parse_number(x.trim().trim_start_matches('+'), s)?);
+                continue;
+            }
+            let mut handled = false;
+            for piece in part.split(" or ") {
+                let piece = piece.trim();
+                if let Some(x) = piece.strip_prefix("reach") {
+                    reach = Some(parse_number(strip_feet(x), s)?);
+                    handled = true;
+                } else if let Some(x) = piece.strip_prefix("range") {
+                    range = Some(parse_range(x, s)?);
+                    handled = true;
+                }
+            }
+            if !handled {
+                target_type = Some(parse_target(part, s)?);
+            }
+        }
+        let modifier = modifier.ok_or_else(|| ParseError::new(s, "missing attack bonus"))?;
+        let target_type = target_type.unwrap_or_default();
+        let (damage, description) = parse_hit(hit, s)?;
+
+        Ok(match kind {
+            Kind::MeleeWeapon | Kind::MeleeSpell => {
+                let melee = Melee {
+                    name,
+                    modifier,
+                    reach,
+                    target_type,
+                    damage,
+                    description,
+                };
+                match kind {
+                    Kind::MeleeSpell => Attack::MeleeSpellAttack(melee),
+                    _ => Attack::MeleeWeaponAttack(melee),
+                }
+            }
+            Kind::RangedWeapon | Kind::RangedSpell => {
+                let ranged = Ranged {
+                    name,
+                    modifier,
+                    range,
+                    target_type,
+                    damage,
+                    description,
+                };
+                match kind {
+                    Kind::RangedSpell => Attack::RangedSpellAttack(ranged),
+                    _ => Attack::RangedWeaponAttack(ranged),
+                }
+            }
+            Kind::MeleeOrRangedWeapon => Attack::MeleeOrRangedWeaponAttack(MeleeOrRanged {
+                name,
+                modifier,
+                reach,
+                range,
+                target_type,
+                damage,
+                description,
+            }),
+        })
+    }
+}
+
+fn parse_number(text: &str, input: &str) -> Result<i32, ParseError> {
+    let text = text.trim();
+    if let Ok(x) = text.parse() {
+        return Ok(x);
+    }
+    number_words()
+        .get(&text.to_lowercase())
+        .copied()
+        .ok_or_else(|| ParseError::new(input, &format!("invalid number \"{}\"", text)))
+}
+
+/// The English words for 0 to 1000, built on first use.
+fn number_words() -> &'static HashMap<String, i32> {
+    static WORDS: OnceLock<HashMap<String, i32>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        (0..=1000)
+            .filter_map(|x| Some((num_to_words::integer_to_en_us(x as i64).ok()?, x)))
+            .collect()
+    })
+}
+
+fn strip_feet(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_suffix("ft.")
+        .or_else(|| text.strip_suffix("feet"))
+        .unwrap_or(text)
+        .trim()
+}
+
+fn parse_range(text: &str, input: &str) -> Result<Range, ParseError> {
+    let text = text.replace("ft.", "");
+    match text.split_once('/') {
+        Some((close, long)) => Ok(Range {
+            close_range: parse_number(close, input)?,
+            long_range: parse_number(long, input)?,
+        }),
+        None => {
+            let close_range = parse_number(&text, input)?;
+            Ok(Range {
+                close_range,
+                long_range: close_range,
+            })
+        }
+    }
+}
+
+fn parse_target(text: &str, input: &str) -> Result<TargetType, ParseError> {
+    let text = text.trim().trim_end_matches('.');
+    let lower = text.to_lowercase();
+    if lower.starts_with("one ") {
+        return Ok(TargetType::OneTarget);
+    }
+    for (suffix, shape) in [
+        ("cone", TargetType::Cone as fn(i32) -> TargetType),
+        ("line", TargetType::Line),
+        ("cube", TargetType::Cube),
+        ("sphere", TargetType::Sphere),
+    ] {
+        if let Some(size) = lower.strip_suffix(suffix) {
+            let size = size.trim().trim_end_matches("-foot");
+            return Ok(shape(parse_number(strip_feet(size), input)?));
+        }
+    }
+    let count = lower
+        .strip_suffix("targets")
+        .or_else(|| lower.strip_suffix("creatures"))
+        .ok_or_else(|| ParseError::new(input, &format!("unknown target \"{}\"", text)))?;
+    Ok(TargetType::MultipleTargets(parse_number(
+        count.trim().trim_start_matches("up to"),
+        input,
+    )?))
+}
+
+/// Splits the text after `Hit:` into damage components and the trailing description.
+fn parse_hit(text: &str, input: &str) -> Result<(Vec<Damage>, String), ParseError> {
+    let (sentence, rest) = match text.find(". ") {
+        Some(i) => (&text[..i], text[i + 2..].trim()),
+        None => (text.trim_end_matches('.'), ""),
+    };
+    let mut damage = Vec::new();
+    let mut spill = None;
+    for part in sentence.split(" plus ") {
+        let part = part.trim();
+        let (part, after_comma) = match part.split_once(", ") {
+            Some((part, after)) => (part, Some(after)),
+            None => (part, None),
+        };
+        match parse_damage(part) {
+            Some(x) => damage.push(x),
+            None if damage.is_empty() => return Ok((Vec::new(), text.to_string())),
+            None => return Err(ParseError::new(input, "invalid damage")),
+        }
+        if after_comma.is_some() {
+            spill = after_comma;
+            break;
+        }
+    }
+    let description = match spill {
+        Some(spill) if rest.is_empty() => format!("{}.", spill),
+        Some(spill) => format!("{}. {}", spill, rest),
+        None => rest.to_string(),
+    };
+    Ok((damage, description))
+}
+
+fn parse_damage(text: &str) -> Option<Damage> {
+    let (dice, rest) = match (text.find('('), text.find(')')) {
+        (Some(open), Some(close)) if open < close => {
+            (text[open + 1..close].to_string(), &text[close + 1..])
+        }
+        _ => {
+            let mut end = 0;
+            for word in text.split_whitespace() {
+                if !word
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, 'd' | '+' | '-'))
+                {
+                    break;
+                }
+                end += text[end..].find(word).unwrap_or(0) + word.len();
+            }
+            (text[..end].to_string(), &text[end..])
+        }
+    };
+    let damage = dice.parse::<DieStat>().ok()?;
+    let mut words = rest.split_whitespace();
+    let damage_type = words.next()?.parse::<DamageType>().ok()?;
+    let mut condition: Vec<&str> = words.collect();
+    if condition.first() == Some(&"damage") {
+        condition.remove(0);
+    }
+    Some(Damage {
+        damage,
+        damage_type,
+        condition: if condition.is_empty() {
+            None
+        } else {
+            Some(condition.join(" "))
+        },
+    })
+}
+
+mod test {
+    #[test]
+    fn test_parse_srd_attack() {
+        use crate::{DamageType, Die};
+
+        let attack: super::Attack = "Bite. Melee Weapon Attack: +5 to hit, reach 5 ft., one target. Hit: 10 (2d6 + 3) piercing damage plus 7 (2d6) poison damage on a failed DC 13 Constitution saving throw."
+            .parse()
+            .unwrap();
+        match &attack {
+            super::Attack::MeleeWeaponAttack(x) => {
+                assert_eq!(x.name, "Bite");
+                assert_eq!(x.modifier, 5);
+                assert_eq!(x.reach, Some(5));
+                assert_eq!(x.target_type, super::TargetType::OneTarget);
+                assert_eq!(x.description, "");
+            }
+            other => panic!("unexpected attack {:?}", other),
+        }
+        let damage = attack.damage();
+        assert_eq!(damage.len(), 2);
+        assert_eq!(damage[0].damage.die_count, 2);
+        assert_eq!(damage[0].damage.die_type, Die::D6);
+        assert_eq!(damage[0].damage.extra, 3);
+        assert_eq!(damage[0].damage_type, DamageType::Piercing);
+        assert_eq!(damage[1].damage_type, DamageType::Poison);
+        assert_eq!(
+            damage[1].condition.as_deref(),
+            Some("on a failed DC 13 Constitution saving throw")
+        );
+    }
+
+    #[test]
+    fn test_parse_melee_or_ranged_attack() {
+        let attack: super::Attack = "Javelin. Melee or Ranged Weapon Attack: +4 to hit, reach 5 ft. or range 30/120 ft., one target. Hit: 5 (1d6 + 2) piercing damage, and the target is pushed 5 feet."
+            .parse()
+            .unwrap();
+        match attack {
+            super::Attack::MeleeOrRangedWeaponAttack(x) => {
+                assert_eq!(x.reach, Some(5));
+                assert_eq!(
+                    x.range,
+                    Some(super::Range {
+                        close_range: 30,
+                        long_range: 120
+                    })
+                );
+                assert_eq!(x.description, "and the target is pushed 5 feet.");
+            }
+            other => panic!("unexpected attack {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_display_round_trip() {
+        let attack = super::Attack::RangedSpellAttack(super::Ranged {
+            name: String::from("Fire Bolt"),
+            modifier: 6,
+            range: Some(super::Range {
+                close_range: 120,
+                long_range: 120,
+            }),
+            target_type: super::TargetType::MultipleTargets(2),
+            damage: vec![super::Damage {
+                damage: crate::DieStat {
+                    die_count: 2,
+                    die_type: crate::Die::D10,
+                    extra: 0,
+                },
+                damage_type: crate::DamageType::Fire,
+                condition: None,
+            }],
+            description: String::from("The target ignites."),
+        });
+        assert_eq!(attack.to_string().parse::<super::Attack>(), Ok(attack));
+    }
+
+    #[test]
+    fn test_parse_attacks_reports_diagnostics() {
+        let (attacks, diagnostics) = super::parse_attacks(
+            "Claw. Melee Weapon Attack: +3 to hit, reach 5 ft., one target. Hit: 4 (1d4 + 2) slashing damage.\n\nMultiattack. The creature makes two attacks.\n",
+        );
+        assert_eq!(attacks.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 3);
+    }
+
+    #[test]
+    fn test_parse_damage_odd_input() {
+        use crate::{DamageType, DieStat};
+
+        let damage = super::parse_damage("1\u{3000}fire damage").unwrap();
+        assert_eq!(damage.damage.extra, 1);
+        assert_eq!(damage.damage_type, DamageType::Fire);
+        assert!(super::parse_damage("(2147483647+1) fire damage").is_none());
+        assert!("2147483647d6+1".parse::<DieStat>().is_err());
+        assert_eq!("1000d100+5".parse::<DieStat>().unwrap().value(), 50505);
+    }
+}
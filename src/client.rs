@@ -0,0 +1,346 @@
+//! A typed async client for the gamemstr REST API, available with the `client` feature.
+//!
+//! Every [`Resource`] lives under its own path, such as `/worlds` or `/spells`, and is sent
+//! and received as the JSON form of its type:
+//!
+//! | Request               | Method             |
+//! |-----------------------|--------------------|
+//! | `GET /<path>`         | [`Client::list`]   |
+//! | `GET /<path>/<id>`    | [`Client::get`]    |
+//! | `POST /<path>`        | [`Client::create`] |
+//! | `PUT /<path>/<id>`    | [`Client::update`] |
+//! | `DELETE /<path>/<id>` | [`Client::delete`] |
+//!
+//! Failed requests are returned as a [`ClientError`] that tells missing resources, rejected
+//! bodies and conflicts apart from other failures.
+//!
+//! With the `client-mock` feature, [`mock::MockApi`] serves these routes from memory for tests.
+
+use core::fmt;
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    creature::Creature,
+    id::{CampaignId, CreatureId, MapId, SessionId, WorldId},
+    item::Item,
+    spell::Spell,
+    world::{campaign::session::Session, campaign::Campaign, map::Map, World},
+};
+
+#[cfg(feature = "client-mock")]
+pub mod mock;
+
+/// A type the API serves under its own path.
+pub trait Resource: Serialize + DeserializeOwned {
+    /// The path of the collection, without slashes.
+    const PATH: &'static str;
+
+    type Id: fmt::Display;
+
+    fn resource_id(&self) -> Self::Id;
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    base: Url,
+    http: reqwest::Client,
+    token: Option<String>,
+}
+
+impl Client {
+    /// A client for the API at `base`, such as `https://api.gamemstr.com/v1`.
+    pub fn new(base: &str) -> Result<Self, ClientError> {
+        Self::with_http(base, reqwest::Client::new())
+    }
+
+    /// A client that sends its requests with the given HTTP client, for example one with
+    /// custom timeouts.
+    pub fn with_http(base: &str, http: reqwest::Client) -> Result<Self, ClientError> {
+        let base = Url::parse(base).map_err(|_| ClientError::InvalidUrl(base.to_string()))?;
+        if base.cannot_be_a_base() {
+            return Err(ClientError::InvalidUrl(base.to_string()));
+        }
+        Ok(Self {
+            base,
+            http,
+            token: None,
+        })
+    }
+
+    /// Sends the token as a bearer token with every request.
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub async fn list<T: Resource>(&self) -> Result<Vec<T>, ClientError> {
+        let response = self.request::<T>(Method::GET, None).send().await?;
+        read(check::<T>(response, None).await?).await
+    }
+
+    pub async fn get<T: Resource>(&self, id: &T::Id) -> Result<T, ClientError> {
+        let id = id.to_string();
+        let response = self.request::<T>(Method::GET, Some(&id)).send().await?;
+        read(check::<T>(response, Some(&id)).await?).await
+    }
+
+    /// Creates a resource, returning it as stored by the API.
+    pub async fn create<T: Resource>(&self, resource: &T) -> Result<T, ClientError> {
+        let request = self.request::<T>(Method::POST, None).json(resource);
+        read(check::<T>(request.send().await?, None).await?).await
+    }
+
+    /// Replaces the stored resource with the same id, returning it as stored by the API.
+    pub async fn update<T: Resource>(&self, resource: &T) -> Result<T, ClientError> {
+        let id = resource.resource_id().to_string();
+        let request = self.request::<T>(Method::PUT, Some(&id)).json(resource);
+        read(check::<T>(request.send().await?, Some(&id)).await?).await
+    }
+
+    pub async fn delete<T: Resource>(&self, id: &T::Id) -> Result<(), ClientError> {
+        let id = id.to_string();
+        let response = self.request::<T>(Method::DELETE, Some(&id)).send().await?;
+        check::<T>(response, Some(&id)).await?;
+        Ok(())
+    }
+
+    fn request<T: Resource>(&self, method: Method, id: Option<&str>) -> RequestBuilder {
+        let mut url = self.base.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().push(T::PATH);
+            segments.extend(id);
+        }
+        let request = self.http.request(method, url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Turns an unsuccessful response into the matching error.
+async fn check<T: Resource>(response: Response, id: Option<&str>) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(match (status, id) {
+        (StatusCode::NOT_FOUND, Some(id)) => ClientError::NotFound {
+            path: T::PATH,
+            id: id.to_string(),
+        },
+        (StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY, _) => {
+            ClientError::Rejected(message)
+        }
+        (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN, _) => ClientError::Unauthorized,
+        (StatusCode::CONFLICT, _) => ClientError::Conflict(message),
+        _ => ClientError::Status {
+            status: status.as_u16(),
+            message,
+        },
+    })
+}
+
+async fn read<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let body = response.bytes().await?;
+    serde_json::from_slice(&body).map_err(ClientError::Decode)
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    InvalidUrl(String),
+    /// The request could not be sent or its response could not be received.
+    Http(reqwest::Error),
+    /// The response body is not the JSON form of the expected type.
+    Decode(serde_json::Error),
+    NotFound {
+        path: &'static str,
+        id: String,
+    },
+    /// The API did not accept the request body.
+    Rejected(String),
+    Unauthorized,
+    /// The request conflicts with the stored state, such as a resource that already exists.
+    Conflict(String),
+    /// Any other unsuccessful status, with the body of the response.
+    Status {
+        status: u16,
+        message: String,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            ClientError::InvalidUrl(url) => write!(f, "invalid API url {}", url),
+            ClientError::Http(e) => write!(f, "{}", e),
+            ClientError::Decode(e) => write!(f, "unexpected response: {}", e),
+            ClientError::NotFound { path, id } => write!(f, "no {} with id {}", path, id),
+            ClientError::Rejected(message) => write!(f, "request rejected: {}", message),
+            ClientError::Unauthorized => write!(f, "not authorized"),
+            ClientError::Conflict(message) => write!(f, "conflict: {}", message),
+            ClientError::Status { status, message } => write!(f, "status {}: {}", status, message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
+
+impl Resource for World {
+    const PATH: &'static str = "worlds";
+
+    type Id = WorldId;
+
+    fn resource_id(&self) -> WorldId {
+        self.id
+    }
+}
+
+impl Resource for Campaign {
+    const PATH: &'static str = "campaigns";
+
+    type Id = CampaignId;
+
+    fn resource_id(&self) -> CampaignId {
+        self.id
+    }
+}
+
+impl Resource for Session {
+    const PATH: &'static str = "sessions";
+
+    type Id = SessionId;
+
+    fn resource_id(&self) -> SessionId {
+        self.id
+    }
+}
+
+impl Resource for Map {
+    const PATH: &'static str = "maps";
+
+    type Id = MapId;
+
+    fn resource_id(&self) -> MapId {
+        self.id
+    }
+}
+
+impl Resource for Creature {
+    const PATH: &'static str = "creatures";
+
+    type Id = CreatureId;
+
+    fn resource_id(&self) -> CreatureId {
+        *self.id()
+    }
+}
+
+impl Resource for Item {
+    const PATH: &'static str = "items";
+
+    type Id = String;
+
+    fn resource_id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+impl Resource for Spell {
+    const PATH: &'static str = "spells";
+
+    type Id = String;
+
+    fn resource_id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+mod test {
+    #[test]
+    fn test_client_crud() {
+        use super::{Client, ClientError};
+        use crate::{id::WorldId, world::World};
+        use wiremock::{
+            matchers::{body_json, header, method, path},
+            Mock, MockServer, ResponseTemplate,
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let server = MockServer::start().await;
+            let world = World::new(String::from("Greyhawk"), String::from("The Flanaess"));
+            let missing = WorldId::new();
+            let path_of = |id: &WorldId| format!("/v1/worlds/{}", id);
+            Mock::given(method("POST"))
+                .and(path("/v1/worlds"))
+                .and(header("authorization", "Bearer secret"))
+                .and(body_json(&world))
+                .respond_with(ResponseTemplate::new(201).set_body_json(&world))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(path_of(&world.id)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(&world))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/v1/worlds"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(vec![&world]))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(path_of(&missing)))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+            Mock::given(method("PUT"))
+                .and(path(path_of(&world.id)))
+                .respond_with(ResponseTemplate::new(409).set_body_string("stale revision"))
+                .mount(&server)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path(path_of(&world.id)))
+                .respond_with(ResponseTemplate::new(204))
+                .mount(&server)
+                .await;
+
+            let client = Client::new(&format!("{}/v1/", server.uri()))
+                .unwrap()
+                .with_token(String::from("secret"));
+            let created = client.create(&world).await.unwrap();
+            assert_eq!(created.id, world.id);
+            assert_eq!(
+                client.get::<World>(&world.id).await.unwrap().name,
+                "Greyhawk"
+            );
+            assert_eq!(client.list::<World>().await.unwrap().len(), 1);
+            client.delete::<World>(&world.id).await.unwrap();
+
+            assert!(matches!(
+                client.get::<World>(&missing).await,
+                Err(ClientError::NotFound { path: "worlds", id }) if id == missing.to_string()
+            ));
+            assert!(matches!(
+                client.update(&world).await,
+                Err(ClientError::Conflict(message)) if message == "stale revision"
+            ));
+            assert!(matches!(
+                Client::new("not a url"),
+                Err(ClientError::InvalidUrl(_))
+            ));
+        });
+    }
+}
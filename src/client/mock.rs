@@ -0,0 +1,162 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::Url;
+use serde_json::Value;
+use wiremock::{matchers::path_regex, Mock, MockServer, Request, ResponseTemplate};
+
+use super::{Client, Resource};
+
+/// An in-memory API server, for testing code built on the [`Client`] without the real API.
+///
+/// Each resource type it [`serve`](MockApi::serve)s is kept in its own store and answers the
+/// CRUD routes the way the API does: unknown ids are `404 Not Found`, bodies that are not the
+/// JSON form of the type are `422 Unprocessable Entity`, and creating an id twice is
+/// `409 Conflict`.
+pub struct MockApi {
+    server: MockServer,
+}
+
+impl MockApi {
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// The base URL of the API.
+    pub fn url(&self) -> String {
+        self.server.uri()
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new(&self.url()).expect("the mock server has a valid URL")
+    }
+
+    /// Mounts the CRUD routes of `T`, starting with the given resources.
+    pub async fn serve<T: Resource + 'static>(&self, resources: Vec<T>) {
+        let mut store = BTreeMap::new();
+        for resource in resources {
+            if let Ok(value) = serde_json::to_value(&resource) {
+                store.insert(segment(&resource.resource_id().to_string()), value);
+            }
+        }
+        let store = Arc::new(Mutex::new(store));
+        Mock::given(path_regex(format!("^/{}(/[^/]+)?$", T::PATH)))
+            .respond_with(move |request: &Request| respond::<T>(&store, request))
+            .mount(&self.server)
+            .await;
+    }
+}
+
+/// The id as it appears in a path, which is how the store is keyed.
+fn segment(id: &str) -> String {
+    let mut url = Url::parse("http://localhost/").expect("the URL is valid");
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.pop_if_empty().push(id);
+    }
+    url.path()[1..].to_string()
+}
+
+fn respond<T: Resource>(
+    store: &Mutex<BTreeMap<String, Value>>,
+    request: &Request,
+) -> ResponseTemplate {
+    let mut store = match store.lock() {
+        Ok(store) => store,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let id = request
+        .url
+        .path_segments()
+        .and_then(|mut x| x.nth(1))
+        .map(String::from);
+    let body = || {
+        serde_json::from_slice::<T>(&request.body).and_then(|x| {
+            Ok((
+                segment(&x.resource_id().to_string()),
+                serde_json::to_value(x)?,
+            ))
+        })
+    };
+    match (request.method.as_str(), id) {
+        ("GET", None) => {
+            ResponseTemplate::new(200).set_body_json(store.values().collect::<Vec<_>>())
+        }
+        ("GET", Some(id)) => match store.get(&id) {
+            Some(value) => ResponseTemplate::new(200).set_body_json(value),
+            None => ResponseTemplate::new(404),
+        },
+        ("POST", None) => match body() {
+            Err(e) => ResponseTemplate::new(422).set_body_string(e.to_string()),
+            Ok((id, _)) if store.contains_key(&id) => {
+                ResponseTemplate::new(409).set_body_string(format!("{} already exists", id))
+            }
+            Ok((id, value)) => {
+                let response = ResponseTemplate::new(201).set_body_json(&value);
+                store.insert(id, value);
+                response
+            }
+        },
+        ("PUT", Some(id)) => match body() {
+            Err(e) => ResponseTemplate::new(422).set_body_string(e.to_string()),
+            Ok((body_id, _)) if body_id != id => {
+                ResponseTemplate::new(400).set_body_string(format!("the body is {}", body_id))
+            }
+            Ok(_) if !store.contains_key(&id) => ResponseTemplate::new(404),
+            Ok((id, value)) => {
+                let response = ResponseTemplate::new(200).set_body_json(&value);
+                store.insert(id, value);
+                response
+            }
+        },
+        ("DELETE", Some(id)) => match store.remove(&id) {
+            Some(_) => ResponseTemplate::new(204),
+            None => ResponseTemplate::new(404),
+        },
+        _ => ResponseTemplate::new(405),
+    }
+}
+
+mod test {
+    #[test]
+    fn test_mock_api() {
+        use super::MockApi;
+        use crate::{client::ClientError, item::Item, world::World};
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let api = MockApi::start().await;
+            let greyhawk = World::new(String::from("Greyhawk"), String::from("The Flanaess"));
+            api.serve(vec![greyhawk.clone()]).await;
+            api.serve::<Item>(Vec::new()).await;
+            let client = api.client();
+
+            let mut eberron = World::new(String::from("Eberron"), String::new());
+            client.create(&eberron).await.unwrap();
+            assert!(matches!(
+                client.create(&eberron).await,
+                Err(ClientError::Conflict(_))
+            ));
+            eberron.description = String::from("Khorvaire");
+            client.update(&eberron).await.unwrap();
+            assert_eq!(
+                client.get::<World>(&eberron.id).await.unwrap().description,
+                "Khorvaire"
+            );
+            assert_eq!(client.list::<World>().await.unwrap().len(), 2);
+
+            client.delete::<World>(&greyhawk.id).await.unwrap();
+            assert!(matches!(
+                client.get::<World>(&greyhawk.id).await,
+                Err(ClientError::NotFound { path: "worlds", .. })
+            ));
+            assert!(client.list::<Item>().await.unwrap().is_empty());
+        });
+    }
+}
@@ -0,0 +1,654 @@
+//! Compendium packs for sharing bundles of creatures, items, spells and locations.
+//!
+//! A pack is a directory holding a `compendium.json` [`Manifest`] and the content files it
+//! lists. Each content file is a JSON object with optional `creatures`, `items`, `spells` and
+//! `locations` arrays. Inside the content, an entry that is part of the pack can be referred to
+//! by id with `{"$ref": "<id>"}` instead of being repeated, for example as the attached spell of
+//! an item, an item inside another item's inventory, or an NPC of a location.
+
+use core::fmt;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{creature::Creature, item::Item, spell::Spell, world::location::Location};
+
+/// The newest pack format this version of the library reads and writes.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "compendium.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub license: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// Content files, relative to the pack directory.
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl Manifest {
+    pub fn new(id: String, name: String, version: String, license: String) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            id,
+            name,
+            version,
+            description: String::new(),
+            authors: Vec::new(),
+            license,
+            homepage: None,
+            files: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CompendiumError {
+    Io(io::Error),
+    Json {
+        file: PathBuf,
+        error: serde_json::Error,
+    },
+    UnsupportedVersion(u32),
+    DuplicateId(String),
+    /// A content file of the manifest is not a relative path inside the pack directory.
+    InvalidPath(String),
+}
+
+impl fmt::Display for CompendiumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            CompendiumError::Io(e) => write!(f, "{}", e),
+            CompendiumError::Json { file, error } => write!(f, "{}: {}", file.display(), error),
+            CompendiumError::UnsupportedVersion(x) => write!(
+                f,
+                "pack format version {} is newer than the supported version {}",
+                x, FORMAT_VERSION
+            ),
+            CompendiumError::DuplicateId(x) => write!(f, "more than one entry has the id {}", x),
+            CompendiumError::InvalidPath(x) => write!(f, "{} is outside the pack", x),
+        }
+    }
+}
+
+impl std::error::Error for CompendiumError {}
+
+impl From<io::Error> for CompendiumError {
+    fn from(e: io::Error) -> Self {
+        CompendiumError::Io(e)
+    }
+}
+
+/// A `$ref` that did not lead to an entry of the pack. The reference is dropped on load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DanglingReference {
+    /// The name of the entry holding the reference.
+    pub entry: String,
+    pub field: String,
+    pub id: String,
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} refers to unknown id {}",
+            self.entry, self.field, self.id
+        )
+    }
+}
+
+/// The content file each entry of a [`Contents`] was read from.
+#[derive(Default)]
+struct Sources {
+    creatures: Vec<PathBuf>,
+    items: Vec<PathBuf>,
+    spells: Vec<PathBuf>,
+    locations: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Contents {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    creatures: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    items: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spells: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Compendium {
+    pub manifest: Manifest,
+    pub creatures: Vec<Creature>,
+    pub items: Vec<Item>,
+    pub spells: Vec<Spell>,
+    pub locations: Vec<Location>,
+}
+
+impl Compendium {
+    pub fn new(manifest: Manifest) -> Self {
+        Self {
+            manifest,
+            creatures: Vec::new(),
+            items: Vec::new(),
+            spells: Vec::new(),
+            locations: Vec::new(),
+        }
+    }
+
+    /// Reads a pack, resolving the references between its entries. References to ids that are
+    /// not in the pack, or that would place an item inside itself, are dropped and returned.
+    pub fn load(dir: &Path) -> Result<(Self, Vec<DanglingReference>), CompendiumError> {
+        let manifest: Manifest = read_json(&dir.join(MANIFEST_FILE))?;
+        if manifest.format_version > FORMAT_VERSION {
+            return Err(CompendiumError::UnsupportedVersion(manifest.format_version));
+        }
+
+        let mut raw = Contents::default();
+        let mut sources = Sources::default();
+        for file in &manifest.files {
+            let path = content_path(dir, file)?;
+            let contents: Contents = read_json(&path)?;
+            let from = |n: usize| std::iter::repeat_n(path.clone(), n);
+            sources.creatures.extend(from(contents.creatures.len()));
+            sources.items.extend(from(contents.items.len()));
+            sources.spells.extend(from(contents.spells.len()));
+            sources.locations.extend(from(contents.locations.len()));
+            raw.creatures.extend(contents.creatures);
+            raw.items.extend(contents.items);
+            raw.spells.extend(contents.spells);
+            raw.locations.extend(contents.locations);
+        }
+
+        let mut seen = BTreeSet::new();
+        for entry in raw
+            .creatures
+            .iter()
+            .chain(&raw.items)
+            .chain(&raw.spells)
+            .chain(&raw.locations)
+        {
+            let id = id_of(entry);
+            if !id.is_empty() && !seen.insert(id.to_string()) {
+                return Err(CompendiumError::DuplicateId(id.to_string()));
+            }
+        }
+
+        let mut resolver = Resolver {
+            creatures: index(&raw.creatures),
+            items: index(&raw.items),
+            spells: index(&raw.spells),
+            resolved: BTreeMap::new(),
+            dangling: Vec::new(),
+            open: Vec::new(),
+        };
+        let items: Vec<Value> = raw
+            .items
+            .iter()
+            .map(|x| match id_of(x) {
+                // Entries without an id cannot be referred to, so they are resolved on their own.
+                "" => resolver.item(x.clone()),
+                id => match resolver.item_by_id(id) {
+                    Some(item) => item,
+                    None => resolver.item(x.clone()),
+                },
+            })
+            .collect();
+        let locations: Vec<Value> = raw
+            .locations
+            .iter()
+            .map(|x| resolver.location(x.clone()))
+            .collect();
+        let dangling = resolver.dangling;
+
+        let compendium = Self {
+            creatures: from_values(&sources.creatures, raw.creatures)?,
+            items: from_values(&sources.items, items)?,
+            spells: from_values(&sources.spells, raw.spells)?,
+            locations: from_values(&sources.locations, locations)?,
+            manifest,
+        };
+        Ok((compendium, dangling))
+    }
+
+    /// Writes the manifest and one content file per kind of entry. Entries that are also part
+    /// of the pack are written as references where another entry contains them.
+    pub fn write(&self, dir: &Path) -> Result<(), CompendiumError> {
+        let creature_ids: BTreeSet<String> =
+            self.creatures.iter().map(|x| x.id().to_string()).collect();
+        let item_ids: BTreeSet<&str> = self.items.iter().map(|x| x.id.as_str()).collect();
+        let spell_ids: BTreeSet<&str> = self.spells.iter().map(|x| x.id.as_str()).collect();
+
+        let items = self
+            .items
+            .iter()
+            .map(|item| {
+                let mut value = to_value(item);
+                reference_item_fields(&mut value, &item_ids, &spell_ids);
+                value
+            })
+            .collect();
+        let locations = self
+            .locations
+            .iter()
+            .map(|location| {
+                let mut value = to_value(location);
+                if let Some(Value::Array(npcs)) = value.get_mut("npcs") {
+                    for npc in npcs {
+                        if creature_ids.contains(id_of(npc)) {
+                            *npc = reference(id_of(npc));
+                        }
+                    }
+                }
+                value
+            })
+            .collect();
+
+        let files = [
+            (
+                "creatures.json",
+                self.creatures.iter().map(to_value).collect(),
+            ),
+            ("items.json", items),
+            ("spells.json", self.spells.iter().map(to_value).collect()),
+            ("locations.json", locations),
+        ];
+        fs::create_dir_all(dir)?;
+        let mut manifest = self.manifest.clone();
+        manifest.format_version = FORMAT_VERSION;
+        manifest.files.clear();
+        for (file, entries) in files {
+            let entries: Vec<Value> = entries;
+            if entries.is_empty() {
+                continue;
+            }
+            let kind = file.trim_end_matches(".json");
+            let mut contents = Map::new();
+            contents.insert(kind.to_string(), Value::Array(entries));
+            write_json(&content_path(dir, file)?, &Value::Object(contents))?;
+            manifest.files.push(file.to_string());
+        }
+        write_json(&dir.join(MANIFEST_FILE), &manifest)
+    }
+}
+
+/// The path of a content file, which may only name files and directories inside the pack.
+fn content_path(dir: &Path, file: &str) -> Result<PathBuf, CompendiumError> {
+    let relative = Path::new(file);
+    let inside = relative
+        .components()
+        .all(|x| matches!(x, Component::Normal(_)));
+    match inside && relative.components().next().is_some() {
+        true => Ok(dir.join(relative)),
+        false => Err(CompendiumError::InvalidPath(file.to_string())),
+    }
+}
+
+/// Replaces the attached spell and inventory of an item with references where they are part of
+/// the pack, recursing into inventory items that stay embedded.
+fn reference_item_fields(value: &mut Value, item_ids: &BTreeSet<&str>, spell_ids: &BTreeSet<&str>) {
+    if let Some(spell) = value.get_mut("attached_spell") {
+        if spell_ids.contains(id_of(spell)) {
+            *spell = reference(id_of(spell));
+        }
+    }
+    if let Some(Value::Array(inventory)) = value.get_mut("inventory") {
+        for item in inventory {
+            if item_ids.contains(id_of(item)) {
+                *item = reference(id_of(item));
+            } else {
+                reference_item_fields(item, item_ids, spell_ids);
+            }
+        }
+    }
+}
+
+struct Resolver<'a> {
+    creatures: BTreeMap<&'a str, &'a Value>,
+    items: BTreeMap<&'a str, &'a Value>,
+    spells: BTreeMap<&'a str, &'a Value>,
+    /// Items that have been resolved already, so each one is only resolved and reported once.
+    resolved: BTreeMap<String, Value>,
+    dangling: Vec<DanglingReference>,
+    /// Ids of the items being resolved, to catch items that contain themselves.
+    open: Vec<String>,
+}
+
+impl Resolver<'_> {
+    fn item_by_id(&mut self, id: &str) -> Option<Value> {
+        if let Some(item) = self.resolved.get(id) {
+            return Some(item.clone());
+        }
+        let item = self.item((*self.items.get(id)?).clone());
+        self.resolved.insert(id.to_string(), item.clone());
+        Some(item)
+    }
+
+    fn item(&mut self, mut value: Value) -> Value {
+        let id = id_of(&value).to_string();
+        self.open.push(id);
+        let name = name_of(&value);
+        if let Some(target) = value.get("attached_spell").and_then(reference_id) {
+            value["attached_spell"] = match self.spells.get(target) {
+                Some(spell) => (*spell).clone(),
+                None => {
+                    self.dangle(&name, "attached_spell", target);
+                    Value::Null
+                }
+            };
+        }
+        if let Some(Value::Array(inventory)) = value.get_mut("inventory").map(Value::take) {
+            let mut resolved = Vec::new();
+            for entry in inventory {
+                match reference_id(&entry) {
+                    Some(target) if self.open.iter().any(|x| x == target) => {
+                        self.dangle(&name, "inventory", target)
+                    }
+                    Some(target) => match self.item_by_id(target) {
+                        Some(item) => resolved.push(item),
+                        None => self.dangle(&name, "inventory", target),
+                    },
+                    None => resolved.push(self.item(entry)),
+                }
+            }
+            value["inventory"] = Value::Array(resolved);
+        }
+        self.open.pop();
+        value
+    }
+
+    fn location(&mut self, mut value: Value) -> Value {
+        let name = name_of(&value);
+        if let Some(Value::Array(npcs)) = value.get_mut("npcs").map(Value::take) {
+            let mut resolved = Vec::new();
+            for entry in npcs {
+                match reference_id(&entry) {
+                    Some(target) => match self.creatures.get(target) {
+                        Some(creature) => resolved.push((*creature).clone()),
+                        None => self.dangle(&name, "npcs", target),
+                    },
+                    None => resolved.push(entry),
+                }
+            }
+            value["npcs"] = Value::Array(resolved);
+        }
+        value
+    }
+
+    fn dangle(&mut self, entry: &str, field: &str, id: &str) {
+        self.dangling.push(DanglingReference {
+            entry: entry.to_string(),
+            field: field.to_string(),
+            id: id.to_string(),
+        });
+    }
+}
+
+fn reference(id: &str) -> Value {
+    json!({ "$ref": id })
+}
+
+fn reference_id(value: &Value) -> Option<&str> {
+    value.get("$ref").and_then(Value::as_str)
+}
+
+fn id_of(value: &Value) -> &str {
+    value.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+fn name_of(value: &Value) -> String {
+    value
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The entries that can be referred to, by id.
+fn index(values: &[Value]) -> BTreeMap<&str, &Value> {
+    values
+        .iter()
+        .map(|x| (id_of(x), x))
+        .filter(|(id, _)| !id.is_empty())
+        .collect()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Deserializes entries, reporting errors against the file each entry came from.
+fn from_values<T: DeserializeOwned>(
+    files: &[PathBuf],
+    values: Vec<Value>,
+) -> Result<Vec<T>, CompendiumError> {
+    values
+        .into_iter()
+        .zip(files)
+        .map(|(x, file)| {
+            serde_json::from_value(x).map_err(|error| CompendiumError::Json {
+                file: file.clone(),
+                error,
+            })
+        })
+        .collect()
+}
+
+fn read_json<T: DeserializeOwned>(file: &Path) -> Result<T, CompendiumError> {
+    let text = fs::read_to_string(file)?;
+    serde_json::from_str(&text).map_err(|error| CompendiumError::Json {
+        file: file.to_path_buf(),
+        error,
+    })
+}
+
+fn write_json<T: Serialize>(file: &Path, value: &T) -> Result<(), CompendiumError> {
+    let text = serde_json::to_string_pretty(value).map_err(|error| CompendiumError::Json {
+        file: file.to_path_buf(),
+        error,
+    })?;
+    Ok(fs::write(file, text)?)
+}
+
+mod test {
+    #[test]
+    fn test_write_and_load() {
+        use crate::{
+            item::{Item, ItemRarity, ItemType},
+            spell::{CastingTime, Components, Duration, Spell, SpellLevel, SpellRange},
+        };
+
+        let spell = Spell {
+            id: String::from("fireball"),
+            name: String::from("Fireball"),
+            description: String::from("A bright streak flashes from your pointing finger."),
+            level: SpellLevel::Level3,
+            casting_time: CastingTime::Action,
+            duration: Duration::Instantaneous,
+            damage: None,
+            range: SpellRange::Range(150),
+            area: None,
+            damage_type: None,
+            components: Components::VSM,
+            attack_bonus: None,
+            save: None,
+        };
+        let item = |id: &str, name: &str| Item {
+            id: id.to_string(),
+            name: name.to_string(),
+            item_type: ItemType::WondrousItem,
+            rarity: ItemRarity::Rare,
+            attunement: None,
+            weapon_type: None,
+            armor_type: None,
+            conditions: None,
+            attached_spell: None,
+            has_charges: None,
+            inventory: None,
+            others: None,
+            actions: None,
+        };
+        let necklace = Item {
+            attached_spell: Some(spell.clone()),
+            ..item("necklace", "Necklace of Fireballs")
+        };
+        let bag = Item {
+            inventory: Some(vec![necklace.clone(), item("gem", "Loose Gem")]),
+            ..item("bag", "Bag of Holding")
+        };
+
+        let mut manifest = super::Manifest::new(
+            String::from("fiery-things"),
+            String::from("Fiery Things"),
+            String::from("1.0.0"),
+            String::from("CC-BY-4.0"),
+        );
+        manifest.authors.push(String::from("Jane Doe"));
+        let mut compendium = super::Compendium::new(manifest);
+        compendium.spells.push(spell);
+        compendium.items.push(necklace.clone());
+        compendium.items.push(bag.clone());
+
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        compendium.write(&dir).unwrap();
+        let items: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("items.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            items["items"][0]["attached_spell"],
+            serde_json::json!({ "$ref": "fireball" })
+        );
+        assert_eq!(
+            items["items"][1]["inventory"][0],
+            serde_json::json!({ "$ref": "necklace" })
+        );
+        assert_eq!(items["items"][1]["inventory"][1]["name"], "Loose Gem");
+
+        let (loaded, dangling) = super::Compendium::load(&dir).unwrap();
+        assert!(dangling.is_empty());
+        assert_eq!(loaded.manifest.authors, vec!["Jane Doe"]);
+        assert_eq!(loaded.manifest.files, vec!["items.json", "spells.json"]);
+        assert_eq!(loaded.items, vec![necklace, bag]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("compendium.json"),
+            r#"{"format_version": 1, "id": "broken", "name": "Broken", "version": "0.1.0",
+                "license": "MIT", "files": ["items.json"]}"#,
+        )
+        .unwrap();
+        let item = |id: &str, name: &str, extra: &str| {
+            format!(
+                r#"{{"id": "{}", "name": "{}", "item_type": "Ring", "rarity": "Rare",
+                    "attunement": null, "weapon_type": null, "armor_type": null,
+                    "conditions": null, "has_charges": null, "others": null,
+                    "actions": null, {}}}"#,
+                id, name, extra
+            )
+        };
+        std::fs::write(
+            dir.join("items.json"),
+            format!(
+                r#"{{"items": [{}, {}]}}"#,
+                item(
+                    "ring",
+                    "Ring of Spell Storing",
+                    r#""attached_spell": {"$ref": "missing-spell"}, "inventory": null"#
+                ),
+                item(
+                    "box",
+                    "Puzzle Box",
+                    r#""attached_spell": null, "inventory": [{"$ref": "ring"}, {"$ref": "box"}]"#
+                ),
+            ),
+        )
+        .unwrap();
+
+        let (loaded, dangling) = super::Compendium::load(&dir).unwrap();
+        let dangling: Vec<String> = dangling.iter().map(|x| x.to_string()).collect();
+        assert_eq!(
+            dangling,
+            vec![
+                "Ring of Spell Storing: attached_spell refers to unknown id missing-spell",
+                "Puzzle Box: inventory refers to unknown id box",
+            ]
+        );
+        assert_eq!(loaded.items[0].attached_spell, None);
+        let inventory = loaded.items[1].inventory.as_ref().unwrap();
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory[0].name, "Ring of Spell Storing");
+
+        // Items without an id are kept apart, and errors name the file the item came from.
+        std::fs::write(
+            dir.join("items.json"),
+            format!(
+                r#"{{"items": [{}, {}]}}"#,
+                item("", "First", r#""attached_spell": null, "inventory": null"#),
+                item("", "Second", r#""attached_spell": null, "inventory": null"#),
+            ),
+        )
+        .unwrap();
+        let (loaded, _) = super::Compendium::load(&dir).unwrap();
+        let names: Vec<&str> = loaded.items.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["First", "Second"]);
+        std::fs::write(
+            dir.join("items.json"),
+            format!(
+                r#"{{"items": [{}]}}"#,
+                item("x", "Broken", r#""attached_spell": 5, "inventory": null"#)
+            ),
+        )
+        .unwrap();
+        assert!(matches!(
+            super::Compendium::load(&dir),
+            Err(super::CompendiumError::Json { file, .. }) if file == dir.join("items.json")
+        ));
+
+        std::fs::write(
+            dir.join("compendium.json"),
+            r#"{"format_version": 2, "id": "broken", "name": "Broken", "version": "0.1.0",
+                "license": "MIT"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            super::Compendium::load(&dir),
+            Err(super::CompendiumError::UnsupportedVersion(2))
+        ));
+
+        for file in ["../items.json", "/etc/passwd", "./items.json", ""] {
+            let manifest = serde_json::json!({
+                "format_version": 1, "id": "escape", "name": "Escape", "version": "0.1.0",
+                "license": "MIT", "files": [file],
+            });
+            std::fs::write(dir.join("compendium.json"), manifest.to_string()).unwrap();
+            assert!(matches!(
+                super::Compendium::load(&dir),
+                Err(super::CompendiumError::InvalidPath(x)) if x == file
+            ));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,1091 @@
+//! Import and export adapters for content formats used by other tools.
+//!
+//! Every adapter returns a [`ConversionReport`] next to the converted value, listing the fields
+//! that had no counterpart on the other side or could not be understood.
+
+use core::{fmt, ops::RangeBounds};
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    action::{attack::Attack, Action, ActionType, SpecialAction},
+    creature::{
+        parse::Warning, CreatureType, Language, MonsterType, MovementSpeed, Sense, Size, Skill,
+        SkillType, Stat, StatType,
+    },
+    item::{ArmorType, Attuneable, Item, ItemRarity, ItemType, WeaponType},
+    render,
+    spell::{Area, CastingTime, Components, Duration, Save, SpellLevel, SpellRange},
+    world::campaign::player::{Class, Race},
+    Alignment, ConditionType, DamageType, Die, DieStat, OtherAttribute, ParseError,
+};
+
+pub mod fantasy_grounds;
+pub mod fivetools;
+pub mod foundry;
+pub mod open5e;
+pub mod roll20;
+pub mod srd;
+
+#[derive(Debug)]
+pub enum ConvertError {
+    Json(serde_json::Error),
+    NotAnObject,
+    MissingField(&'static str),
+    Invalid(ParseError),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            ConvertError::Json(e) => write!(f, "invalid JSON: {}", e),
+            ConvertError::NotAnObject => write!(f, "expected a JSON object"),
+            ConvertError::MissingField(x) => write!(f, "missing required field \"{}\"", x),
+            ConvertError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<ParseError> for ConvertError {
+    fn from(e: ParseError) -> Self {
+        ConvertError::Invalid(e)
+    }
+}
+
+impl From<serde_json::Error> for ConvertError {
+    fn from(e: serde_json::Error) -> Self {
+        ConvertError::Json(e)
+    }
+}
+
+/// A field that was dropped during a conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unmapped {
+    pub entity: String,
+    pub field: String,
+    pub value: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConversionReport {
+    pub unmapped: Vec<Unmapped>,
+}
+
+impl ConversionReport {
+    pub fn is_empty(&self) -> bool {
+        self.unmapped.is_empty()
+    }
+
+    pub fn push(&mut self, entity: &str, field: &str, value: impl ToString, reason: &str) {
+        self.unmapped.push(Unmapped {
+            entity: entity.to_string(),
+            field: field.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    pub fn extend(&mut self, other: ConversionReport) {
+        self.unmapped.extend(other.unmapped);
+    }
+
+    fn warnings(&mut self, entity: &str, field: &str, value: &str, warnings: Vec<Warning>) {
+        for warning in warnings {
+            self.push(entity, field, value, &warning.message);
+        }
+    }
+}
+
+/// The ability score fields of the SRD and Open5e formats, in stat block order.
+pub(crate) const ABILITIES: [(&str, StatType); 6] = [
+    ("strength", StatType::Strength),
+    ("dexterity", StatType::Dexterity),
+    ("constitution", StatType::Constitution),
+    ("intelligence", StatType::Intelligence),
+    ("wisdom", StatType::Wisdom),
+    ("charisma", StatType::Charisma),
+];
+
+/// Reads fields out of a JSON object, remembering which ones were used so that the rest can be
+/// reported as unmapped.
+pub(crate) struct Reader<'a> {
+    pub entity: String,
+    map: &'a Map<String, Value>,
+    used: BTreeSet<String>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(value: &'a Value) -> Result<Self, ConvertError> {
+        let map = value.as_object().ok_or(ConvertError::NotAnObject)?;
+        let entity = map
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or(ConvertError::MissingField("name"))?
+            .to_string();
+        Ok(Self {
+            entity,
+            map,
+            used: BTreeSet::from([String::from("name")]),
+        })
+    }
+
+    /// A reader that treats the given bookkeeping fields as used without reading them.
+    pub fn ignoring(value: &'a Value, fields: &[&str]) -> Result<Self, ConvertError> {
+        let mut reader = Self::new(value)?;
+        for key in fields {
+            reader.value(key);
+        }
+        Ok(reader)
+    }
+
+    /// Returns a field when it holds anything other than null or an empty value.
+    pub fn value(&mut self, key: &str) -> Option<&'a Value> {
+        self.used.insert(key.to_string());
+        self.map.get(key).filter(|x| !is_empty(x))
+    }
+
+    pub fn str(&mut self, key: &str) -> Option<&'a str> {
+        self.value(key).and_then(Value::as_str)
+    }
+
+    /// Reads an integer stored either as a number or as text like `"17 (natural armor)"`.
+    pub fn int(&mut self, key: &str) -> Option<i64> {
+        self.value(key).and_then(leading_int)
+    }
+
+    /// Reads an ability score, 10 when missing, clamping scores outside 1 to 30.
+    pub fn ability_score(&mut self, report: &mut ConversionReport, key: &str) -> i32 {
+        let score = self.int(key).unwrap_or(10);
+        let (min, max) = (*Stat::SCORES.start(), *Stat::SCORES.end());
+        if !(min as i64..=max as i64).contains(&score) {
+            report.push(&self.entity, key, score, "ability score clamped to 1 to 30");
+        }
+        score.clamp(min as i64, max as i64) as i32
+    }
+
+    pub fn size(&mut self, report: &mut ConversionReport) -> Size {
+        match self.str("size") {
+            Some(x) => x.parse().unwrap_or_else(|_| {
+                report.push(&self.entity, "size", x, "unknown size");
+                Size::default()
+            }),
+            None => Size::default(),
+        }
+    }
+
+    /// Reads a monster type like `dragon` from the `type` field.
+    pub fn creature_type(&mut self, report: &mut ConversionReport) -> CreatureType {
+        match self.str("type") {
+            Some(x) => match monster_type(x) {
+                Some(x) => CreatureType::Monster(x),
+                None => {
+                    report.push(&self.entity, "type", x, "unknown creature type");
+                    CreatureType::default()
+                }
+            },
+            None => CreatureType::default(),
+        }
+    }
+
+    pub fn alignment(&mut self, report: &mut ConversionReport) -> Alignment {
+        match self.str("alignment") {
+            Some(x) => x.parse().unwrap_or_else(|e: ParseError| {
+                report.push(&self.entity, "alignment", x, &e.message);
+                Alignment::default()
+            }),
+            None => Alignment::default(),
+        }
+    }
+
+    /// Reads a speed object like `{"walk": "30 ft.", "fly": 60, "hover": true}` as
+    /// `(kind, feet, hover)` entries for [`speeds`].
+    pub fn speed_map(&mut self, report: &mut ConversionReport) -> Vec<(&'a str, i64, bool)> {
+        let mut entries = Vec::new();
+        if let Some(Value::Object(map)) = self.value("speed") {
+            let hover = map
+                .get("hover")
+                .and_then(Value::as_bool)
+                .unwrap_or_default();
+            for (kind, feet) in map {
+                if kind == "hover" {
+                    continue;
+                }
+                match leading_int(feet) {
+                    Some(feet) => entries.push((kind.as_str(), feet, hover && kind == "fly")),
+                    None => report.push(&self.entity, "speed", feet, "invalid speed"),
+                }
+            }
+        }
+        entries
+    }
+
+    pub fn f64(&mut self, key: &str) -> Option<f64> {
+        self.value(key).and_then(|x| match x {
+            Value::Number(x) => x.as_f64(),
+            Value::String(x) => x.parse().ok(),
+            _ => None,
+        })
+    }
+
+    pub fn bool(&mut self, key: &str) -> bool {
+        self.value(key)
+            .map(|x| match x {
+                Value::Bool(x) => *x,
+                Value::String(x) => x.eq_ignore_ascii_case("yes") || x == "true",
+                _ => false,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Reads text stored either as a single string or as a list of paragraphs.
+    pub fn text(&mut self, key: &str) -> Option<String> {
+        self.value(key).map(|x| match x {
+            Value::Array(x) => x.iter().map(display).collect::<Vec<_>>().join("\n\n"),
+            other => display(other),
+        })
+    }
+
+    pub fn array(&mut self, key: &str) -> &'a [Value] {
+        self.value(key)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Marks a field as understood but without a place to go.
+    pub fn skip(&mut self, report: &mut ConversionReport, key: &str, reason: &str) {
+        if let Some(value) = self.value(key) {
+            report.push(&self.entity, key, display(value), reason);
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'a String> {
+        self.map.keys()
+    }
+
+    pub fn finish(self, report: &mut ConversionReport) {
+        for (key, value) in self.map {
+            if !self.used.contains(key) && !is_empty(value) {
+                report.push(&self.entity, key, display(value), "unknown field");
+            }
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(x) => x.trim().is_empty(),
+        Value::Array(x) => x.is_empty(),
+        Value::Object(x) => x.is_empty(),
+        _ => false,
+    }
+}
+
+pub(crate) fn display(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn leading_int(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(x) => x.as_i64().or_else(|| x.as_f64().map(|x| x as i64)),
+        Value::String(x) => {
+            let text = x.trim();
+            let sign = text.starts_with(['-', '+']) as usize;
+            let digits = text[sign..].find(|c: char| !c.is_ascii_digit());
+            text[..sign + digits.unwrap_or(text.len() - sign)]
+                .trim_start_matches('+')
+                .parse()
+                .ok()
+        }
+        _ => None,
+    }
+}
+
+/// Narrows an integer read from JSON to an `i32` within `range`, reporting it when it does not
+/// fit.
+pub(crate) fn narrow(
+    value: i64,
+    range: impl RangeBounds<i32>,
+    entity: &str,
+    field: &str,
+    report: &mut ConversionReport,
+) -> Option<i32> {
+    let narrowed = i32::try_from(value).ok().filter(|x| range.contains(x));
+    if narrowed.is_none() {
+        report.push(entity, field, value, "out of range");
+    }
+    narrowed
+}
+
+/// Reads creature types like `dragon` or `swarm of Tiny beasts`.
+pub(crate) fn monster_type(text: &str) -> Option<MonsterType> {
+    let word = text.split('(').next()?.split_whitespace().last()?;
+    word.parse()
+        .or_else(|_| word.trim_end_matches('s').parse())
+        .ok()
+}
+
+/// Turns a name into a lowercase, dash separated identifier like `young-red-dragon`.
+pub fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Formats dice the compact way JSON formats store them, e.g. `18d10+36`.
+pub(crate) fn compact_dice(die: &DieStat) -> String {
+    match (die.die_count, die.extra) {
+        (0, extra) => extra.to_string(),
+        (count, 0) => format!("{}{}", count, die.die_type),
+        (count, extra) => format!("{}{}{:+}", count, die.die_type, extra),
+    }
+}
+
+pub(crate) fn challenge_rating_from_f64(value: f64) -> String {
+    match value {
+        0.125 => String::from("1/8"),
+        0.25 => String::from("1/4"),
+        0.5 => String::from("1/2"),
+        x => (x as i64).to_string(),
+    }
+}
+
+pub(crate) fn spell_level(level: i64) -> Option<SpellLevel> {
+    match level {
+        0 => Some(SpellLevel::Cantrip),
+        1 => Some(SpellLevel::Level1),
+        2 => Some(SpellLevel::Level2),
+        3 => Some(SpellLevel::Level3),
+        4 => Some(SpellLevel::Level4),
+        5 => Some(SpellLevel::Level5),
+        6 => Some(SpellLevel::Level6),
+        7 => Some(SpellLevel::Level7),
+        8 => Some(SpellLevel::Level8),
+        9 => Some(SpellLevel::Level9),
+        _ => None,
+    }
+}
+
+pub(crate) fn spell_level_number(level: &SpellLevel) -> i64 {
+    match level {
+        SpellLevel::Cantrip => 0,
+        SpellLevel::Level1 => 1,
+        SpellLevel::Level2 => 2,
+        SpellLevel::Level3 => 3,
+        SpellLevel::Level4 => 4,
+        SpellLevel::Level5 => 5,
+        SpellLevel::Level6 => 6,
+        SpellLevel::Level7 => 7,
+        SpellLevel::Level8 => 8,
+        SpellLevel::Level9 => 9,
+    }
+}
+
+/// Parses casting times like `1 action`, `1 reaction, which you take when...` or `10 minutes`.
+/// Returns whether the amount fit the unit exactly.
+pub(crate) fn casting_time(text: &str) -> Option<(CastingTime, bool)> {
+    let lower = text.to_lowercase();
+    let first = lower.split(',').next().unwrap_or_default().trim();
+    let exact = first.starts_with("1 ");
+    let casting_time = if first.contains("bonus action") {
+        CastingTime::BonusAction
+    } else if first.contains("reaction") {
+        CastingTime::Reaction
+    } else if first.contains("action") {
+        CastingTime::Action
+    } else if first.contains("minute") {
+        CastingTime::Minute
+    } else if first.contains("hour") {
+        CastingTime::Hour
+    } else {
+        return None;
+    };
+    Some((casting_time, exact && first == lower.trim()))
+}
+
+pub(crate) fn casting_time_text(casting_time: &CastingTime) -> &'static str {
+    match casting_time {
+        CastingTime::Action => "1 action",
+        CastingTime::BonusAction => "1 bonus action",
+        CastingTime::Reaction => "1 reaction",
+        CastingTime::Minute => "1 minute",
+        CastingTime::Hour => "1 hour",
+    }
+}
+
+pub(crate) fn duration(text: &str, concentration: bool) -> Option<Duration> {
+    let lower = text.to_lowercase();
+    if concentration || lower.starts_with("concentration") {
+        return Some(Duration::Concentration);
+    }
+    Some(match lower.trim() {
+        "instantaneous" => Duration::Instantaneous,
+        "until dispelled" => Duration::UntilDispelled,
+        "until dispelled or triggered" => Duration::UntilDispelledOrTriggered,
+        "until triggered" => Duration::UntilTriggered,
+        x if x.ends_with("minute") || x.ends_with("minutes") => Duration::Minute,
+        x if x.ends_with("hour") || x.ends_with("hours") => Duration::Hour,
+        x if x.ends_with("day") || x.ends_with("days") => Duration::Day,
+        _ => return None,
+    })
+}
+
+pub(crate) fn duration_text(duration: &Duration) -> &'static str {
+    match duration {
+        Duration::Instantaneous => "Instantaneous",
+        Duration::Concentration => "Concentration, up to 1 minute",
+        Duration::Minute => "1 minute",
+        Duration::Hour => "1 hour",
+        Duration::Day => "24 hours",
+        Duration::UntilDispelled => "Until dispelled",
+        Duration::UntilDispelledOrTriggered => "Until dispelled or triggered",
+        Duration::UntilTriggered => "Until triggered",
+    }
+}
+
+/// Parses ranges like `Self (15-foot cone)`, `Touch`, `150 feet` or `1 mile`. Ranges too far to
+/// store are reported and kept as the farthest range there is.
+pub(crate) fn spell_range(
+    text: &str,
+    entity: &str,
+    report: &mut ConversionReport,
+) -> Option<(SpellRange, Option<Area>)> {
+    let lower = text.to_lowercase();
+    let (range, area) = match lower.split_once('(') {
+        Some((range, area)) => (range.trim(), area_text(area.trim_end_matches(')'))),
+        None => (lower.trim(), None),
+    };
+    let range = match range {
+        "self" => SpellRange::S,
+        "touch" => SpellRange::Touch,
+        x => {
+            let amount = leading_int(&Value::String(x.to_string()))?;
+            let feet = match x.contains("mile") {
+                true => amount.checked_mul(5280),
+                false => Some(amount),
+            };
+            match feet.and_then(|x| i32::try_from(x).ok()) {
+                Some(feet) => SpellRange::Range(feet),
+                None => {
+                    report.push(entity, "range", text, "range too far, kept as the farthest");
+                    SpellRange::Range(i32::MAX)
+                }
+            }
+        }
+    };
+    Some((range, area))
+}
+
+pub(crate) fn spell_range_text(range: &SpellRange, area: Option<&Area>) -> String {
+    let text = match range {
+        SpellRange::S => String::from("Self"),
+        SpellRange::Touch => String::from("Touch"),
+        SpellRange::Range(x) => format!("{} feet", x),
+    };
+    match (range, area) {
+        (SpellRange::S, Some(area)) => format!("{} ({})", text, area_phrase(area)),
+        _ => text,
+    }
+}
+
+/// Parses areas like `15-foot cone` or `20-foot-radius sphere`.
+pub(crate) fn area_text(text: &str) -> Option<Area> {
+    let size = i32::try_from(leading_int(&Value::String(text.trim().to_string()))?).ok()?;
+    area(text.split_whitespace().last()?, size)
+}
+
+pub(crate) fn area(shape: &str, size: i32) -> Option<Area> {
+    Some(match shape.to_lowercase().as_str() {
+        "cube" => Area::Cube(size),
+        "sphere" | "radius" => Area::Sphere(size),
+        "cone" => Area::Cone(size),
+        "line" => Area::Line(size),
+        "cylinder" => Area::Cylinder(size),
+        "wall" => Area::Wall(size),
+        _ => return None,
+    })
+}
+
+pub(crate) fn area_shape(area: &Area) -> (&'static str, i32) {
+    match area {
+        Area::Cube(x) => ("cube", *x),
+        Area::Sphere(x) => ("sphere", *x),
+        Area::Cone(x) => ("cone", *x),
+        Area::Line(x) => ("line", *x),
+        Area::Cylinder(x) => ("cylinder", *x),
+        Area::Wall(x) => ("wall", *x),
+    }
+}
+
+pub(crate) fn area_phrase(area: &Area) -> String {
+    let (shape, size) = area_shape(area);
+    format!("{}-foot {}", size, shape)
+}
+
+pub(crate) fn components(letters: &str) -> Option<Components> {
+    let upper = letters.to_uppercase();
+    let (v, s, m) = (
+        upper.contains('V'),
+        upper.contains('S'),
+        upper.contains('M'),
+    );
+    Some(match (v, s, m) {
+        (true, false, false) => Components::V,
+        (false, true, false) => Components::S,
+        (false, false, true) => Components::M,
+        (true, true, false) => Components::VS,
+        (true, false, true) => Components::VM,
+        (false, true, true) => Components::SM,
+        (true, true, true) => Components::VSM,
+        (false, false, false) => return None,
+    })
+}
+
+/// Finds the first `8d6 fire damage` style phrase in a description.
+pub(crate) fn damage_from_text(text: &str) -> Option<(DieStat, DamageType)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.iter().enumerate().find_map(|(i, word)| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if !word.contains('d') || !word.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let dice = word.parse::<DieStat>().ok()?;
+        let damage_type = words
+            .get(i + 1)?
+            .trim_matches(|c: char| !c.is_alphabetic())
+            .parse::<DamageType>()
+            .ok()?;
+        Some((dice, damage_type))
+    })
+}
+
+/// Finds the ability of the first `Dexterity saving throw` in a description.
+pub(crate) fn save_from_text(text: &str) -> Option<Save> {
+    let lower = text.to_lowercase();
+    let index = lower.find("saving throw")?;
+    let before = lower[..index].split_whitespace().last()?;
+    save(before, None)
+}
+
+pub(crate) fn save(ability: &str, dc: Option<i32>) -> Option<Save> {
+    Some(match ability.to_lowercase().as_str() {
+        "str" | "strength" => Save::Strength(dc),
+        "dex" | "dexterity" => Save::Dexterity(dc),
+        "con" | "constitution" => Save::Constitution(dc),
+        "int" | "intelligence" => Save::Intelligence(dc),
+        "wis" | "wisdom" => Save::Wisdom(dc),
+        "cha" | "charisma" => Save::Charisma(dc),
+        _ => return None,
+    })
+}
+
+pub(crate) fn save_parts(save: &Save) -> (&'static str, Option<i32>) {
+    match save {
+        Save::Strength(x) => ("str", *x),
+        Save::Dexterity(x) => ("dex", *x),
+        Save::Constitution(x) => ("con", *x),
+        Save::Intelligence(x) => ("int", *x),
+        Save::Wisdom(x) => ("wis", *x),
+        Save::Charisma(x) => ("cha", *x),
+    }
+}
+
+/// Parses item types like `Weapon (any sword)`, `Armor (shield)` or `Wondrous item`.
+pub(crate) fn item_type(text: &str) -> Option<(ItemType, Option<WeaponType>, Option<ArmorType>)> {
+    let lower = text.to_lowercase();
+    let kind = lower.split('(').next().unwrap_or_default().trim();
+    let item_type = match kind {
+        "armor" => ItemType::Armor,
+        "potion" => ItemType::Potion,
+        "ring" => ItemType::Ring,
+        "rod" => ItemType::Rod,
+        "scroll" => ItemType::Scroll,
+        "staff" => ItemType::Staff,
+        "wand" => ItemType::Wand,
+        "weapon" => ItemType::Weapon,
+        "wondrous item" | "wondrous items" => ItemType::WondrousItem,
+        _ => return None,
+    };
+    let weapon_type =
+        (item_type == ItemType::Weapon && lower.contains("sword")).then_some(WeaponType::Sword);
+    let armor_type =
+        (item_type == ItemType::Armor && lower.contains("shield")).then_some(ArmorType::Shield);
+    Some((item_type, weapon_type, armor_type))
+}
+
+pub(crate) fn item_type_text(
+    item_type: &ItemType,
+    weapon_type: Option<&WeaponType>,
+    armor_type: Option<&ArmorType>,
+) -> String {
+    let kind = match item_type {
+        ItemType::WondrousItem => String::from("Wondrous item"),
+        other => other.to_string(),
+    };
+    match (weapon_type, armor_type) {
+        (Some(x), _) => format!("{} ({})", kind, x.to_string().to_lowercase()),
+        (None, Some(x)) => format!("{} ({})", kind, x.to_string().to_lowercase()),
+        (None, None) => kind,
+    }
+}
+
+pub(crate) fn rarity(text: &str) -> Option<ItemRarity> {
+    Some(match text.trim().to_lowercase().as_str() {
+        "common" => ItemRarity::Common,
+        "uncommon" => ItemRarity::Uncommon,
+        "rare" => ItemRarity::Rare,
+        "very rare" => ItemRarity::VeryRare,
+        "legendary" => ItemRarity::Legendary,
+        "artifact" => ItemRarity::Artifact,
+        "varies" | "rarity varies" => ItemRarity::Varies,
+        "unknown" | "unknown rarity" => ItemRarity::Unknown,
+        _ => return None,
+    })
+}
+
+/// Parses `requires attunement` texts, keeping an alignment restriction when one is named.
+pub(crate) fn attunement(text: &str) -> Option<Attuneable> {
+    use strum::IntoEnumIterator;
+
+    let lower = text.to_lowercase();
+    if !lower.contains("attunement") {
+        return None;
+    }
+    let alignments: Vec<crate::Alignment> = crate::Alignment::iter()
+        .filter(|x| {
+            let name = x.to_string();
+            !name.starts_with("any") && !name.starts_with("typically") && lower.contains(&name)
+        })
+        .collect();
+    Some(Attuneable {
+        alignments: if alignments.is_empty() {
+            None
+        } else {
+            Some(alignments)
+        },
+    })
+}
+
+pub(crate) fn attunement_text(attunement: &Attuneable) -> String {
+    attunement
+        .to_string()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .to_string()
+}
+
+/// Picks the main speed out of `(kind, feet, hover)` entries, keeping the rest as other
+/// attributes the same way the stat block parser does.
+pub(crate) fn speeds(
+    entries: &[(&str, i64, bool)],
+    entity: &str,
+    report: &mut ConversionReport,
+) -> (MovementSpeed, Vec<OtherAttribute>) {
+    let mut speeds = Vec::new();
+    for (kind, feet, hover) in entries {
+        let text = match *kind {
+            "walk" => format!("{} ft.", feet),
+            kind => format!(
+                "{} {} ft.{}",
+                kind,
+                feet,
+                if *hover { " (hover)" } else { "" }
+            ),
+        };
+        match text.parse::<MovementSpeed>() {
+            Ok(speed) => speeds.push((speed, text)),
+            Err(e) => report.push(entity, "speed", &text, &e.message),
+        }
+    }
+    let main = speeds
+        .iter()
+        .position(|(x, _)| matches!(x, MovementSpeed::Walk(_)))
+        .unwrap_or_default();
+    if speeds.is_empty() {
+        return (MovementSpeed::default(), Vec::new());
+    }
+    let (speed, _) = speeds.remove(main);
+    let others = speeds
+        .into_iter()
+        .map(|(_, value)| OtherAttribute {
+            title: String::from("Speed"),
+            description: String::new(),
+            value,
+        })
+        .collect();
+    (speed, others)
+}
+
+/// Every speed of a creature as `(kind, feet, hover)` entries.
+pub(crate) fn speed_entries(
+    creature: &crate::creature::Creature,
+    report: &mut ConversionReport,
+) -> Vec<(&'static str, u8, bool)> {
+    let extra = creature
+        .others()
+        .into_iter()
+        .flatten()
+        .filter(|x| x.title == "Speed")
+        .filter_map(|x| match x.value.parse::<MovementSpeed>() {
+            Ok(speed) => Some(speed),
+            Err(e) => {
+                report.push(creature.name(), "speed", &x.value, &e.message);
+                None
+            }
+        });
+    std::iter::once(creature.speed().clone())
+        .chain(extra)
+        .map(|x| match x {
+            MovementSpeed::Walk(x) => ("walk", x, false),
+            MovementSpeed::Swim(x) => ("swim", x, false),
+            MovementSpeed::Fly { speed, hover } => ("fly", speed, hover),
+            MovementSpeed::Burrow(x) => ("burrow", x, false),
+            MovementSpeed::Climb(x) => ("climb", x, false),
+        })
+        .collect()
+}
+
+pub(crate) fn damage_types(
+    text: &str,
+    field: &str,
+    entity: &str,
+    report: &mut ConversionReport,
+) -> Option<Vec<DamageType>> {
+    let mut warnings = Vec::new();
+    let types = crate::creature::parse::parse_damage_types(text, 0, &mut warnings);
+    report.warnings(entity, field, text, warnings);
+    Some(types).filter(|x| !x.is_empty())
+}
+
+pub(crate) fn conditions<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    entity: &str,
+    report: &mut ConversionReport,
+) -> Option<Vec<ConditionType>> {
+    let mut conditions = Vec::new();
+    for name in names {
+        match name.trim().parse::<ConditionType>() {
+            Ok(x) => conditions.push(x),
+            Err(_) => report.push(entity, "condition_immunities", name, "unknown condition"),
+        }
+    }
+    Some(conditions).filter(|x| !x.is_empty())
+}
+
+/// Parses senses, dropping the passive Perception entry the creature works out itself.
+pub(crate) fn senses<'a>(
+    entries: impl IntoIterator<Item = &'a str>,
+    entity: &str,
+    report: &mut ConversionReport,
+) -> Option<Vec<Sense>> {
+    let mut senses = Vec::new();
+    for entry in entries {
+        if entry.to_lowercase().starts_with("passive perception") {
+            continue;
+        }
+        match entry.parse::<Sense>() {
+            Ok(x) => senses.push(x),
+            Err(e) => report.push(entity, "senses", entry, &e.message),
+        }
+    }
+    Some(senses).filter(|x| !x.is_empty())
+}
+
+pub(crate) fn languages(
+    text: &str,
+    entity: &str,
+    report: &mut ConversionReport,
+) -> Option<Vec<Language>> {
+    let mut languages = Vec::new();
+    for entry in crate::creature::parse::split_list(text) {
+        match entry.parse::<Language>() {
+            Ok(x) => languages.push(x),
+            Err(e) if entry != "—" && entry != "-" => {
+                report.push(entity, "languages", entry, &e.message)
+            }
+            Err(_) => {}
+        }
+    }
+    Some(languages).filter(|x| !x.is_empty())
+}
+
+/// A 64 bit FNV-1a hash, used to derive ids that stay the same across exports.
+pub(crate) fn stable_hash(seed: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in seed.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub(crate) fn race_text(race: &Race) -> &'static str {
+    match race {
+        Race::Human => "Human",
+        Race::Elf => "Elf",
+        Race::Dwarf => "Dwarf",
+        Race::Halfling => "Halfling",
+        Race::Gnome => "Gnome",
+        Race::HalfElf => "Half-Elf",
+        Race::HalfOrc => "Half-Orc",
+        Race::Tiefling => "Tiefling",
+    }
+}
+
+pub(crate) fn hit_die(class: &Class) -> Die {
+    match class {
+        Class::Barbarian => Die::D12,
+        Class::Fighter | Class::Paladin | Class::Ranger => Die::D10,
+        Class::Sorcerer | Class::Wizard => Die::D6,
+        _ => Die::D8,
+    }
+}
+
+/// The proficiency bonus of a character of the given level.
+pub(crate) fn proficiency_bonus(level: u8) -> i32 {
+    2 + (level.max(1) as i32 - 1) / 4
+}
+
+/// The ability a skill is rolled with, read from names like `Arcana (Int)`.
+pub(crate) fn skill_ability(skill_type: &SkillType) -> StatType {
+    skill_type
+        .to_string()
+        .split_once('(')
+        .and_then(|(_, x)| x.trim_end_matches(')').parse().ok())
+        .unwrap_or(StatType::Dexterity)
+}
+
+/// How many times the proficiency bonus went into a skill modifier: 0, 0.5, 1 or 2 for
+/// expertise. Modifiers that fall between those levels are rounded down and reported.
+pub(crate) fn skill_proficiency(
+    creature: &crate::creature::Creature,
+    skill: &Skill,
+    proficiency: i32,
+    report: &mut ConversionReport,
+) -> f64 {
+    let ability = skill_ability(&skill.skill_type);
+    let stat = creature
+        .stat(&ability)
+        .map(|x| x.modifier)
+        .unwrap_or_default();
+    let Some(bonus) = skill.modifier.checked_sub(stat) else {
+        report.push(
+            creature.name(),
+            "skills",
+            render::skill(skill),
+            "modifier out of range",
+        );
+        return 0.0;
+    };
+    let level = match bonus {
+        x if x >= 2 * proficiency => 2.0,
+        x if x >= proficiency => 1.0,
+        x if x > 0 => 0.5,
+        _ => 0.0,
+    };
+    if bonus != (level * proficiency as f64).floor() as i32 {
+        report.push(
+            creature.name(),
+            "skills",
+            render::skill(skill),
+            "rounded down to a proficiency level",
+        );
+    }
+    level
+}
+
+/// The ability an attack is made with: Dexterity for ranged weapons, the best mental ability
+/// for spells and Strength for everything else.
+pub(crate) fn attack_ability(attack: &Attack, creature: &crate::creature::Creature) -> StatType {
+    match attack {
+        Attack::RangedWeaponAttack(_) => StatType::Dexterity,
+        Attack::MeleeSpellAttack(_) | Attack::RangedSpellAttack(_) => {
+            [StatType::Intelligence, StatType::Wisdom, StatType::Charisma]
+                .into_iter()
+                .max_by_key(|x| creature.stat(x).map(|x| x.modifier).unwrap_or_default())
+                .unwrap_or(StatType::Charisma)
+        }
+        _ => StatType::Strength,
+    }
+}
+
+/// Turns a named action into an attack when its text reads like one.
+pub(crate) fn action(name: &str, description: &str) -> Action {
+    match format!("{}. {}", name, description).parse::<Attack>() {
+        Ok(attack) => Action::new(ActionType::Attack(attack)),
+        Err(_) => Action::new(ActionType::Special(special(name, description))),
+    }
+}
+
+pub(crate) fn special(name: &str, description: &str) -> SpecialAction {
+    SpecialAction {
+        name: name.to_string(),
+        description: description.to_string(),
+    }
+}
+
+/// The name and stat block text of an action.
+pub(crate) fn action_text(action: &ActionType) -> (String, String) {
+    match action {
+        ActionType::Attack(attack) => {
+            let text = render::attack_text(attack);
+            let mut description = format!("{}: {} Hit: {}.", text.kind, text.details, text.hit);
+            if !text.description.is_empty() {
+                description.push(' ');
+                description.push_str(&text.description);
+            }
+            (text.name, description)
+        }
+        ActionType::Special(x) | ActionType::Reaction(x) | ActionType::LegendaryAction(x) => {
+            (x.name.clone(), x.description.clone())
+        }
+    }
+}
+
+/// Attributes that are kept on the creature but have no place in an export.
+pub(crate) fn report_extra_attributes(
+    creature: &crate::creature::Creature,
+    report: &mut ConversionReport,
+) {
+    for other in render::extra_attributes(creature) {
+        report.push(creature.name(), "others", other, "no matching field");
+    }
+    if let Some(lair) = creature.lair() {
+        report.push(creature.name(), "lair", &lair.name, "no matching field");
+    }
+}
+
+/// Item fields that none of the supported formats can hold.
+pub(crate) fn report_item_extras(item: &Item, report: &mut ConversionReport) {
+    if let Some(conditions) = &item.conditions {
+        report.push(
+            &item.name,
+            "conditions",
+            render::list(conditions),
+            "no matching field",
+        );
+    }
+    if let Some(spell) = &item.attached_spell {
+        report.push(
+            &item.name,
+            "attached_spell",
+            &spell.name,
+            "no matching field",
+        );
+    }
+    if let Some(charges) = &item.has_charges {
+        report.push(&item.name, "has_charges", charges, "no matching field");
+    }
+    for inner in item.inventory.iter().flatten() {
+        report.push(&item.name, "inventory", &inner.name, "no matching field");
+    }
+    for action in item.actions.iter().flatten() {
+        report.push(
+            &item.name,
+            "actions",
+            action_text(&action.action).0,
+            "no matching field",
+        );
+    }
+}
+
+mod test {
+    #[test]
+    fn test_export_extreme_skill_modifier() {
+        use crate::creature::{Skill, SkillType};
+
+        let mut player = tessa();
+        player.set_skills(Some(vec![Skill {
+            skill_type: SkillType::Arcana,
+            modifier: i32::MIN,
+        }]));
+        let (_, report) = super::roll20::export_player(&player);
+        assert!(report.unmapped.iter().any(|x| x.field == "skills"));
+        let (_, report) = super::foundry::export_player(&player);
+        assert!(report.unmapped.iter().any(|x| x.field == "skills"));
+        let (_, report) = super::fantasy_grounds::export_player(&player);
+        assert!(report.unmapped.iter().any(|x| x.field == "skills"));
+    }
+
+    /// A level 5 elf wizard with a wand and the Shield spell, exported by the tests of each
+    /// virtual tabletop.
+    #[cfg(test)]
+    pub(crate) fn tessa() -> crate::world::campaign::player::Player {
+        use crate::{
+            creature::CreatureType,
+            id::CampaignId,
+            item::{Item, ItemRarity, ItemType},
+            spell::{CastingTime, Components, Duration, Spell, SpellLevel, SpellRange},
+            world::campaign::player::{Class, Player, Race},
+        };
+
+        let (mut creature, _) = crate::creature::parse::parse_stat_block(
+            "Tessa
+Medium humanoid, chaotic good
+Armor Class 12
+Hit Points 27 (5d8 + 5)
+Speed 30 ft.
+STR DEX CON INT WIS CHA
+8 (-1) 14 (+2) 12 (+1) 18 (+4) 10 (+0) 10 (+0)
+Saving Throws Int +7, Wis +3
+Skills Arcana +7, History +10
+Senses darkvision 60 ft.
+Languages Common, Elvish, Draconic
+Fey Ancestry. Tessa has advantage on saving throws against being charmed.
+Actions
+Quarterstaff. Melee Weapon Attack: +1 to hit, reach 5 ft., one target. Hit: 2 (1d6 - 1) bludgeoning damage.
+",
+        );
+        creature.set_creature_type(CreatureType::Player);
+        let mut player = Player::try_new(
+            creature,
+            Race::Elf,
+            Class::Wizard,
+            5,
+            6500,
+            CampaignId::new(),
+        )
+        .unwrap();
+        player.add_item(Item {
+            id: String::from("wand"),
+            name: String::from("Wand of Magic Missiles"),
+            item_type: ItemType::Wand,
+            rarity: ItemRarity::Uncommon,
+            attunement: None,
+            weapon_type: None,
+            armor_type: None,
+            conditions: None,
+            attached_spell: None,
+            has_charges: None,
+            inventory: None,
+            others: None,
+            actions: None,
+        });
+        player.add_spell(Spell {
+            id: String::from("shield"),
+            name: String::from("Shield"),
+            description: String::from("An invisible barrier of magical force appears."),
+            level: SpellLevel::Level1,
+            casting_time: CastingTime::Reaction,
+            duration: Duration::Instantaneous,
+            damage: None,
+            range: SpellRange::S,
+            area: None,
+            damage_type: None,
+            components: Components::VS,
+            attack_bonus: None,
+            save: None,
+        });
+        player
+    }
+}
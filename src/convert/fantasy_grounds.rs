@@ -0,0 +1,512 @@
+//! Fantasy Grounds character sheets for the 5E ruleset.
+//!
+//! [`export_player`] writes the `<character>` XML that Fantasy Grounds produces when exporting a
+//! character, which can be imported again by dragging the file onto the character selection.
+
+use crate::{
+    action::{attack::Attack, ActionType},
+    creature::StatType,
+    item::{Item, ItemType},
+    render::{self, html::escape},
+    spell::Spell,
+    world::campaign::player::Player,
+};
+
+use super::{
+    attack_ability, attunement_text, casting_time_text, duration_text, hit_die, item_type_text,
+    proficiency_bonus, race_text, report_item_extras, skill_ability, skill_proficiency,
+    spell_level_number, spell_range_text, ConversionReport,
+};
+
+/// One element of the exported document. Text is escaped when the element is written.
+struct Element {
+    name: String,
+    kind: Option<&'static str>,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: None,
+            text: String::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn value(name: &str, kind: &'static str, text: impl ToString) -> Self {
+        Self {
+            kind: Some(kind),
+            text: text.to_string(),
+            ..Self::new(name)
+        }
+    }
+
+    fn string(name: &str, text: impl ToString) -> Self {
+        Self::value(name, "string", text)
+    }
+
+    fn number(name: &str, number: impl ToString) -> Self {
+        Self::value(name, "number", number)
+    }
+
+    /// Formatted text, with a `<p>` for every paragraph.
+    fn formatted(name: &str, text: &str) -> Self {
+        Self {
+            kind: Some("formattedtext"),
+            children: text
+                .split("\n\n")
+                .filter(|x| !x.trim().is_empty())
+                .map(|x| Element {
+                    text: x.trim().to_string(),
+                    ..Element::new("p")
+                })
+                .collect(),
+            ..Self::new(name)
+        }
+    }
+
+    fn with(mut self, children: Vec<Element>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// A list of records, each keyed by an `id-00001` style element.
+    fn list(name: &str, records: Vec<Vec<Element>>) -> Self {
+        Self::new(name).with(
+            records
+                .into_iter()
+                .enumerate()
+                .map(|(i, fields)| Element::new(&format!("id-{:05}", i + 1)).with(fields))
+                .collect(),
+        )
+    }
+
+    fn write(&self, output: &mut String, depth: usize) {
+        let indent = "\t".repeat(depth);
+        output.push_str(&indent);
+        output.push('<');
+        output.push_str(&self.name);
+        if let Some(kind) = self.kind {
+            output.push_str(&format!(" type=\"{}\"", kind));
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            output.push_str(" />\n");
+            return;
+        }
+        output.push('>');
+        if self.children.is_empty() {
+            output.push_str(&escape(&self.text));
+        } else {
+            output.push('\n');
+            for child in &self.children {
+                child.write(output, depth + 1);
+            }
+            output.push_str(&indent);
+        }
+        output.push_str(&format!("</{}>\n", self.name));
+    }
+}
+
+fn dice(die_count: i32, die: &crate::Die) -> String {
+    vec![die.to_string(); die_count.max(0) as usize].join(",")
+}
+
+fn abilities(player: &Player, proficiency: i32) -> Element {
+    use strum::IntoEnumIterator;
+
+    let creature = player.creature();
+    Element::new("abilities").with(
+        StatType::iter()
+            .map(|stat_type| {
+                let stat = creature.stat(&stat_type);
+                let modifier = stat.map(|x| x.modifier).unwrap_or_default();
+                let proficient = creature
+                    .saving_throws()
+                    .into_iter()
+                    .flatten()
+                    .any(|x| x.stat_type == stat_type);
+                let save = modifier + if proficient { proficiency } else { 0 };
+                Element::new(&stat_type.to_string().to_lowercase()).with(vec![
+                    Element::number("bonus", modifier),
+                    Element::number("save", save),
+                    Element::number("saveprof", proficient as i32),
+                    Element::number("score", stat.map(|x| x.value).unwrap_or(10)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn skills(player: &Player, proficiency: i32, report: &mut ConversionReport) -> Element {
+    let creature = player.creature();
+    let records = creature
+        .skills()
+        .into_iter()
+        .flatten()
+        .map(|skill| {
+            let name = skill.skill_type.to_string();
+            let level = skill_proficiency(creature, skill, proficiency, report);
+            // The sheet works the total out from the stat and proficiency, anything on top is misc.
+            let ability = skill_ability(&skill.skill_type);
+            let misc = skill
+                .modifier
+                .saturating_sub(
+                    creature
+                        .stat(&ability)
+                        .map(|x| x.modifier)
+                        .unwrap_or_default(),
+                )
+                .saturating_sub((level * proficiency as f64).floor() as i32);
+            vec![
+                Element::number("misc", misc),
+                Element::string("name", name.split(" (").next().unwrap_or(&name)),
+                Element::number(
+                    "prof",
+                    match level {
+                        x if x >= 2.0 => 2,
+                        x if x >= 1.0 => 1,
+                        x if x > 0.0 => 3,
+                        _ => 0,
+                    },
+                ),
+                Element::string("stat", ability.to_string().to_lowercase()),
+            ]
+        })
+        .collect();
+    Element::list("skilllist", records)
+}
+
+fn weapon(attack: &Attack, player: &Player, proficiency: i32) -> Vec<Element> {
+    let creature = player.creature();
+    let ability = attack_ability(attack, creature);
+    let modifier = creature
+        .stat(&ability)
+        .map(|x| x.modifier)
+        .unwrap_or_default();
+    let damage = attack
+        .damage()
+        .iter()
+        .filter(|x| x.condition.is_none())
+        .enumerate()
+        .map(|(i, damage)| {
+            vec![
+                Element::number(
+                    "bonus",
+                    damage.damage.extra - if i == 0 { modifier } else { 0 },
+                ),
+                Element::value(
+                    "dice",
+                    "dice",
+                    dice(damage.damage.die_count, &damage.damage.die_type),
+                ),
+                Element::string("stat", if i == 0 { "base" } else { "" }),
+                Element::string("type", damage.damage_type.to_string().to_lowercase()),
+            ]
+        })
+        .collect();
+    let ranged = matches!(
+        attack,
+        Attack::RangedWeaponAttack(_) | Attack::RangedSpellAttack(_)
+    );
+    vec![
+        Element::number(
+            "attackbonus",
+            attack
+                .modifier()
+                .saturating_sub(modifier)
+                .saturating_sub(proficiency),
+        ),
+        Element::string("attackstat", ability.to_string().to_lowercase()),
+        Element::list("damagelist", damage),
+        Element::string("name", render::attack_text(attack).name),
+        Element::number("prof", 1),
+        Element::number("type", ranged as i32),
+    ]
+}
+
+fn item(item: &Item, report: &mut ConversionReport) -> Vec<Element> {
+    report_item_extras(item, report);
+    let description = item
+        .others
+        .iter()
+        .flatten()
+        .filter(|x| x.title == "Description")
+        .map(|x| x.description.clone())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let kind = match &item.item_type {
+        ItemType::WondrousItem => String::from("Wondrous Item"),
+        other => other.to_string(),
+    };
+    let subtype = item_type_text(
+        &item.item_type,
+        item.weapon_type.as_ref(),
+        item.armor_type.as_ref(),
+    );
+    let mut rarity = item.rarity.to_string();
+    if let Some(attunement) = &item.attunement {
+        rarity.push_str(&format!(" ({})", attunement_text(attunement)));
+    }
+    vec![
+        Element::number("carried", 1),
+        Element::number("count", 1),
+        Element::formatted("description", &description),
+        Element::number("isidentified", 1),
+        Element::string("name", &item.name),
+        Element::string("rarity", rarity),
+        Element::string(
+            "subtype",
+            subtype
+                .split_once('(')
+                .map(|(_, x)| x.trim_end_matches(')'))
+                .unwrap_or_default(),
+        ),
+        Element::string("type", kind),
+    ]
+}
+
+fn power(spell: &Spell, report: &mut ConversionReport) -> Vec<Element> {
+    // Fantasy Grounds builds the roll actions of a power by parsing its description.
+    if let Some(damage) = &spell.damage {
+        report.push(
+            &spell.name,
+            "damage",
+            damage,
+            "left to the description parser",
+        );
+    }
+    if let Some(save) = &spell.save {
+        report.push(&spell.name, "save", save, "left to the description parser");
+    }
+    if let Some(bonus) = spell.attack_bonus {
+        report.push(
+            &spell.name,
+            "attack_bonus",
+            bonus,
+            "left to the description parser",
+        );
+    }
+    vec![
+        Element::string("castingtime", casting_time_text(&spell.casting_time)),
+        Element::string("components", &spell.components),
+        Element::formatted("description", &spell.description),
+        Element::string("duration", duration_text(&spell.duration)),
+        Element::string("group", "Spells"),
+        Element::number("level", spell_level_number(&spell.level)),
+        Element::string("name", &spell.name),
+        Element::string("range", spell_range_text(&spell.range, spell.area.as_ref())),
+    ]
+}
+
+/// Exports a player as a Fantasy Grounds 5E character file.
+pub fn export_player(player: &Player) -> (String, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let creature = player.creature();
+    let proficiency = proficiency_bonus(*player.level());
+
+    let mut weapons = Vec::new();
+    let mut features = Vec::new();
+    for action in creature.actions().into_iter().flatten() {
+        match &action.action {
+            ActionType::Attack(attack) => {
+                for damage in attack.damage().iter().filter(|x| x.condition.is_some()) {
+                    report.push(
+                        player.name(),
+                        "damage.condition",
+                        damage,
+                        "no matching field",
+                    );
+                }
+                weapons.push(weapon(attack, player, proficiency));
+            }
+            ActionType::Special(x) | ActionType::Reaction(x) | ActionType::LegendaryAction(x) => {
+                features.push(vec![
+                    Element::string("name", &x.name),
+                    Element::formatted("text", &x.description),
+                ])
+            }
+        }
+    }
+    super::report_extra_attributes(creature, &mut report);
+
+    let traits = creature
+        .racial_traits()
+        .into_iter()
+        .flatten()
+        .map(|x| {
+            vec![
+                Element::string("name", &x.name),
+                Element::formatted("text", &x.description),
+            ]
+        })
+        .collect();
+    let languages = creature
+        .languages()
+        .into_iter()
+        .flatten()
+        .map(|x| vec![Element::string("name", x)])
+        .collect();
+    let inventory = player
+        .inventory()
+        .iter()
+        .map(|x| item(x, &mut report))
+        .collect();
+    let powers = player
+        .spells()
+        .iter()
+        .map(|x| power(x, &mut report))
+        .collect();
+    let speed = match creature.speed() {
+        crate::creature::MovementSpeed::Walk(x) => *x,
+        other => {
+            report.push(player.name(), "speed", other, "only walking speed is kept");
+            0
+        }
+    };
+    let resistances = |name: &str, values: Option<&Vec<crate::DamageType>>| {
+        values.map(|x| Element::string(name, render::lowercase_list(x)))
+    };
+    let health = &creature.health_points().health;
+
+    let mut fields = vec![
+        abilities(player, proficiency),
+        Element::string("alignment", creature.alignment()),
+        Element::list(
+            "classes",
+            vec![vec![
+                Element::value("hddie", "dice", hit_die(player.class())),
+                Element::number("level", player.level()),
+                Element::string("name", player.class()),
+            ]],
+        ),
+        Element::new("defenses").with(vec![
+            Element::new("ac").with(vec![Element::number("total", creature.armor_class())])
+        ]),
+        Element::number("exp", player.experience()),
+        Element::list("featurelist", features),
+        Element::new("hp").with(vec![Element::number("total", health.value())]),
+        Element::list("inventorylist", inventory),
+        Element::list("languagelist", languages),
+        Element::number("level", player.level()),
+        Element::string("name", player.name()),
+        Element::formatted("notes", &creature.description().unwrap_or_default()),
+        Element::list("powers", powers),
+        Element::number("profbonus", proficiency),
+        Element::string("race", race_text(player.race())),
+        Element::string(
+            "senses",
+            creature
+                .senses()
+                .map(|x| render::list(x))
+                .unwrap_or_default(),
+        ),
+        Element::string("size", creature.size()),
+        skills(player, proficiency, &mut report),
+        Element::new("speed").with(vec![
+            Element::number("base", speed),
+            Element::number("total", speed),
+        ]),
+        Element::list("traitlist", traits),
+        Element::list("weaponlist", weapons),
+    ];
+    fields.extend(
+        [
+            resistances("damageimmunities", creature.damage_immunities()),
+            resistances("damageresistances", creature.damage_resistances()),
+            resistances("damagevulnerabilities", creature.damage_vulnerabilities()),
+            creature
+                .condition_immunities()
+                .map(|x| Element::string("conditionimmunities", render::lowercase_list(x))),
+        ]
+        .into_iter()
+        .flatten(),
+    );
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    output
+        .push_str("<root version=\"4.1\" dataversion=\"20210708\" release=\"8.1|CoreRPG:4.1\">\n");
+    Element::new("character").with(fields).write(&mut output, 1);
+    output.push_str("</root>\n");
+    (output, report)
+}
+
+mod test {
+    #[test]
+    fn test_export_player() {
+        use std::collections::BTreeMap;
+
+        /// Every element path of a document with the value of its `type` attribute, with record
+        /// ids collapsed so that lists of different lengths compare equal.
+        fn paths(xml: &str) -> BTreeMap<String, String> {
+            let mut paths = BTreeMap::new();
+            let mut stack: Vec<String> = Vec::new();
+            let mut rest = xml;
+            while let Some(start) = rest.find('<') {
+                let end = rest[start..].find('>').unwrap() + start;
+                let tag = &rest[start + 1..end];
+                rest = &rest[end + 1..];
+                if tag.starts_with('?') {
+                    continue;
+                }
+                if let Some(name) = tag.strip_prefix('/') {
+                    let name = if name.starts_with("id-") {
+                        "id-*"
+                    } else {
+                        name
+                    };
+                    assert_eq!(stack.pop().as_deref(), Some(name), "unbalanced element");
+                    continue;
+                }
+                let closed = tag.ends_with('/');
+                let tag = tag.trim_end_matches('/').trim();
+                let name = tag.split_whitespace().next().unwrap();
+                let name = if name.starts_with("id-") {
+                    "id-*"
+                } else {
+                    name
+                };
+                let kind = tag
+                    .split_once("type=\"")
+                    .map(|(_, x)| x.trim_end_matches('"'))
+                    .unwrap_or_default();
+                stack.push(name.to_string());
+                paths.insert(stack.join("/"), kind.to_string());
+                if closed {
+                    stack.pop();
+                }
+            }
+            assert!(stack.is_empty(), "unclosed elements: {:?}", stack);
+            paths
+        }
+
+        let player = crate::convert::test::tessa();
+        let (xml, report) = super::export_player(&player);
+        assert!(report.is_empty(), "{:?}", report);
+
+        let sample = paths(include_str!("samples/fantasy_grounds_character.xml"));
+        for (path, kind) in paths(&xml) {
+            assert_eq!(
+                sample.get(&path),
+                Some(&kind),
+                "{} is not in the sample character",
+                path
+            );
+        }
+
+        assert!(xml.contains("<race type=\"string\">Elf</race>"));
+        assert!(xml.contains("<hddie type=\"dice\">d6</hddie>"));
+        assert!(xml.contains("<score type=\"number\">18</score>"));
+        assert!(xml.contains("<name type=\"string\">Arcana</name>"));
+        assert!(xml.contains("<name type=\"string\">Wand of Magic Missiles</name>"));
+        assert!(xml.contains("<castingtime type=\"string\">1 reaction</castingtime>"));
+        // History +10 is expertise on top of the +4 Intelligence modifier.
+        assert!(xml.contains("<prof type=\"number\">2</prof>"));
+        // +1 to hit is a -1 Strength modifier, +3 proficiency and a -1 penalty.
+        assert!(xml.contains("<attackbonus type=\"number\">-1</attackbonus>"));
+        assert!(xml.contains("<bonus type=\"number\">0</bonus>"));
+    }
+}
@@ -0,0 +1,1460 @@
+//! [5etools](https://5e.tools) monster, spell and item JSON, as used in its homebrew files.
+//!
+//! Text fields are read with their `{@tag ...}` markup resolved to plain text, and attacks and
+//! dice are tagged again on export so 5etools renders them as rollable.
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    action::{attack::Attack, Action, ActionType},
+    creature::{
+        Creature, CreatureType, Health, RacialTrait, Size, Skill, SkillType, Stat, StatType,
+    },
+    id::CreatureId,
+    item::{ArmorType, Charge, Item, ItemRarity, ItemType, TimeDivision, WeaponType},
+    render,
+    spell::{CastingTime, Duration, Spell, SpellRange},
+    Alignment, DamageType, DieStat, OtherAttribute, ParseError,
+};
+
+use super::{
+    action_text, area, area_shape, attunement, challenge_rating_from_f64, compact_dice, conditions,
+    damage_from_text, damage_types, display, languages, leading_int, monster_type, narrow, rarity,
+    report_extra_attributes, report_item_extras, save, save_parts, senses, special, speed_entries,
+    speeds, spell_level, spell_level_number, ConversionReport, ConvertError, Reader,
+};
+
+/// Fields 5etools derives from an entry for its filters, or uses to place it in a book.
+fn is_derived(key: &str) -> bool {
+    key.ends_with("Tags")
+        || key.starts_with("has")
+        || [
+            "source",
+            "page",
+            "srd",
+            "basicRules",
+            "otherSources",
+            "reprintedAs",
+            "conditionInflict",
+            "conditionInflictLegendary",
+            "savingThrowForced",
+            "savingThrowForcedLegendary",
+            "damageInflict",
+        ]
+        .contains(&key)
+}
+
+fn reader(value: &Value) -> Result<Reader<'_>, ConvertError> {
+    let mut reader = Reader::new(value)?;
+    let derived: Vec<&String> = reader.keys().filter(|x| is_derived(x)).collect();
+    for key in derived {
+        reader.value(key);
+    }
+    Ok(reader)
+}
+
+/// Resolves `{@tag ...}` markup to the text 5etools would display for it.
+pub fn strip_tags(text: &str) -> String {
+    let mut text = text.to_string();
+    // Resolve the innermost tag first so nested tags come out right.
+    while let Some(start) = text.rfind("{@") {
+        let Some(length) = text[start..].find('}') else {
+            break;
+        };
+        let inner = &text[start + 2..start + length];
+        let (tag, content) = inner.split_once(' ').unwrap_or((inner, ""));
+        let replacement = match tag {
+            "atk" => match content {
+                "mw" => String::from("Melee Weapon Attack:"),
+                "rw" => String::from("Ranged Weapon Attack:"),
+                "mw,rw" | "rw,mw" => String::from("Melee or Ranged Weapon Attack:"),
+                "ms" => String::from("Melee Spell Attack:"),
+                "rs" => String::from("Ranged Spell Attack:"),
+                other => format!("{} Attack:", other),
+            },
+            "h" => String::from("Hit: "),
+            "hit" => match content.parse::<i32>() {
+                Ok(x) => render::modifier(x),
+                Err(_) => content.to_string(),
+            },
+            "dc" => format!("DC {}", content),
+            "recharge" => match content {
+                "" => String::from("(Recharge 6)"),
+                x => format!("(Recharge {}-6)", x),
+            },
+            _ => {
+                let parts: Vec<&str> = content.split('|').collect();
+                parts.get(2).unwrap_or(&parts[0]).to_string()
+            }
+        };
+        text.replace_range(start..start + length + 1, &replacement);
+    }
+    text
+}
+
+/// Wraps dice expressions in `{@damage ...}` tags, both bare ones like `3d6` and the
+/// parenthesised ones after an average like `5 (1d6 + 2)`.
+fn tag_dice(text: &str) -> String {
+    let text = text
+        .split(' ')
+        .map(|word| {
+            let trimmed = word.trim_end_matches([',', '.', ';']);
+            match trimmed.parse::<DieStat>() {
+                Ok(x) if x.die_count > 0 => {
+                    format!("{{@damage {}}}{}", trimmed, &word[trimmed.len()..])
+                }
+                _ => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut result = String::new();
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('(') {
+        let Some(close) = rest[open..].find(')') else {
+            break;
+        };
+        let inner = &rest[open + 1..open + close];
+        result.push_str(&rest[..=open]);
+        match inner.parse::<DieStat>() {
+            Ok(x) if x.die_count > 0 => result.push_str(&format!("{{@damage {}}}", inner)),
+            _ => result.push_str(inner),
+        }
+        result.push(')');
+        rest = &rest[open + close + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Flattens a list of 5etools entries, which may nest lists and named sections, into
+/// paragraphs of plain text.
+fn entries_text(values: &[Value]) -> String {
+    let mut paragraphs = Vec::new();
+    for value in values {
+        match value {
+            Value::String(x) => paragraphs.push(strip_tags(x)),
+            Value::Object(x) => {
+                for key in ["entries", "items"] {
+                    if let Some(Value::Array(inner)) = x.get(key) {
+                        paragraphs.push(entries_text(inner));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+fn entries(text: &str) -> Value {
+    Value::Array(text.split("\n\n").map(|x| json!(x)).collect())
+}
+
+const SIZES: [(&str, Size); 6] = [
+    ("T", Size::Tiny),
+    ("S", Size::Small),
+    ("M", Size::Medium),
+    ("L", Size::Large),
+    ("H", Size::Huge),
+    ("G", Size::Gargantuan),
+];
+
+/// The alignment letters 5etools uses, and whether the alignment is only typical.
+fn alignment_codes(alignment: &Alignment) -> (&'static [&'static str], bool) {
+    match alignment {
+        Alignment::AnyAlignment => (&["A"], false),
+        Alignment::AnyChaotic => (&["C", "G", "NY", "E"], false),
+        Alignment::AnyEvil => (&["L", "NX", "C", "E"], false),
+        Alignment::AnyGood => (&["L", "NX", "C", "G"], false),
+        Alignment::AnyLawful => (&["L", "G", "NY", "E"], false),
+        Alignment::AnyNeutral => (&["NX", "NY", "N"], false),
+        Alignment::AnyNonChaotic => (&["NX", "L", "G", "NY", "E"], false),
+        Alignment::AnyNonEvil => (&["L", "NX", "C", "NY", "G"], false),
+        Alignment::AnyNonGood => (&["L", "NX", "C", "NY", "E"], false),
+        Alignment::AnyNonLawful => (&["NX", "C", "G", "NY", "E"], false),
+        Alignment::AnyNonNeutral => (&["L", "C", "G", "E"], false),
+        Alignment::ChaoticEvil => (&["C", "E"], false),
+        Alignment::ChaoticNeutral => (&["C", "N"], false),
+        Alignment::ChaoticGood => (&["C", "G"], false),
+        Alignment::LawfulEvil => (&["L", "E"], false),
+        Alignment::LawfulNeutral => (&["L", "N"], false),
+        Alignment::LawfulGood => (&["L", "G"], false),
+        Alignment::NeutralEvil => (&["N", "E"], false),
+        Alignment::TrueNeutral => (&["N"], false),
+        Alignment::NeutralGood => (&["N", "G"], false),
+        Alignment::TypicallyChaoticEvil => (&["C", "E"], true),
+        Alignment::TypicallyChaoticNeutral => (&["C", "N"], true),
+        Alignment::TypicallyChaoticGood => (&["C", "G"], true),
+        Alignment::TypicallyLawfulEvil => (&["L", "E"], true),
+        Alignment::TypicallyLawfulNeutral => (&["L", "N"], true),
+        Alignment::TypicallyLawfulGood => (&["L", "G"], true),
+        Alignment::TypicallyNeutralEvil => (&["N", "E"], true),
+        Alignment::TypicallyTrueNeutral => (&["N"], true),
+        Alignment::TypicallyNeutralGood => (&["N", "G"], true),
+        Alignment::Unaligned => (&["U"], false),
+    }
+}
+
+fn alignment(codes: &[&str], typically: bool) -> Option<Alignment> {
+    use strum::IntoEnumIterator;
+
+    let mut codes = codes.to_vec();
+    codes.sort_unstable();
+    Alignment::iter().find(|x| {
+        let (expected, expected_typically) = alignment_codes(x);
+        let mut expected = expected.to_vec();
+        expected.sort_unstable();
+        expected == codes && expected_typically == typically
+    })
+}
+
+pub fn import_creature(value: &Value) -> Result<(Creature, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = reader(value)?;
+    let entity = reader.entity.clone();
+
+    let sizes = reader.array("size");
+    let size = sizes
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|x| SIZES.iter().find(|(code, _)| *code == x))
+        .map(|(_, size)| size.clone())
+        .unwrap_or_default();
+    if sizes.len() > 1 {
+        report.push(
+            &entity,
+            "size",
+            json!(sizes),
+            "only the first size was kept",
+        );
+    }
+    let creature_type = match reader.value("type") {
+        Some(value) => {
+            let kind = match value {
+                Value::Object(x) => {
+                    if let Some(tags) = x.get("tags") {
+                        report.push(&entity, "type", tags, "type tags have no matching field");
+                    }
+                    x.get("type").map(display).unwrap_or_default()
+                }
+                other => display(other),
+            };
+            match monster_type(&kind) {
+                Some(x) => CreatureType::Monster(x),
+                None => {
+                    report.push(&entity, "type", kind, "unknown creature type");
+                    CreatureType::default()
+                }
+            }
+        }
+        None => CreatureType::default(),
+    };
+    let codes: Vec<&str> = reader
+        .array("alignment")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    let typically = reader
+        .str("alignmentPrefix")
+        .map(|x| x.trim().eq_ignore_ascii_case("typically"))
+        .unwrap_or_default();
+    let alignment = match alignment(&codes, typically) {
+        Some(x) => x,
+        None => {
+            reader.skip(&mut report, "alignment", "unknown alignment");
+            Alignment::default()
+        }
+    };
+
+    let armor_class = match reader.array("ac").first() {
+        Some(Value::Object(x)) => {
+            if let Some(from) = x.get("from") {
+                report.push(
+                    &entity,
+                    "ac",
+                    from,
+                    "armor description has no matching field",
+                );
+            }
+            x.get("ac").and_then(leading_int)
+        }
+        Some(x) => leading_int(x),
+        None => None,
+    }
+    .and_then(|x| narrow(x, Creature::ARMOR_CLASS, &entity, "ac", &mut report))
+    .unwrap_or(10);
+    let health = match reader.value("hp") {
+        Some(hp) => match hp.get("formula").and_then(Value::as_str) {
+            Some(x) => x
+                .parse()
+                .map_err(|_| ParseError::new(x, "invalid hit dice"))?,
+            None => format!(
+                "{}",
+                hp.get("average").and_then(leading_int).unwrap_or_default()
+            )
+            .parse()?,
+        },
+        None => DieStat::default(),
+    };
+
+    let mut speed_list = Vec::new();
+    if let Some(Value::Object(map)) = reader.value("speed") {
+        for (kind, feet) in map {
+            if kind == "canHover" {
+                continue;
+            }
+            let (feet, condition) = match feet {
+                Value::Object(x) => (
+                    x.get("number").and_then(leading_int),
+                    x.get("condition")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                ),
+                other => (leading_int(other), ""),
+            };
+            match feet {
+                Some(feet) => speed_list.push((kind.as_str(), feet, condition.contains("hover"))),
+                None => report.push(&entity, "speed", kind, "invalid speed"),
+            }
+        }
+    }
+    let (speed, mut others) = speeds(&speed_list, &entity, &mut report);
+
+    let stats: Vec<Stat> = ["str", "dex", "con", "int", "wis", "cha"]
+        .iter()
+        .map(|key| {
+            Stat::from_value(
+                key.parse::<StatType>().unwrap_or(StatType::Strength),
+                reader.ability_score(&mut report, key),
+            )
+        })
+        .collect();
+    let mut saving_throws = Vec::new();
+    if let Some(Value::Object(map)) = reader.value("save") {
+        for (ability, modifier) in map {
+            match (ability.parse::<StatType>(), leading_int(modifier)) {
+                (Ok(stat_type), Some(modifier)) => {
+                    let Some(modifier) = narrow(modifier, .., &entity, "save", &mut report) else {
+                        continue;
+                    };
+                    saving_throws.push(Stat {
+                        value: stats
+                            .iter()
+                            .find(|x| x.stat_type == stat_type)
+                            .map(|x| x.value)
+                            .unwrap_or(10),
+                        stat_type,
+                        modifier,
+                    })
+                }
+                _ => report.push(&entity, "save", ability, "invalid saving throw"),
+            }
+        }
+    }
+    // JSON objects come back sorted by key, so put the saves back in stat block order.
+    saving_throws.sort_by_key(|x: &Stat| {
+        use strum::IntoEnumIterator;
+        StatType::iter().position(|y| y == x.stat_type)
+    });
+    let mut skills = Vec::new();
+    if let Some(Value::Object(map)) = reader.value("skill") {
+        for (name, modifier) in map {
+            match (name.parse::<SkillType>(), leading_int(modifier)) {
+                (Ok(skill_type), Some(modifier)) => {
+                    if let Some(modifier) = narrow(modifier, .., &entity, "skill", &mut report) {
+                        skills.push(Skill {
+                            skill_type,
+                            modifier,
+                        });
+                    }
+                }
+                _ => report.push(&entity, "skill", name, "unknown skill"),
+            }
+        }
+    }
+
+    let [damage_vulnerabilities, damage_resistances, damage_immunities] =
+        ["vulnerable", "resist", "immune"].map(|key| {
+            let mut names = Vec::new();
+            for value in reader.array(key) {
+                match value {
+                    Value::String(x) => names.push(x.clone()),
+                    Value::Object(_) => {
+                        report.push(&entity, key, value, "conditional damage types were dropped");
+                    }
+                    _ => {}
+                }
+            }
+            damage_types(&names.join(", "), key, &entity, &mut report)
+        });
+    let condition_immunities = conditions(
+        reader
+            .array("conditionImmune")
+            .iter()
+            .filter_map(Value::as_str),
+        &entity,
+        &mut report,
+    );
+    let senses = senses(
+        reader.array("senses").iter().filter_map(Value::as_str),
+        &entity,
+        &mut report,
+    );
+    reader.value("passive");
+    let language_list: Vec<String> = reader
+        .array("languages")
+        .iter()
+        .map(|x| strip_tags(&display(x)))
+        .collect();
+    let languages = languages(&language_list.join(", "), &entity, &mut report);
+    let challenge_rating = match reader.value("cr") {
+        Some(Value::Object(x)) => {
+            for (key, value) in x.iter().filter(|(key, _)| *key != "cr") {
+                report.push(&entity, &format!("cr.{}", key), value, "no matching field");
+            }
+            x.get("cr").map(display).unwrap_or_default()
+        }
+        Some(Value::Number(x)) => challenge_rating_from_f64(x.as_f64().unwrap_or_default()),
+        Some(x) => display(x),
+        None => String::new(),
+    };
+
+    let racial_traits: Vec<RacialTrait> = named_entries(reader.array("trait"))
+        .map(|(name, description)| RacialTrait { name, description })
+        .collect();
+    let mut actions: Vec<Action> = named_entries(reader.array("action"))
+        .map(|(name, description)| super::action(&name, &description))
+        .collect();
+    actions.extend(
+        named_entries(reader.array("reaction"))
+            .map(|(name, x)| Action::new(ActionType::Reaction(special(&name, &x)))),
+    );
+    actions.extend(
+        named_entries(reader.array("legendary"))
+            .map(|(name, x)| Action::new(ActionType::LegendaryAction(special(&name, &x)))),
+    );
+    let header = reader.array("legendaryHeader");
+    if !header.is_empty() {
+        others.push(OtherAttribute {
+            title: String::from("Legendary Actions"),
+            description: entries_text(header),
+            value: String::new(),
+        });
+    }
+    if let Some(count) = reader.int("legendaryActions").filter(|x| *x != 3) {
+        report.push(&entity, "legendaryActions", count, "no matching field");
+    }
+    let description = reader
+        .value("fluff")
+        .and_then(|x| x.get("entries"))
+        .and_then(Value::as_array)
+        .map(|x| entries_text(x));
+    for key in ["environment", "legendaryGroup", "spellcasting", "soundClip"] {
+        reader.skip(&mut report, key, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    let mut creature = Creature::new(
+        CreatureId::new(),
+        entity,
+        creature_type,
+        alignment,
+        armor_class,
+        Health { health },
+        speed,
+        stats,
+        Some(saving_throws).filter(|x| !x.is_empty()),
+        damage_resistances,
+        damage_immunities,
+        damage_vulnerabilities,
+        condition_immunities,
+        Some(skills).filter(|x| !x.is_empty()),
+        senses,
+        languages,
+        challenge_rating,
+        Some(racial_traits).filter(|x| !x.is_empty()),
+        description,
+        Some(actions).filter(|x| !x.is_empty()),
+        None,
+        Some(others).filter(|x| !x.is_empty()),
+    );
+    creature.set_size(size);
+    Ok((creature, report))
+}
+
+/// The name and plain text of each entry in a list of traits or actions.
+fn named_entries(values: &[Value]) -> impl Iterator<Item = (String, String)> + '_ {
+    values.iter().filter_map(|x| {
+        Some((
+            strip_tags(x.get("name")?.as_str()?),
+            x.get("entries")
+                .and_then(Value::as_array)
+                .map(|x| entries_text(x))
+                .unwrap_or_default(),
+        ))
+    })
+}
+
+pub fn export_creature(creature: &Creature, source: &str) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut value = Map::new();
+    value.insert(String::from("name"), json!(creature.name()));
+    value.insert(String::from("source"), json!(source));
+    let size = SIZES
+        .iter()
+        .find(|(_, x)| x == creature.size())
+        .map(|(code, _)| *code)
+        .unwrap_or("M");
+    value.insert(String::from("size"), json!([size]));
+    value.insert(
+        String::from("type"),
+        json!(match creature.creature_type() {
+            CreatureType::Monster(x) => x.to_string().to_lowercase(),
+            other => {
+                report.push(
+                    creature.name(),
+                    "creature_type",
+                    format!("{:?}", other),
+                    "exported as humanoid",
+                );
+                String::from("humanoid")
+            }
+        }),
+    );
+    let (codes, typically) = alignment_codes(creature.alignment());
+    value.insert(String::from("alignment"), json!(codes));
+    if typically {
+        value.insert(String::from("alignmentPrefix"), json!("typically "));
+    }
+    value.insert(String::from("ac"), json!([creature.armor_class()]));
+    let health = &creature.health_points().health;
+    let mut hp = json!({ "average": health.value() });
+    if health.die_count > 0 {
+        hp["formula"] = json!(render::dice(health));
+    }
+    value.insert(String::from("hp"), hp);
+
+    let mut speed = Map::new();
+    for (kind, feet, hover) in speed_entries(creature, &mut report) {
+        if hover {
+            speed.insert(
+                kind.to_string(),
+                json!({ "number": feet, "condition": "(hover)" }),
+            );
+            speed.insert(String::from("canHover"), json!(true));
+        } else {
+            speed.insert(kind.to_string(), json!(feet));
+        }
+    }
+    value.insert(String::from("speed"), Value::Object(speed));
+
+    for stat in creature.stats() {
+        value.insert(
+            stat.stat_type.abbreviation().to_lowercase(),
+            json!(stat.value),
+        );
+    }
+    if let Some(saves) = creature.saving_throws().filter(|x| !x.is_empty()) {
+        let saves: Map<String, Value> = saves
+            .iter()
+            .map(|x| {
+                (
+                    x.stat_type.abbreviation().to_lowercase(),
+                    json!(render::modifier(x.modifier)),
+                )
+            })
+            .collect();
+        value.insert(String::from("save"), Value::Object(saves));
+    }
+    if let Some(skills) = creature.skills().filter(|x| !x.is_empty()) {
+        let skills: Map<String, Value> = skills
+            .iter()
+            .map(|x| {
+                let text = render::skill(x).to_lowercase();
+                let (name, modifier) = text.rsplit_once(' ').unwrap_or_default();
+                (name.to_string(), json!(modifier))
+            })
+            .collect();
+        value.insert(String::from("skill"), Value::Object(skills));
+    }
+    if let Some(senses) = creature.senses().filter(|x| !x.is_empty()) {
+        let senses: Vec<String> = senses
+            .iter()
+            .map(|x| x.to_string().to_lowercase())
+            .collect();
+        value.insert(String::from("senses"), json!(senses));
+    }
+    value.insert(
+        String::from("passive"),
+        json!(creature.passive_perception()),
+    );
+    for (key, values) in [
+        ("vulnerable", creature.damage_vulnerabilities()),
+        ("resist", creature.damage_resistances()),
+        ("immune", creature.damage_immunities()),
+    ] {
+        if let Some(values) = values.filter(|x| !x.is_empty()) {
+            let names: Vec<String> = values
+                .iter()
+                .map(|x| x.to_string().to_lowercase())
+                .collect();
+            value.insert(key.to_string(), json!(names));
+        }
+    }
+    if let Some(conditions) = creature.condition_immunities().filter(|x| !x.is_empty()) {
+        let names: Vec<String> = conditions
+            .iter()
+            .map(|x| x.to_string().to_lowercase())
+            .collect();
+        value.insert(String::from("conditionImmune"), json!(names));
+    }
+    if let Some(languages) = creature.languages().filter(|x| !x.is_empty()) {
+        let names: Vec<String> = languages.iter().map(|x| x.to_string()).collect();
+        value.insert(String::from("languages"), json!(names));
+    }
+    value.insert(String::from("cr"), json!(creature.challenge_rating()));
+
+    if let Some(traits) = creature.racial_traits().filter(|x| !x.is_empty()) {
+        let traits: Vec<Value> = traits
+            .iter()
+            .map(|x| json!({ "name": x.name, "entries": entries(&x.description) }))
+            .collect();
+        value.insert(String::from("trait"), json!(traits));
+    }
+    let sections = render::action_sections(creature);
+    for (key, actions) in [
+        ("action", &sections.actions),
+        ("reaction", &sections.reactions),
+        ("legendary", &sections.legendary_actions),
+    ] {
+        if !actions.is_empty() {
+            let actions: Vec<Value> = actions.iter().map(|x| action_entry(x)).collect();
+            value.insert(key.to_string(), json!(actions));
+        }
+    }
+    let intro = creature
+        .others()
+        .into_iter()
+        .flatten()
+        .find(|x| x.title == "Legendary Actions");
+    if let Some(intro) = intro {
+        value.insert(String::from("legendaryHeader"), entries(&intro.description));
+    }
+    if let Some(description) = creature.description() {
+        value.insert(
+            String::from("fluff"),
+            json!({ "entries": entries(&description) }),
+        );
+    }
+    report_extra_attributes(creature, &mut report);
+    (Value::Object(value), report)
+}
+
+fn action_entry(action: &ActionType) -> Value {
+    let (name, description) = match action {
+        ActionType::Attack(attack) => (render::attack_text(attack).name, attack_entry(attack)),
+        other => action_text(other),
+    };
+    json!({ "name": name, "entries": entries(&description) })
+}
+
+/// Writes an attack the way 5etools does, e.g.
+/// `{@atk mw} {@hit 4} to hit, reach 5 ft., one target. {@h}5 ({@damage 1d6 + 2}) slashing damage.`
+fn attack_entry(attack: &Attack) -> String {
+    let text = render::attack_text(attack);
+    let kind = match attack {
+        Attack::MeleeWeaponAttack(_) => "mw",
+        Attack::RangedWeaponAttack(_) => "rw",
+        Attack::MeleeOrRangedWeaponAttack(_) => "mw,rw",
+        Attack::MeleeSpellAttack(_) => "ms",
+        Attack::RangedSpellAttack(_) => "rs",
+    };
+    let modifier = render::modifier(attack.modifier());
+    let details = text
+        .details
+        .strip_prefix(&modifier)
+        .unwrap_or(&text.details);
+    let mut entry = format!(
+        "{{@atk {}}} {{@hit {}}}{} {{@h}}{}.",
+        kind,
+        attack.modifier(),
+        details,
+        tag_dice(&text.hit)
+    );
+    if !text.description.is_empty() {
+        entry.push(' ');
+        entry.push_str(&text.description);
+    }
+    entry
+}
+
+pub fn import_spell(value: &Value) -> Result<(Spell, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = reader(value)?;
+    let entity = reader.entity.clone();
+
+    let mut description = entries_text(reader.array("entries"));
+    let higher_level = entries_text(reader.array("entriesHigherLevel"));
+    if !higher_level.is_empty() {
+        description.push_str("\n\nAt Higher Levels. ");
+        description.push_str(&higher_level);
+    }
+    let level = reader
+        .int("level")
+        .ok_or(ConvertError::MissingField("level"))?;
+    let level = spell_level(level)
+        .ok_or_else(|| ParseError::new(&level.to_string(), "invalid spell level"))?;
+
+    let time = reader
+        .array("time")
+        .first()
+        .ok_or(ConvertError::MissingField("time"))?;
+    let unit = time.get("unit").and_then(Value::as_str).unwrap_or_default();
+    let casting_time = match unit {
+        "action" => CastingTime::Action,
+        "bonus" => CastingTime::BonusAction,
+        "reaction" => CastingTime::Reaction,
+        "minute" => CastingTime::Minute,
+        "hour" => CastingTime::Hour,
+        _ => return Err(ParseError::new(unit, "unknown casting time").into()),
+    };
+    if time.get("number").and_then(leading_int) != Some(1) || time.get("condition").is_some() {
+        report.push(&entity, "time", time, "only the unit was kept");
+    }
+
+    let range = reader
+        .value("range")
+        .ok_or(ConvertError::MissingField("range"))?;
+    let kind = range
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let distance = range.get("distance");
+    let distance_type = distance
+        .and_then(|x| x.get("type"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let amount = distance
+        .and_then(|x| x.get("amount"))
+        .and_then(leading_int)
+        .unwrap_or_default();
+    let feet = match distance_type {
+        "miles" => amount.checked_mul(5280),
+        _ => Some(amount),
+    };
+    let amount = match feet.and_then(|x| i32::try_from(x).ok()).filter(|x| *x >= 0) {
+        Some(feet) => feet,
+        None => {
+            report.push(&entity, "range", amount, "out of range");
+            0
+        }
+    };
+    let (range, spell_area) = match (kind, distance_type) {
+        ("point", "self") => (SpellRange::S, None),
+        ("point", "touch") => (SpellRange::Touch, None),
+        ("point", "feet") => (SpellRange::Range(amount), None),
+        ("point", "miles") => (SpellRange::Range(amount), None),
+        (shape, "feet") => match area(shape, amount) {
+            Some(x) => (SpellRange::S, Some(x)),
+            None => return Err(ParseError::new(shape, "unknown spell area").into()),
+        },
+        (_, other) => return Err(ParseError::new(other, "unknown spell range").into()),
+    };
+
+    let components = reader
+        .value("components")
+        .ok_or(ConvertError::MissingField("components"))?;
+    let letters: String = ["v", "s", "m"]
+        .iter()
+        .filter(|x| components.get(x).map(|x| x != &json!(false)) == Some(true))
+        .map(|x| x.to_uppercase())
+        .collect();
+    let components_value =
+        super::components(&letters).ok_or_else(|| ParseError::new(&letters, "no components"))?;
+    if let Some(material) = components.get("m").filter(|x| !x.is_boolean()) {
+        report.push(&entity, "components.m", material, "no matching field");
+    }
+
+    let durations = reader.array("duration");
+    let first = durations
+        .first()
+        .ok_or(ConvertError::MissingField("duration"))?;
+    let timed = first.get("duration");
+    let timed_type = timed
+        .and_then(|x| x.get("type"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let ends: Vec<&str> = first
+        .get("ends")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let duration = match first
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+    {
+        "instant" => Duration::Instantaneous,
+        "timed" if first.get("concentration") == Some(&json!(true)) => Duration::Concentration,
+        "timed" => match timed_type {
+            "round" | "minute" => Duration::Minute,
+            "hour" => Duration::Hour,
+            "day" | "week" | "year" => Duration::Day,
+            other => return Err(ParseError::new(other, "unknown duration").into()),
+        },
+        "permanent" if ends.contains(&"dispel") && ends.contains(&"trigger") => {
+            Duration::UntilDispelledOrTriggered
+        }
+        "permanent" if ends.contains(&"trigger") => Duration::UntilTriggered,
+        "permanent" => Duration::UntilDispelled,
+        other => return Err(ParseError::new(other, "unknown duration").into()),
+    };
+    if durations.len() > 1 || duration_value(&duration) != *first {
+        report.push(
+            &entity,
+            "duration",
+            json!(durations),
+            "only the unit was kept",
+        );
+    }
+
+    let damage_type = reader
+        .array("damageInflict")
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|x| x.parse::<DamageType>().ok());
+    let save = reader
+        .array("savingThrow")
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|x| save(x, None));
+    for key in [
+        "school",
+        "meta",
+        "classes",
+        "subclasses",
+        "races",
+        "backgrounds",
+    ] {
+        reader.skip(&mut report, key, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    let damage = damage_from_text(&description).map(|(x, _)| x);
+    Ok((
+        Spell {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            description,
+            level,
+            casting_time,
+            duration,
+            damage,
+            range,
+            area: spell_area,
+            damage_type,
+            components: components_value,
+            attack_bonus: None,
+            save,
+        },
+        report,
+    ))
+}
+
+fn duration_value(duration: &Duration) -> Value {
+    let timed = |unit: &str| json!({ "type": "timed", "duration": { "type": unit, "amount": 1 } });
+    match duration {
+        Duration::Instantaneous => json!({ "type": "instant" }),
+        Duration::Concentration => json!({
+            "type": "timed",
+            "duration": { "type": "minute", "amount": 1 },
+            "concentration": true,
+        }),
+        Duration::Minute => timed("minute"),
+        Duration::Hour => timed("hour"),
+        Duration::Day => timed("day"),
+        Duration::UntilDispelled => json!({ "type": "permanent", "ends": ["dispel"] }),
+        Duration::UntilDispelledOrTriggered => {
+            json!({ "type": "permanent", "ends": ["dispel", "trigger"] })
+        }
+        Duration::UntilTriggered => json!({ "type": "permanent", "ends": ["trigger"] }),
+    }
+}
+
+pub fn export_spell(spell: &Spell, source: &str) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let (description, higher_level) = match spell.description.split_once("\n\nAt Higher Levels. ") {
+        Some((description, higher_level)) => (description, Some(higher_level)),
+        None => (spell.description.as_str(), None),
+    };
+    let range = match (&spell.range, &spell.area) {
+        (SpellRange::S, Some(area)) => {
+            let (shape, size) = area_shape(area);
+            json!({ "type": shape, "distance": { "type": "feet", "amount": size } })
+        }
+        (range, area) => {
+            if let Some(area) = area {
+                report.push(
+                    &spell.name,
+                    "area",
+                    area,
+                    "only ranges of self can have an area",
+                );
+            }
+            let distance = match range {
+                SpellRange::S => json!({ "type": "self" }),
+                SpellRange::Touch => json!({ "type": "touch" }),
+                SpellRange::Range(x) => json!({ "type": "feet", "amount": x }),
+            };
+            json!({ "type": "point", "distance": distance })
+        }
+    };
+    let letters = spell.components.to_string();
+    let mut components = Map::new();
+    for letter in ["v", "s", "m"] {
+        if letters.contains(&letter.to_uppercase()) {
+            components.insert(letter.to_string(), json!(true));
+        }
+    }
+    let mut value = json!({
+        "name": spell.name,
+        "source": source,
+        "level": spell_level_number(&spell.level),
+        "time": [{
+            "number": 1,
+            "unit": match spell.casting_time {
+                CastingTime::Action => "action",
+                CastingTime::BonusAction => "bonus",
+                CastingTime::Reaction => "reaction",
+                CastingTime::Minute => "minute",
+                CastingTime::Hour => "hour",
+            },
+        }],
+        "range": range,
+        "components": components,
+        "duration": [duration_value(&spell.duration)],
+        "entries": entries(&tag_dice(description)),
+    });
+    if let Some(higher_level) = higher_level {
+        value["entriesHigherLevel"] = json!([{
+            "type": "entries",
+            "name": "At Higher Levels",
+            "entries": entries(&tag_dice(higher_level)),
+        }]);
+    }
+    if let Some(damage_type) = &spell.damage_type {
+        value["damageInflict"] = json!([damage_type.to_string().to_lowercase()]);
+    }
+    if let Some(save) = &spell.save {
+        let (ability, dc) = save_parts(save);
+        let ability = ability
+            .parse::<StatType>()
+            .map(|x| x.to_string().to_lowercase());
+        value["savingThrow"] = json!([ability.unwrap_or_default()]);
+        if let Some(dc) = dc {
+            report.push(&spell.name, "save", dc, "the DC comes from the caster");
+        }
+    }
+    if let Some(damage) = &spell.damage {
+        if damage_from_text(description).map(|(x, _)| x).as_ref() != Some(damage) {
+            report.push(
+                &spell.name,
+                "damage",
+                compact_dice(damage),
+                "not mentioned in the description",
+            );
+        }
+    }
+    if let Some(bonus) = spell.attack_bonus {
+        report.push(&spell.name, "attack_bonus", bonus, "no matching field");
+    }
+    (value, report)
+}
+
+pub fn import_item(value: &Value) -> Result<(Item, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = reader(value)?;
+    let entity = reader.entity.clone();
+
+    let code = reader
+        .str("type")
+        .map(|x| x.split('|').next().unwrap_or_default())
+        .unwrap_or_default();
+    let staff = reader.bool("staff");
+    let wondrous = reader.bool("wondrous");
+    let item_type = match code {
+        _ if staff => ItemType::Staff,
+        "M" | "R" => ItemType::Weapon,
+        "LA" | "MA" | "HA" | "S" => ItemType::Armor,
+        "P" => ItemType::Potion,
+        "RG" => ItemType::Ring,
+        "RD" => ItemType::Rod,
+        "SC" => ItemType::Scroll,
+        "WD" => ItemType::Wand,
+        _ if wondrous => ItemType::WondrousItem,
+        other => return Err(ParseError::new(other, "unknown item type").into()),
+    };
+    let weapon_type = reader.bool("sword").then_some(WeaponType::Sword);
+    let armor_type = (code == "S").then_some(ArmorType::Shield);
+    let rarity = match reader.str("rarity") {
+        Some(text) => rarity(text).unwrap_or_else(|| {
+            report.push(&entity, "rarity", text, "kept as varies");
+            ItemRarity::Varies
+        }),
+        None => ItemRarity::Unknown,
+    };
+    let attunement = match reader.value("reqAttune") {
+        Some(Value::Bool(true)) => attunement("requires attunement"),
+        Some(Value::String(x)) => attunement(&format!("requires attunement {}", x)),
+        _ => None,
+    };
+    let charges = reader.int("charges");
+    let charges = charges.and_then(|x| narrow(x, 0.., &entity, "charges", &mut report));
+    let has_charges = charges.map(|num| {
+        let time = match reader.str("recharge") {
+            Some("dawn") | Some("dusk") | Some("midnight") | None => TimeDivision::Day,
+            Some(other) => {
+                report.push(&entity, "recharge", other, "kept as daily");
+                TimeDivision::Day
+            }
+        };
+        Charge { num, time }
+    });
+    reader.value("recharge");
+    for spell in reader.array("attachedSpells") {
+        report.push(
+            &entity,
+            "attachedSpells",
+            spell,
+            "spells are attached by value, not name",
+        );
+    }
+    let description = entries_text(reader.array("entries"));
+    for key in [
+        "baseItem",
+        "weaponCategory",
+        "bonusWeapon",
+        "bonusAc",
+        "property",
+        "dmg1",
+        "dmgType",
+    ] {
+        reader.skip(&mut report, key, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    Ok((
+        Item {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            item_type,
+            rarity,
+            attunement,
+            weapon_type,
+            armor_type,
+            conditions: None,
+            attached_spell: None,
+            has_charges,
+            inventory: None,
+            others: Some(vec![OtherAttribute {
+                title: String::from("Description"),
+                description: description.clone(),
+                value: String::new(),
+            }])
+            .filter(|_| !description.is_empty()),
+            actions: None,
+        },
+        report,
+    ))
+}
+
+pub fn export_item(item: &Item, source: &str) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut value = json!({
+        "name": item.name,
+        "source": source,
+        "rarity": match item.rarity {
+            ItemRarity::Unknown => String::from("unknown"),
+            ref other => other.to_string().to_lowercase(),
+        },
+    });
+    let code = match (&item.item_type, &item.armor_type) {
+        (ItemType::Armor, Some(ArmorType::Shield)) => Some("S"),
+        (ItemType::Armor, None) => Some("MA"),
+        (ItemType::Weapon, _) | (ItemType::Staff, _) => Some("M"),
+        (ItemType::Potion, _) => Some("P"),
+        (ItemType::Ring, _) => Some("RG"),
+        (ItemType::Rod, _) => Some("RD"),
+        (ItemType::Scroll, _) => Some("SC"),
+        (ItemType::Wand, _) => Some("WD"),
+        (ItemType::WondrousItem, _) => None,
+    };
+    match code {
+        Some(code) => value["type"] = json!(code),
+        None => value["wondrous"] = json!(true),
+    }
+    if item.item_type == ItemType::Staff {
+        value["staff"] = json!(true);
+    }
+    if item.weapon_type == Some(WeaponType::Sword) {
+        value["sword"] = json!(true);
+    }
+    if let Some(attunement) = &item.attunement {
+        value["reqAttune"] = match &attunement.alignments {
+            Some(alignments) => json!(format!("by a {} creature", render::list(alignments))),
+            None => json!(true),
+        };
+    }
+    if let Some(charges) = &item.has_charges {
+        value["charges"] = json!(charges.num);
+        match charges.time {
+            TimeDivision::Day => value["recharge"] = json!("dawn"),
+            _ => report.push(
+                &item.name,
+                "has_charges",
+                charges,
+                "only daily recharges are supported",
+            ),
+        }
+    }
+    if let Some(spell) = &item.attached_spell {
+        value["attachedSpells"] = json!([spell.name.to_lowercase()]);
+    }
+    let mut description = Vec::new();
+    for other in item.others.iter().flatten() {
+        if other.title == "Description" {
+            description.push(other.description.clone());
+        } else {
+            report.push(&item.name, "others", other, "no matching field");
+        }
+    }
+    if !description.is_empty() {
+        value["entries"] = entries(&description.join("\n\n"));
+    }
+    report_item_extras(
+        &Item {
+            has_charges: None,
+            attached_spell: None,
+            ..item.clone()
+        },
+        &mut report,
+    );
+    (value, report)
+}
+
+/// Creatures, spells and items gathered from a 5etools homebrew file.
+#[derive(Debug, Default)]
+pub struct Homebrew {
+    pub creatures: Vec<Creature>,
+    pub spells: Vec<Spell>,
+    pub items: Vec<Item>,
+}
+
+/// Reads every monster, spell and item in a homebrew file. Entries that can't be converted are
+/// reported and skipped.
+pub fn import_homebrew(value: &Value) -> (Homebrew, ConversionReport) {
+    let mut homebrew = Homebrew::default();
+    let mut report = ConversionReport::default();
+    let failed = |report: &mut ConversionReport, kind: &str, entry: &Value, e: ConvertError| {
+        let name = entry.get("name").map(display).unwrap_or_default();
+        report.push(&name, kind, entry, &e.to_string());
+    };
+    for entry in value
+        .get("monster")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match import_creature(entry) {
+            Ok((x, r)) => {
+                homebrew.creatures.push(x);
+                report.extend(r);
+            }
+            Err(e) => failed(&mut report, "monster", entry, e),
+        }
+    }
+    for entry in value
+        .get("spell")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match import_spell(entry) {
+            Ok((x, r)) => {
+                homebrew.spells.push(x);
+                report.extend(r);
+            }
+            Err(e) => failed(&mut report, "spell", entry, e),
+        }
+    }
+    for entry in value
+        .get("item")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match import_item(entry) {
+            Ok((x, r)) => {
+                homebrew.items.push(x);
+                report.extend(r);
+            }
+            Err(e) => failed(&mut report, "item", entry, e),
+        }
+    }
+    (homebrew, report)
+}
+
+/// Writes a homebrew file with a single source, ready to be loaded into 5etools.
+pub fn export_homebrew(
+    homebrew: &Homebrew,
+    source: &str,
+    full_name: &str,
+) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut collect = |values: Vec<(Value, ConversionReport)>| -> Vec<Value> {
+        values
+            .into_iter()
+            .map(|(value, r)| {
+                report.extend(r);
+                value
+            })
+            .collect()
+    };
+    let monsters = collect(
+        homebrew
+            .creatures
+            .iter()
+            .map(|x| export_creature(x, source))
+            .collect(),
+    );
+    let spells = collect(
+        homebrew
+            .spells
+            .iter()
+            .map(|x| export_spell(x, source))
+            .collect(),
+    );
+    let items = collect(
+        homebrew
+            .items
+            .iter()
+            .map(|x| export_item(x, source))
+            .collect(),
+    );
+    let value = json!({
+        "_meta": {
+            "sources": [{ "json": source, "abbreviation": source, "full": full_name, "version": "1.0.0" }],
+        },
+        "monster": monsters,
+        "spell": spells,
+        "item": items,
+    });
+    (value, report)
+}
+
+mod test {
+    #[test]
+    fn test_strip_tags() {
+        assert_eq!(
+            super::strip_tags("{@atk mw} {@hit 4} to hit, reach 5 ft., one target. {@h}5 ({@damage 1d6 + 2}) slashing damage."),
+            "Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage."
+        );
+        assert_eq!(
+            super::strip_tags("a {@condition frightened|phb|scared} {@spell fire bolt} {@dc 15}"),
+            "a scared fire bolt DC 15"
+        );
+    }
+
+    #[test]
+    fn test_import_creature() {
+        let value = serde_json::json!({
+            "name": "Goblin",
+            "source": "MM",
+            "page": 166,
+            "size": ["S"],
+            "type": { "type": "humanoid", "tags": ["goblinoid"] },
+            "alignment": ["N", "E"],
+            "ac": [{ "ac": 15, "from": ["{@item leather armor|phb}", "{@item shield|phb}"] }],
+            "hp": { "average": 7, "formula": "2d6" },
+            "speed": { "walk": 30 },
+            "str": 8, "dex": 14, "con": 10, "int": 10, "wis": 8, "cha": 8,
+            "skill": { "stealth": "+6" },
+            "senses": ["darkvision 60 ft."],
+            "passive": 9,
+            "languages": ["Common", "Goblin"],
+            "cr": "1/4",
+            "trait": [{
+                "name": "Nimble Escape",
+                "entries": ["The goblin can take the {@action Disengage} or {@action Hide} action as a bonus action on each of its turns."]
+            }],
+            "action": [{
+                "name": "Scimitar",
+                "entries": ["{@atk mw} {@hit 4} to hit, reach 5 ft., one target. {@h}5 ({@damage 1d6 + 2}) slashing damage."]
+            }],
+            "environment": ["forest"],
+            "traitTags": ["Nimble Escape"],
+            "hasToken": true
+        });
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(creature.alignment(), &crate::Alignment::NeutralEvil);
+        assert_eq!(creature.size(), &crate::creature::Size::Small);
+        assert!(creature.racial_traits().unwrap()[0]
+            .description
+            .starts_with("The goblin can take the Disengage or Hide action"));
+        assert!(matches!(
+            &creature.actions().unwrap()[0].action,
+            crate::action::ActionType::Attack(x) if x.average_damage() == 5
+        ));
+        assert_eq!(
+            report
+                .unmapped
+                .iter()
+                .map(|x| x.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["type", "ac", "environment"]
+        );
+
+        let (exported, _) = super::export_creature(&creature, "MM");
+        assert_eq!(
+            exported["action"][0]["entries"],
+            value["action"][0]["entries"]
+        );
+        assert_eq!(exported["alignment"], value["alignment"]);
+
+        let mut value = value;
+        value["ac"] = serde_json::json!([4294967297_i64]);
+        value["skill"] = serde_json::json!({ "stealth": -4294967296_i64 });
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(*creature.armor_class(), 10);
+        assert!(creature.skills().is_none_or(|x| x.is_empty()));
+        let fields: Vec<&str> = report.unmapped.iter().map(|x| x.field.as_str()).collect();
+        assert!(fields.contains(&"ac") && fields.contains(&"skill"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        /// Serializes a value with every generated id removed, so converted copies can be compared.
+        fn without_ids<T: serde::Serialize>(value: &T) -> serde_json::Value {
+            fn strip(value: &mut serde_json::Value) {
+                match value {
+                    serde_json::Value::Object(map) => {
+                        map.remove("id");
+                        map.values_mut().for_each(strip);
+                    }
+                    serde_json::Value::Array(values) => values.iter_mut().for_each(strip),
+                    _ => {}
+                }
+            }
+            let mut value = serde_json::to_value(value).unwrap();
+            strip(&mut value);
+            value
+        }
+
+        use crate::{
+            action::{Action, ActionType, SpecialAction},
+            item::{Attuneable, Charge, Item, ItemRarity, ItemType, TimeDivision, WeaponType},
+            spell::{Area, CastingTime, Components, Duration, Save, Spell, SpellLevel, SpellRange},
+            Alignment, DamageType, OtherAttribute,
+        };
+
+        let (mut creature, _) = crate::creature::parse::parse_stat_block(
+            "Young Red Dragon
+Large dragon, typically chaotic evil
+Armor Class 18 (natural armor)
+Hit Points 178 (17d10 + 85)
+Speed 40 ft., climb 40 ft., fly 80 ft. (hover)
+STR DEX CON INT WIS CHA
+23 (+6) 10 (+0) 21 (+5) 14 (+2) 11 (+0) 19 (+4)
+Saving Throws Dex +4, Con +9, Wis +4, Cha +8
+Skills Perception +8, Stealth +4
+Damage Immunities fire
+Condition Immunities frightened
+Senses blindsight 30 ft., darkvision 120 ft., passive Perception 18
+Languages Common, Draconic
+Challenge 10 (5,900 XP)
+Actions
+Bite. Melee Weapon Attack: +10 to hit, reach 10 ft., one target. Hit: 17 (2d10 + 6) piercing damage plus 3 (1d6) fire damage.
+Fire Breath (Recharge 5-6). The dragon exhales fire in a 30-foot cone.
+Reactions
+Tail Flick. The dragon flicks its tail.
+Legendary Actions
+The dragon can take 3 legendary actions.
+Detect. The dragon makes a Wisdom (Perception) check.
+",
+        );
+        creature.set_description(Some(String::from("Red dragons are the most covetous.")));
+        assert_eq!(creature.alignment(), &Alignment::TypicallyChaoticEvil);
+        let (exported, report) = super::export_creature(&creature, "GM");
+        assert!(report.is_empty());
+        let (imported, report) = super::import_creature(&exported).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(without_ids(&imported), without_ids(&creature));
+
+        let spell = Spell {
+            id: String::new(),
+            name: String::from("Burning Hands"),
+            description: String::from("Each creature in a 15-foot cone must make a Dexterity saving throw. A creature takes 3d6 fire damage on a failed save.\n\nAt Higher Levels. The damage increases by 1d6 for each slot level above 1st."),
+            level: SpellLevel::Level1,
+            casting_time: CastingTime::Action,
+            duration: Duration::Instantaneous,
+            damage: Some("3d6".parse().unwrap()),
+            range: SpellRange::S,
+            area: Some(Area::Cone(15)),
+            damage_type: Some(DamageType::Fire),
+            components: Components::VS,
+            attack_bonus: None,
+            save: Some(Save::Dexterity(None)),
+        };
+        let (exported, report) = super::export_spell(&spell, "GM");
+        assert!(report.is_empty());
+        assert_eq!(
+            exported["entries"][0],
+            "Each creature in a 15-foot cone must make a Dexterity saving throw. A creature takes {@damage 3d6} fire damage on a failed save."
+        );
+        let (imported, report) = super::import_spell(&exported).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(without_ids(&imported), without_ids(&spell));
+
+        let item = Item {
+            id: String::new(),
+            name: String::from("Flame Tongue"),
+            item_type: ItemType::Weapon,
+            rarity: ItemRarity::Rare,
+            attunement: Some(Attuneable {
+                alignments: Some(vec![Alignment::ChaoticGood]),
+            }),
+            weapon_type: Some(WeaponType::Sword),
+            armor_type: None,
+            conditions: None,
+            attached_spell: None,
+            has_charges: Some(Charge {
+                num: 3,
+                time: TimeDivision::Day,
+            }),
+            inventory: None,
+            others: Some(vec![OtherAttribute {
+                title: String::from("Description"),
+                description: String::from("You can speak this magic sword's command word."),
+                value: String::new(),
+            }]),
+            actions: Some(vec![Action::new(ActionType::Special(
+                SpecialAction::default(),
+            ))]),
+        };
+        let (exported, report) = super::export_item(&item, "GM");
+        assert_eq!(report.unmapped.len(), 1);
+        assert_eq!(report.unmapped[0].field, "actions");
+        let (imported, report) = super::import_item(&exported).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(
+            without_ids(&imported),
+            without_ids(&Item {
+                actions: None,
+                ..item
+            })
+        );
+    }
+}
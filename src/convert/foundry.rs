@@ -0,0 +1,931 @@
+//! Foundry VTT documents for the dnd5e system.
+//!
+//! Creatures and players become Actors with their traits, actions, items and spells embedded,
+//! and items and spells become Item documents. A [`Module`] writes them out as a module with
+//! compendium sources that the Foundry CLI (`fvtt package pack`) can compile into packs.
+
+use std::{fs, io, path::Path};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    action::{
+        attack::{Attack, TargetType},
+        ActionType,
+    },
+    creature::{Creature, CreatureType, Language, Sense, Size, SkillType, StatType},
+    item::{ArmorType, Item, ItemRarity, ItemType, TimeDivision},
+    render::{self, html::escape},
+    spell::{Area, CastingTime, Duration, Spell, SpellRange},
+    world::campaign::player::Player,
+};
+
+use super::{
+    area_shape, attack_ability, compact_dice, hit_die, proficiency_bonus, race_text, save_parts,
+    skill_ability, skill_proficiency, slug, speed_entries, stable_hash, ConversionReport,
+};
+
+/// A stable 16 character document id derived from one of our ids.
+pub fn document_id(seed: &str) -> String {
+    format!("{:016x}", stable_hash(seed))
+}
+
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| format!("<p>{}</p>", escape(x)))
+        .collect()
+}
+
+fn document(id: &str, name: &str, kind: &str, img: &str, system: Value) -> Value {
+    json!({
+        "_id": document_id(id),
+        "name": name,
+        "type": kind,
+        "img": img,
+        "system": system,
+        "effects": [],
+        "flags": {},
+    })
+}
+
+fn ability_code(stat_type: &StatType) -> String {
+    stat_type.abbreviation().to_lowercase()
+}
+
+fn ability_modifier(creature: &Creature, stat_type: &StatType) -> i32 {
+    creature
+        .stat(stat_type)
+        .map(|x| x.modifier)
+        .unwrap_or_default()
+}
+
+fn skill_code(skill_type: &SkillType) -> &'static str {
+    match skill_type {
+        SkillType::Acrobatics => "acr",
+        SkillType::AnimalHandling => "ani",
+        SkillType::Arcana => "arc",
+        SkillType::Athletics => "ath",
+        SkillType::Deception => "dec",
+        SkillType::History => "his",
+        SkillType::Insight => "ins",
+        SkillType::Intimidation => "itm",
+        SkillType::Investigation => "inv",
+        SkillType::Medicine => "med",
+        SkillType::Nature => "nat",
+        SkillType::Perception => "prc",
+        SkillType::Performance => "prf",
+        SkillType::Persuasion => "per",
+        SkillType::Religion => "rel",
+        SkillType::SleightOfHand => "slt",
+        SkillType::Stealth => "ste",
+        SkillType::Survival => "sur",
+    }
+}
+
+fn size_code(size: &Size) -> &'static str {
+    match size {
+        Size::Tiny => "tiny",
+        Size::Small => "sm",
+        Size::Medium => "med",
+        Size::Large => "lg",
+        Size::Huge => "huge",
+        Size::Gargantuan => "grg",
+    }
+}
+
+/// The languages dnd5e knows by key; the rest go into the custom text.
+fn language_code(language: &Language) -> Option<&'static str> {
+    Some(match language {
+        Language::Common => "common",
+        Language::Dwarvish => "dwarvish",
+        Language::Elvish => "elvish",
+        Language::Giant => "giant",
+        Language::Gnomish => "gnomish",
+        Language::Goblin => "goblin",
+        Language::Halfling => "halfling",
+        Language::Orc => "orc",
+        Language::Abyssal => "abyssal",
+        Language::Celestial => "celestial",
+        Language::DeepSpeech => "deep",
+        Language::Draconic => "draconic",
+        Language::Infernal => "infernal",
+        Language::Primordial => "primordial",
+        Language::Sylvan => "sylvan",
+        Language::Undercommon => "undercommon",
+        Language::Aquan => "aquan",
+        Language::Auran => "auran",
+        Language::Ignan => "ignan",
+        Language::Terran => "terran",
+        _ => return None,
+    })
+}
+
+fn rarity_code(rarity: &ItemRarity) -> &'static str {
+    match rarity {
+        ItemRarity::Common => "common",
+        ItemRarity::Uncommon => "uncommon",
+        ItemRarity::Rare => "rare",
+        ItemRarity::VeryRare => "veryRare",
+        ItemRarity::Legendary => "legendary",
+        ItemRarity::Artifact => "artifact",
+        ItemRarity::Varies | ItemRarity::Unknown => "",
+    }
+}
+
+fn lowercase_names<T: ToString>(values: Option<&Vec<T>>) -> Vec<String> {
+    values
+        .into_iter()
+        .flatten()
+        .map(|x| x.to_string().to_lowercase())
+        .collect()
+}
+
+/// The system data shared by NPC and character actors.
+fn actor_system(creature: &Creature, proficiency: i32, report: &mut ConversionReport) -> Value {
+    use strum::IntoEnumIterator;
+
+    let abilities: Map<String, Value> = StatType::iter()
+        .map(|stat_type| {
+            let proficient = creature
+                .saving_throws()
+                .into_iter()
+                .flatten()
+                .any(|x| x.stat_type == stat_type);
+            let value = creature.stat(&stat_type).map(|x| x.value).unwrap_or(10);
+            (
+                ability_code(&stat_type),
+                json!({ "value": value, "proficient": proficient as i32 }),
+            )
+        })
+        .collect();
+
+    let mut skills = Map::new();
+    for skill in creature.skills().into_iter().flatten() {
+        let ability = skill_ability(&skill.skill_type);
+        let value = skill_proficiency(creature, skill, proficiency, report);
+        skills.insert(
+            skill_code(&skill.skill_type).to_string(),
+            json!({ "value": value, "ability": ability_code(&ability) }),
+        );
+    }
+
+    let mut movement = json!({
+        "burrow": 0, "climb": 0, "fly": 0, "swim": 0, "walk": 0, "units": "ft", "hover": false,
+    });
+    for (kind, feet, hover) in speed_entries(creature, report) {
+        movement[kind] = json!(feet);
+        if hover {
+            movement["hover"] = json!(true);
+        }
+    }
+    let mut senses = json!({
+        "darkvision": 0, "blindsight": 0, "tremorsense": 0, "truesight": 0, "units": "ft",
+        "special": "",
+    });
+    for sense in creature.senses().into_iter().flatten() {
+        let (kind, range) = match sense {
+            Sense::Blindsight(x) => ("blindsight", x),
+            Sense::Darkvision(x) => ("darkvision", x),
+            Sense::Tremorsense(x) => ("tremorsense", x),
+            Sense::Truesight(x) => ("truesight", x),
+        };
+        senses[kind] = json!(range);
+    }
+
+    let health = &creature.health_points().health;
+    let mut hp = json!({ "value": health.value(), "max": health.value() });
+    if health.die_count > 0 {
+        hp["formula"] = json!(render::dice(health));
+    }
+
+    let (known, custom): (Vec<&Language>, Vec<&Language>) = creature
+        .languages()
+        .into_iter()
+        .flatten()
+        .partition(|x| language_code(x).is_some());
+
+    json!({
+        "abilities": abilities,
+        "attributes": {
+            "ac": { "flat": creature.armor_class(), "calc": "flat" },
+            "hp": hp,
+            "movement": movement,
+            "senses": senses,
+        },
+        "traits": {
+            "size": size_code(creature.size()),
+            "di": { "value": lowercase_names(creature.damage_immunities()), "custom": "" },
+            "dr": { "value": lowercase_names(creature.damage_resistances()), "custom": "" },
+            "dv": { "value": lowercase_names(creature.damage_vulnerabilities()), "custom": "" },
+            "ci": { "value": lowercase_names(creature.condition_immunities()), "custom": "" },
+            "languages": {
+                "value": known.iter().filter_map(|x| language_code(x)).collect::<Vec<_>>(),
+                "custom": custom.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("; "),
+            },
+        },
+        "skills": skills,
+    })
+}
+
+/// Embedded documents for a creature's traits and actions.
+fn feature_items(
+    creature: &Creature,
+    proficiency: i32,
+    report: &mut ConversionReport,
+) -> Vec<Value> {
+    let mut items = Vec::new();
+    for racial_trait in creature.racial_traits().into_iter().flatten() {
+        items.push(feat(
+            &format!("{}{}", creature.id(), racial_trait.name),
+            &racial_trait.name,
+            &racial_trait.description,
+            "",
+        ));
+    }
+    for action in creature.actions().into_iter().flatten() {
+        items.push(match &action.action {
+            ActionType::Attack(attack) => weapon(&action.id, attack, creature, proficiency, report),
+            ActionType::Special(x) => feat(&action.id, &x.name, &x.description, "action"),
+            ActionType::Reaction(x) => feat(&action.id, &x.name, &x.description, "reaction"),
+            ActionType::LegendaryAction(x) => {
+                feat(&action.id, &x.name, &x.description, "legendary")
+            }
+        });
+    }
+    if let Some(lair) = creature.lair() {
+        for (i, paragraph) in lair.lair_actions.iter().enumerate() {
+            items.push(feat(
+                &format!("{}lair{}", creature.id(), i),
+                &format!("{} {}", lair.name, i + 1),
+                &paragraph.paragraph,
+                "lair",
+            ));
+        }
+        if !lair.description.is_empty() {
+            report.push(
+                creature.name(),
+                "lair.description",
+                &lair.description,
+                "no matching field",
+            );
+        }
+        for paragraph in &lair.regional_effects {
+            report.push(
+                creature.name(),
+                "lair.regional_effects",
+                &paragraph.paragraph,
+                "no matching field",
+            );
+        }
+    }
+    items
+}
+
+fn feat(id: &str, name: &str, description: &str, activation: &str) -> Value {
+    document(
+        id,
+        name,
+        "feat",
+        "icons/svg/upgrade.svg",
+        json!({
+            "description": { "value": paragraphs(description) },
+            "activation": { "type": activation, "cost": if activation.is_empty() { Value::Null } else { json!(1) } },
+            "type": { "value": "monster" },
+        }),
+    )
+}
+
+fn weapon(
+    id: &str,
+    attack: &Attack,
+    creature: &Creature,
+    proficiency: i32,
+    report: &mut ConversionReport,
+) -> Value {
+    let text = render::attack_text(attack);
+    let (action_type, reach, range, target_type) = match attack {
+        Attack::MeleeWeaponAttack(x) => ("mwak", x.reach, None, &x.target_type),
+        Attack::MeleeSpellAttack(x) => ("msak", x.reach, None, &x.target_type),
+        Attack::RangedWeaponAttack(x) => ("rwak", None, x.range.as_ref(), &x.target_type),
+        Attack::RangedSpellAttack(x) => ("rsak", None, x.range.as_ref(), &x.target_type),
+        Attack::MeleeOrRangedWeaponAttack(x) => ("mwak", x.reach, x.range.as_ref(), &x.target_type),
+    };
+    // Foundry adds the ability modifier and proficiency itself, so only the rest is a bonus.
+    let ability = attack_ability(attack, creature);
+    let bonus = attack.modifier() - ability_modifier(creature, &ability) - proficiency;
+
+    let mut parts = Vec::new();
+    let mut conditional = Vec::new();
+    for damage in attack.damage() {
+        let part = json!([
+            render::dice(&damage.damage),
+            damage.damage_type.to_string().to_lowercase()
+        ]);
+        match &damage.condition {
+            None => parts.push(part),
+            Some(condition) => {
+                conditional.push(render::dice(&damage.damage));
+                report.push(
+                    &text.name,
+                    "damage.condition",
+                    condition,
+                    "kept as other formula",
+                );
+            }
+        }
+    }
+    if range.is_some() && reach.is_some() {
+        report.push(
+            &text.name,
+            "reach",
+            reach.unwrap_or(5),
+            "only the range was kept",
+        );
+    }
+    let range = match range {
+        Some(x) => json!({ "value": x.close_range, "long": x.long_range, "units": "ft" }),
+        None => json!({ "value": reach.unwrap_or(5), "long": null, "units": "ft" }),
+    };
+    let target = match target_type {
+        TargetType::OneTarget => json!({ "value": 1, "type": "creature" }),
+        TargetType::MultipleTargets(x) => json!({ "value": x, "type": "creature" }),
+        TargetType::Cone(x) => json!({ "value": x, "units": "ft", "type": "cone" }),
+        TargetType::Line(x) => json!({ "value": x, "units": "ft", "type": "line" }),
+        TargetType::Cube(x) => json!({ "value": x, "units": "ft", "type": "cube" }),
+        TargetType::Sphere(x) => json!({ "value": x, "units": "ft", "type": "sphere" }),
+    };
+    document(
+        id,
+        &text.name,
+        "weapon",
+        "icons/svg/sword.svg",
+        json!({
+            "description": { "value": paragraphs(&text.description) },
+            "activation": { "type": "action", "cost": 1 },
+            "target": target,
+            "range": range,
+            "actionType": action_type,
+            "ability": ability_code(&ability),
+            "attackBonus": if bonus == 0 { String::new() } else { bonus.to_string() },
+            "proficient": true,
+            "damage": { "parts": parts, "versatile": "" },
+            "formula": conditional.join(" + "),
+            "weaponType": "natural",
+            "equipped": true,
+        }),
+    )
+}
+
+fn prototype_token(name: &str, linked: bool, size: &Size) -> Value {
+    let scale = match size {
+        Size::Tiny | Size::Small | Size::Medium => 1,
+        Size::Large => 2,
+        Size::Huge => 3,
+        Size::Gargantuan => 4,
+    };
+    json!({
+        "name": name,
+        "actorLink": linked,
+        "disposition": if linked { 1 } else { -1 },
+        "width": scale,
+        "height": scale,
+    })
+}
+
+/// Exports a creature as an NPC actor.
+pub fn export_creature(creature: &Creature) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let proficiency = creature.proficiency_bonus().unwrap_or(2);
+    let mut system = actor_system(creature, proficiency, &mut report);
+    system["details"] = json!({
+        "biography": { "value": paragraphs(&creature.description().unwrap_or_default()) },
+        "alignment": creature.alignment().to_string(),
+        "type": {
+            "value": match creature.creature_type() {
+                CreatureType::Monster(x) => x.to_string().to_lowercase(),
+                _ => String::from("humanoid"),
+            },
+            "subtype": "",
+            "swarm": "",
+            "custom": "",
+        },
+        "cr": creature.challenge_rating_value(),
+        "xp": { "value": creature.experience_points() },
+    });
+    let sections = render::action_sections(creature);
+    if !sections.legendary_actions.is_empty() {
+        system["resources"]["legact"] = json!({ "value": 3, "max": 3 });
+    }
+    if creature.lair().is_some() {
+        system["resources"]["lair"] = json!({ "value": true, "initiative": 20 });
+    }
+    for other in render::extra_attributes(creature) {
+        report.push(creature.name(), "others", other, "no matching field");
+    }
+    let mut actor = document(
+        &creature.id().to_string(),
+        creature.name(),
+        "npc",
+        "icons/svg/mystery-man.svg",
+        system,
+    );
+    actor["items"] = Value::Array(feature_items(creature, proficiency, &mut report));
+    actor["prototypeToken"] = prototype_token(creature.name(), false, creature.size());
+    (actor, report)
+}
+
+/// Exports a player as a character actor, with their class, inventory and spells embedded.
+pub fn export_player(player: &Player) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let creature = player.creature();
+    let proficiency = proficiency_bonus(*player.level());
+    let mut system = actor_system(creature, proficiency, &mut report);
+    system["details"] = json!({
+        "biography": { "value": paragraphs(&creature.description().unwrap_or_default()) },
+        "alignment": creature.alignment().to_string(),
+        "race": race_text(player.race()),
+        "xp": { "value": player.experience() },
+    });
+    for other in render::extra_attributes(creature) {
+        report.push(creature.name(), "others", other, "no matching field");
+    }
+
+    let mut items = vec![document(
+        &format!("{}class", player.id()),
+        &player.class().to_string(),
+        "class",
+        "icons/svg/book.svg",
+        json!({
+            "identifier": player.class().to_string().to_lowercase(),
+            "levels": player.level(),
+            "hitDice": hit_die(player.class()).to_string(),
+        }),
+    )];
+    items.extend(feature_items(creature, proficiency, &mut report));
+    for item in player.inventory() {
+        let (value, r) = export_item(item);
+        items.push(value);
+        report.extend(r);
+    }
+    for spell in player.spells() {
+        let (mut value, r) = export_spell(spell);
+        value["system"]["preparation"]["prepared"] = json!(true);
+        items.push(value);
+        report.extend(r);
+    }
+
+    let mut actor = document(
+        &player.id().to_string(),
+        player.name(),
+        "character",
+        "icons/svg/mystery-man.svg",
+        system,
+    );
+    actor["items"] = Value::Array(items);
+    actor["prototypeToken"] = prototype_token(player.name(), true, creature.size());
+    (actor, report)
+}
+
+pub fn export_item(item: &Item) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut description = Vec::new();
+    for other in item.others.iter().flatten() {
+        if other.title == "Description" {
+            description.push(other.description.clone());
+        } else {
+            report.push(&item.name, "others", other, "no matching field");
+        }
+    }
+    let mut system = json!({
+        "description": { "value": paragraphs(&description.join("\n\n")) },
+        "rarity": rarity_code(&item.rarity),
+        "attunement": item.attunement.is_some() as i32,
+        "equipped": false,
+    });
+    if let Some(alignments) = item.attunement.as_ref().and_then(|x| x.alignments.as_ref()) {
+        report.push(
+            &item.name,
+            "attunement",
+            render::list(alignments),
+            "alignment restrictions have no matching field",
+        );
+    }
+    let (kind, img) = match (&item.item_type, &item.armor_type) {
+        (ItemType::Weapon, _) => {
+            system["weaponType"] = json!("martialM");
+            ("weapon", "icons/svg/sword.svg")
+        }
+        (ItemType::Staff, _) => {
+            system["weaponType"] = json!("simpleM");
+            ("weapon", "icons/svg/wand.svg")
+        }
+        (ItemType::Armor, Some(ArmorType::Shield)) => {
+            system["armor"] = json!({ "type": "shield", "value": 2 });
+            ("equipment", "icons/svg/shield.svg")
+        }
+        (ItemType::Armor, None) => {
+            system["armor"] = json!({ "type": "medium" });
+            ("equipment", "icons/svg/shield.svg")
+        }
+        (ItemType::Ring, _) | (ItemType::WondrousItem, _) => {
+            system["armor"] = json!({ "type": "trinket" });
+            ("equipment", "icons/svg/item-bag.svg")
+        }
+        (ItemType::Potion, _)
+        | (ItemType::Scroll, _)
+        | (ItemType::Wand, _)
+        | (ItemType::Rod, _) => {
+            system["consumableType"] = json!(item.item_type.to_string().to_lowercase());
+            ("consumable", "icons/svg/pill.svg")
+        }
+    };
+    if let Some(charges) = &item.has_charges {
+        let per = match charges.time {
+            TimeDivision::Day => "day",
+            _ => {
+                report.push(
+                    &item.name,
+                    "has_charges",
+                    charges,
+                    "kept as charges without a recharge",
+                );
+                "charges"
+            }
+        };
+        system["uses"] =
+            json!({ "value": charges.num, "max": charges.num.to_string(), "per": per });
+    }
+    super::report_item_extras(
+        &Item {
+            has_charges: None,
+            ..item.clone()
+        },
+        &mut report,
+    );
+    (document(&item.id, &item.name, kind, img, system), report)
+}
+
+pub fn export_spell(spell: &Spell) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let letters = spell.components.to_string();
+    let (duration_value, units) = match spell.duration {
+        Duration::Instantaneous => ("", "inst"),
+        Duration::Concentration | Duration::Minute => ("1", "minute"),
+        Duration::Hour => ("1", "hour"),
+        Duration::Day => ("1", "day"),
+        Duration::UntilDispelled
+        | Duration::UntilDispelledOrTriggered
+        | Duration::UntilTriggered => ("", "perm"),
+    };
+    if matches!(
+        spell.duration,
+        Duration::UntilTriggered | Duration::UntilDispelledOrTriggered
+    ) {
+        report.push(
+            &spell.name,
+            "duration",
+            &spell.duration,
+            "kept as permanent",
+        );
+    }
+    let range = match spell.range {
+        SpellRange::S => json!({ "value": null, "units": "self" }),
+        SpellRange::Touch => json!({ "value": null, "units": "touch" }),
+        SpellRange::Range(x) => json!({ "value": x, "units": "ft" }),
+    };
+    let target = match &spell.area {
+        Some(area) => {
+            let (shape, size) = area_shape(area);
+            let shape = match area {
+                Area::Sphere(_) => "sphere",
+                Area::Cylinder(_) => "cylinder",
+                _ => shape,
+            };
+            json!({ "value": size, "units": "ft", "type": shape })
+        }
+        None => json!({ "value": null, "units": "", "type": "" }),
+    };
+    let action_type = match (&spell.attack_bonus, &spell.save, &spell.damage) {
+        (Some(_), _, _) => "rsak",
+        (None, Some(_), _) => "save",
+        (None, None, Some(_)) => "other",
+        (None, None, None) => "util",
+    };
+    if let Some(bonus) = spell.attack_bonus {
+        report.push(
+            &spell.name,
+            "attack_bonus",
+            bonus,
+            "Foundry uses the caster's spell attack bonus",
+        );
+    }
+    let save = match &spell.save {
+        Some(save) => {
+            let (ability, dc) = save_parts(save);
+            json!({ "ability": ability, "dc": dc, "scaling": if dc.is_some() { "flat" } else { "spell" } })
+        }
+        None => json!({ "ability": "", "dc": null, "scaling": "spell" }),
+    };
+    let parts = match (&spell.damage, &spell.damage_type) {
+        (Some(damage), damage_type) => vec![json!([
+            compact_dice(damage),
+            damage_type
+                .as_ref()
+                .map(|x| x.to_string().to_lowercase())
+                .unwrap_or_default()
+        ])],
+        (None, _) => Vec::new(),
+    };
+    let system = json!({
+        "description": { "value": paragraphs(&spell.description) },
+        "level": super::spell_level_number(&spell.level),
+        "school": "",
+        "components": {
+            "vocal": letters.contains('V'),
+            "somatic": letters.contains('S'),
+            "material": letters.contains('M'),
+            "ritual": false,
+            "concentration": spell.duration == Duration::Concentration,
+        },
+        "activation": {
+            "type": match spell.casting_time {
+                CastingTime::Action => "action",
+                CastingTime::BonusAction => "bonus",
+                CastingTime::Reaction => "reaction",
+                CastingTime::Minute => "minute",
+                CastingTime::Hour => "hour",
+            },
+            "cost": 1,
+        },
+        "duration": { "value": duration_value, "units": units },
+        "range": range,
+        "target": target,
+        "actionType": action_type,
+        "save": save,
+        "damage": { "parts": parts, "versatile": "" },
+        "preparation": { "mode": "prepared", "prepared": false },
+    });
+    (
+        document(
+            &spell.id,
+            &spell.name,
+            "spell",
+            "icons/svg/daze.svg",
+            system,
+        ),
+        report,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentType {
+    Actor,
+    Item,
+}
+
+impl DocumentType {
+    fn collection(&self) -> &'static str {
+        match self {
+            DocumentType::Actor => "actors",
+            DocumentType::Item => "items",
+        }
+    }
+}
+
+/// One compendium pack of a [`Module`].
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub name: String,
+    pub label: String,
+    pub document_type: DocumentType,
+    pub documents: Vec<Value>,
+}
+
+impl Pack {
+    pub fn new(name: &str, label: &str, document_type: DocumentType) -> Self {
+        Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            document_type,
+            documents: Vec::new(),
+        }
+    }
+}
+
+/// A Foundry module holding compendium packs.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub id: String,
+    pub title: String,
+    pub version: String,
+    pub packs: Vec<Pack>,
+}
+
+impl Module {
+    pub fn new(id: &str, title: &str, version: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            version: version.to_string(),
+            packs: Vec::new(),
+        }
+    }
+
+    /// The contents of `module.json`.
+    pub fn manifest(&self) -> Value {
+        json!({
+            "id": self.id,
+            "title": self.title,
+            "version": self.version,
+            "compatibility": { "minimum": "11", "verified": "12" },
+            "relationships": {
+                "systems": [{ "id": "dnd5e", "type": "system" }],
+            },
+            "packs": self.packs.iter().map(|pack| json!({
+                "name": pack.name,
+                "label": pack.label,
+                "path": format!("packs/{}", pack.name),
+                "type": match pack.document_type {
+                    DocumentType::Actor => "Actor",
+                    DocumentType::Item => "Item",
+                },
+                "system": "dnd5e",
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Writes `module.json` and one JSON source file per document under
+    /// `packs/_source/<pack>/`, each with the `_key` the Foundry CLI packs by. Nothing is
+    /// written when a pack name is not a slug or a document is not an object with an
+    /// alphanumeric `_id`.
+    pub fn write(&self, dir: &Path) -> io::Result<()> {
+        self.check()?;
+        fs::create_dir_all(dir)?;
+        fs::write(
+            dir.join("module.json"),
+            serde_json::to_string_pretty(&self.manifest())?,
+        )?;
+        for pack in &self.packs {
+            let pack_dir = dir.join("packs").join("_source").join(&pack.name);
+            fs::create_dir_all(&pack_dir)?;
+            for document in &pack.documents {
+                let mut document = document.clone();
+                let id = document["_id"].as_str().unwrap_or_default().to_string();
+                let key = format!("!{}!{}", pack.document_type.collection(), id);
+                object(&mut document)?.insert(String::from("_key"), json!(key));
+                if let Some(Value::Array(items)) = document.get_mut("items") {
+                    for item in items {
+                        let item_id = item["_id"].as_str().unwrap_or_default().to_string();
+                        let key = format!("!actors.items!{}.{}", id, item_id);
+                        object(item)?.insert(String::from("_key"), json!(key));
+                    }
+                }
+                let name = document["name"].as_str().map(slug).unwrap_or_default();
+                fs::write(
+                    pack_dir.join(format!("{}_{}.json", name, id)),
+                    serde_json::to_string_pretty(&document)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> io::Result<()> {
+        for pack in &self.packs {
+            if pack.name.is_empty() || slug(&pack.name) != pack.name {
+                return Err(invalid(format!("pack name {:?} is not a slug", pack.name)));
+            }
+            for document in &pack.documents {
+                let id = document.get("_id").and_then(Value::as_str);
+                if !document.is_object() || !id.is_some_and(is_document_id) {
+                    return Err(invalid(format!(
+                        "a document of pack {} has no alphanumeric _id",
+                        pack.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_document_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn object(value: &mut Value) -> io::Result<&mut Map<String, Value>> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| invalid(String::from("a document or item is not an object")))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+mod test {
+    #[test]
+    fn test_export_creature() {
+        let (creature, _) = crate::creature::parse::parse_stat_block(
+            "Goblin
+Small humanoid (goblinoid), neutral evil
+Armor Class 15 (leather armor, shield)
+Hit Points 7 (2d6)
+Speed 30 ft.
+STR DEX CON INT WIS CHA
+8 (-1) 14 (+2) 10 (+0) 10 (+0) 8 (-1) 8 (-1)
+Skills Stealth +6
+Senses darkvision 60 ft., passive Perception 9
+Languages Common, Goblin
+Challenge 1/4 (50 XP)
+Nimble Escape. The goblin can take the Disengage or Hide action as a bonus action on each of its turns.
+Actions
+Scimitar. Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage.
+",
+        );
+        let (actor, report) = super::export_creature(&creature);
+        assert!(report.is_empty());
+        assert_eq!(actor["_id"].as_str().unwrap().len(), 16);
+        assert_eq!(actor["type"], "npc");
+        assert_eq!(actor["system"]["traits"]["size"], "sm");
+        assert_eq!(actor["system"]["attributes"]["hp"]["formula"], "2d6");
+        assert_eq!(actor["system"]["attributes"]["senses"]["darkvision"], 60);
+        assert_eq!(actor["system"]["skills"]["ste"]["value"], 2.0);
+        assert_eq!(actor["system"]["details"]["cr"], 0.25);
+        assert_eq!(
+            actor["system"]["traits"]["languages"]["value"],
+            serde_json::json!(["common", "goblin"])
+        );
+
+        let items = actor["items"].as_array().unwrap();
+        assert_eq!(items[0]["type"], "feat");
+        assert_eq!(items[1]["type"], "weapon");
+        assert_eq!(items[1]["system"]["actionType"], "mwak");
+        assert_eq!(items[1]["system"]["ability"], "str");
+        // +4 to hit is a -1 Strength modifier, +2 proficiency and a +3 bonus.
+        assert_eq!(items[1]["system"]["attackBonus"], "3");
+        assert_eq!(
+            items[1]["system"]["damage"]["parts"],
+            serde_json::json!([["1d6 + 2", "slashing"]])
+        );
+
+        let (again, _) = super::export_creature(&creature);
+        assert_eq!(again["_id"], actor["_id"]);
+    }
+
+    #[test]
+    fn test_export_player_and_pack() {
+        let player = crate::convert::test::tessa();
+
+        let (actor, report) = super::export_player(&player);
+        assert!(report.is_empty());
+        assert_eq!(actor["type"], "character");
+        assert_eq!(actor["system"]["abilities"]["int"]["proficient"], 1);
+        assert_eq!(actor["system"]["abilities"]["str"]["proficient"], 0);
+        assert_eq!(actor["system"]["skills"]["arc"]["value"], 1.0);
+        let items = actor["items"].as_array().unwrap();
+        let kinds: Vec<&str> = items.iter().map(|x| x["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            kinds,
+            vec!["class", "feat", "weapon", "consumable", "spell"]
+        );
+        assert_eq!(items[0]["system"]["levels"], 5);
+        assert_eq!(items[4]["system"]["activation"]["type"], "reaction");
+
+        let mut module = super::Module::new("gamemstr-test", "Gamemstr Test", "1.0.0");
+        let mut pack = super::Pack::new("characters", "Characters", super::DocumentType::Actor);
+        pack.documents.push(actor.clone());
+        module.packs.push(pack);
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        module.write(&dir).unwrap();
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("module.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["packs"][0]["path"], "packs/characters");
+        let file = dir
+            .join("packs/_source/characters")
+            .join(format!("tessa_{}.json", actor["_id"].as_str().unwrap()));
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+        assert_eq!(
+            written["_key"],
+            format!("!actors!{}", actor["_id"].as_str().unwrap())
+        );
+        assert!(written["items"][1]["_key"]
+            .as_str()
+            .unwrap()
+            .starts_with("!actors.items!"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        module.packs[0]
+            .documents
+            .push(serde_json::json!("not a document"));
+        assert!(module.write(&dir).is_err());
+        module.packs[0].documents.pop();
+        module.packs[0].name = String::from("../escape");
+        assert!(module.write(&dir).is_err());
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,834 @@
+//! The [Open5e](https://open5e.com) API's monster, spell and magic item JSON.
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    action::{Action, ActionType},
+    creature::{Creature, CreatureType, Health, RacialTrait, Skill, SkillType, Stat},
+    id::CreatureId,
+    item::Item,
+    render,
+    spell::{Spell, SpellRange},
+    OtherAttribute, ParseError,
+};
+
+use super::{
+    action, action_text, area_shape, attunement, attunement_text, casting_time, casting_time_text,
+    challenge_rating_from_f64, compact_dice, components, conditions, damage_from_text,
+    damage_types, duration, duration_text, item_type, item_type_text, languages, narrow, rarity,
+    report_extra_attributes, save_from_text, senses, slug, special, speed_entries, speeds,
+    spell_level, spell_level_number, spell_range, spell_range_text, ConversionReport, ConvertError,
+    Reader, ABILITIES,
+};
+
+/// Bookkeeping fields that describe where an entry was published rather than the entry itself.
+const PROVENANCE: [&str; 9] = [
+    "slug",
+    "page",
+    "page_no",
+    "img_main",
+    "target_range_sort",
+    "document__slug",
+    "document__title",
+    "document__license_url",
+    "document__url",
+];
+
+pub fn import_creature(value: &Value) -> Result<(Creature, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let size = reader.size(&mut report);
+    let creature_type = reader.creature_type(&mut report);
+    reader.skip(&mut report, "subtype", "no matching field");
+    reader.skip(&mut report, "group", "no matching field");
+    let alignment = reader.alignment(&mut report);
+
+    if let Some(Value::String(text)) = reader.value("armor_class") {
+        if let Some((_, description)) = text.split_once('(') {
+            report.push(
+                &entity,
+                "armor_class",
+                description.trim_end_matches(')'),
+                "armor description has no matching field",
+            );
+        }
+    }
+    let armor_class = reader
+        .int("armor_class")
+        .and_then(|x| {
+            narrow(
+                x,
+                Creature::ARMOR_CLASS,
+                &entity,
+                "armor_class",
+                &mut report,
+            )
+        })
+        .unwrap_or(10);
+    reader.skip(&mut report, "armor_desc", "no matching field");
+    let health_points = match reader.str("hit_dice") {
+        Some(x) => Health {
+            health: x
+                .parse()
+                .map_err(|_| ParseError::new(x, "invalid hit dice"))?,
+        },
+        None => Health {
+            health: format!("{}", reader.int("hit_points").unwrap_or_default()).parse()?,
+        },
+    };
+    reader.value("hit_points");
+
+    let speed_list = reader.speed_map(&mut report);
+    let (speed, mut others) = speeds(&speed_list, &entity, &mut report);
+
+    let stats: Vec<Stat> = ABILITIES
+        .iter()
+        .map(|(key, stat_type)| {
+            Stat::from_value(stat_type.clone(), reader.ability_score(&mut report, key))
+        })
+        .collect();
+    let saving_throws: Vec<Stat> = ABILITIES
+        .iter()
+        .zip(&stats)
+        .filter_map(|((key, _), stat)| {
+            let field = format!("{}_save", key);
+            let modifier = reader.int(&field)?;
+            Some(Stat {
+                modifier: narrow(modifier, .., &entity, &field, &mut report)?,
+                ..stat.clone()
+            })
+        })
+        .collect();
+
+    let mut skills = Vec::new();
+    if let Some(Value::Object(map)) = reader.value("skills") {
+        for (name, modifier) in map {
+            match (name.parse::<SkillType>(), super::leading_int(modifier)) {
+                (Ok(skill_type), Some(modifier)) => {
+                    if let Some(modifier) = narrow(modifier, .., &entity, "skills", &mut report) {
+                        skills.push(Skill {
+                            skill_type,
+                            modifier,
+                        });
+                    }
+                }
+                _ => report.push(&entity, "skills", name, "unknown skill"),
+            }
+        }
+    }
+    if let Some(modifier) = reader.int("perception") {
+        if !skills.iter().any(|x| x.skill_type == SkillType::Perception) {
+            if let Some(modifier) = narrow(modifier, .., &entity, "perception", &mut report) {
+                skills.push(Skill {
+                    skill_type: SkillType::Perception,
+                    modifier,
+                });
+            }
+        }
+    }
+
+    let [damage_vulnerabilities, damage_resistances, damage_immunities] = [
+        "damage_vulnerabilities",
+        "damage_resistances",
+        "damage_immunities",
+    ]
+    .map(|key| {
+        reader
+            .str(key)
+            .and_then(|x| damage_types(x, key, &entity, &mut report))
+    });
+    let condition_immunities = reader
+        .str("condition_immunities")
+        .and_then(|x| conditions(crate::creature::parse::split_list(x), &entity, &mut report));
+    let senses = reader
+        .str("senses")
+        .and_then(|x| senses(crate::creature::parse::split_list(x), &entity, &mut report));
+    let languages = reader
+        .str("languages")
+        .and_then(|x| languages(x, &entity, &mut report));
+    let challenge_rating = match reader.str("challenge_rating") {
+        Some(x) => x.to_string(),
+        None => reader
+            .f64("cr")
+            .map(challenge_rating_from_f64)
+            .unwrap_or_default(),
+    };
+    reader.value("cr");
+
+    let racial_traits: Vec<RacialTrait> = entries(reader.array("special_abilities"))
+        .map(|(name, description)| RacialTrait {
+            name: name.to_string(),
+            description: description.to_string(),
+        })
+        .collect();
+    let mut actions: Vec<Action> = entries(reader.array("actions"))
+        .map(|(name, description)| action(name, description))
+        .collect();
+    actions.extend(
+        entries(reader.array("reactions"))
+            .map(|(name, x)| Action::new(ActionType::Reaction(special(name, x)))),
+    );
+    actions.extend(
+        entries(reader.array("legendary_actions"))
+            .map(|(name, x)| Action::new(ActionType::LegendaryAction(special(name, x)))),
+    );
+    reader.value("reactions");
+    if let Some(intro) = reader.str("legendary_desc") {
+        others.push(OtherAttribute {
+            title: String::from("Legendary Actions"),
+            description: intro.to_string(),
+            value: String::new(),
+        });
+    }
+    let description = reader.text("desc");
+    reader.skip(
+        &mut report,
+        "spell_list",
+        "spells are not linked to creatures",
+    );
+    reader.skip(&mut report, "environments", "no matching field");
+    reader.finish(&mut report);
+
+    let mut creature = Creature::new(
+        CreatureId::new(),
+        entity,
+        creature_type,
+        alignment,
+        armor_class,
+        health_points,
+        speed,
+        stats,
+        Some(saving_throws).filter(|x| !x.is_empty()),
+        damage_resistances,
+        damage_immunities,
+        damage_vulnerabilities,
+        condition_immunities,
+        Some(skills).filter(|x| !x.is_empty()),
+        senses,
+        languages,
+        challenge_rating,
+        Some(racial_traits).filter(|x| !x.is_empty()),
+        description,
+        Some(actions).filter(|x| !x.is_empty()),
+        None,
+        Some(others).filter(|x| !x.is_empty()),
+    );
+    creature.set_size(size);
+    Ok((creature, report))
+}
+
+/// The `name` and `desc` of each entry in a list of traits or actions.
+fn entries(values: &[Value]) -> impl Iterator<Item = (&str, &str)> {
+    values.iter().filter_map(|x| {
+        Some((
+            x.get("name")?.as_str()?,
+            x.get("desc").and_then(Value::as_str).unwrap_or_default(),
+        ))
+    })
+}
+
+pub fn export_creature(creature: &Creature) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut value = Map::new();
+    value.insert(String::from("slug"), json!(slug(creature.name())));
+    value.insert(String::from("name"), json!(creature.name()));
+    value.insert(String::from("size"), json!(creature.size().to_string()));
+    value.insert(
+        String::from("type"),
+        json!(match creature.creature_type() {
+            CreatureType::Monster(x) => x.to_string().to_lowercase(),
+            _ => String::from("humanoid"),
+        }),
+    );
+    value.insert(
+        String::from("alignment"),
+        json!(creature.alignment().to_string()),
+    );
+    value.insert(String::from("armor_class"), json!(creature.armor_class()));
+    let health = &creature.health_points().health;
+    value.insert(String::from("hit_points"), json!(health.value()));
+    value.insert(String::from("hit_dice"), json!(compact_dice(health)));
+
+    let mut speed = Map::new();
+    for (kind, feet, hover) in speed_entries(creature, &mut report) {
+        speed.insert(kind.to_string(), json!(feet));
+        if hover {
+            speed.insert(String::from("hover"), json!(true));
+        }
+    }
+    value.insert(String::from("speed"), Value::Object(speed));
+
+    for (key, stat_type) in ABILITIES {
+        let stat = creature.stat(&stat_type);
+        value.insert(key.to_string(), json!(stat.map(|x| x.value).unwrap_or(10)));
+        let save = creature
+            .saving_throws()
+            .into_iter()
+            .flatten()
+            .find(|x| x.stat_type == stat_type);
+        value.insert(format!("{}_save", key), json!(save.map(|x| x.modifier)));
+    }
+    let skills: Map<String, Value> = creature
+        .skills()
+        .into_iter()
+        .flatten()
+        .map(|x| {
+            (
+                snake_case(&format!("{:?}", x.skill_type)),
+                json!(x.modifier),
+            )
+        })
+        .collect();
+    value.insert(
+        String::from("perception"),
+        skills.get("perception").cloned().unwrap_or(Value::Null),
+    );
+    value.insert(String::from("skills"), Value::Object(skills));
+
+    for (key, values) in [
+        ("damage_vulnerabilities", creature.damage_vulnerabilities()),
+        ("damage_resistances", creature.damage_resistances()),
+        ("damage_immunities", creature.damage_immunities()),
+    ] {
+        let text = values
+            .map(|x| render::lowercase_list(x))
+            .unwrap_or_default();
+        value.insert(key.to_string(), json!(text));
+    }
+    value.insert(
+        String::from("condition_immunities"),
+        json!(creature
+            .condition_immunities()
+            .map(|x| render::lowercase_list(x))
+            .unwrap_or_default()),
+    );
+    value.insert(String::from("senses"), json!(render::senses(creature)));
+    value.insert(
+        String::from("languages"),
+        json!(creature
+            .languages()
+            .map(|x| render::list(x))
+            .unwrap_or_default()),
+    );
+    value.insert(
+        String::from("challenge_rating"),
+        json!(creature.challenge_rating()),
+    );
+    value.insert(String::from("cr"), json!(creature.challenge_rating_value()));
+
+    let sections = render::action_sections(creature);
+    value.insert(
+        String::from("actions"),
+        Value::Array(sections.actions.iter().map(|x| action_entry(x)).collect()),
+    );
+    value.insert(
+        String::from("reactions"),
+        Value::Array(sections.reactions.iter().map(|x| action_entry(x)).collect()),
+    );
+    value.insert(
+        String::from("legendary_desc"),
+        json!(if sections.legendary_actions.is_empty() {
+            String::new()
+        } else {
+            render::legendary_intro(creature)
+        }),
+    );
+    value.insert(
+        String::from("legendary_actions"),
+        Value::Array(
+            sections
+                .legendary_actions
+                .iter()
+                .map(|x| action_entry(x))
+                .collect(),
+        ),
+    );
+    value.insert(
+        String::from("special_abilities"),
+        Value::Array(
+            creature
+                .racial_traits()
+                .into_iter()
+                .flatten()
+                .map(|x| json!({ "name": x.name, "desc": x.description }))
+                .collect(),
+        ),
+    );
+    value.insert(
+        String::from("desc"),
+        json!(creature.description().unwrap_or_default()),
+    );
+    report_extra_attributes(creature, &mut report);
+    (Value::Object(value), report)
+}
+
+fn action_entry(action: &ActionType) -> Value {
+    let (name, description) = action_text(action);
+    let mut value = json!({ "name": name, "desc": description });
+    if let ActionType::Attack(attack) = action {
+        let dice: Vec<String> = attack
+            .damage()
+            .iter()
+            .map(|x| format!("{}{}", x.damage.die_count, x.damage.die_type))
+            .collect();
+        let bonus: i32 = attack.damage().iter().map(|x| x.damage.extra).sum();
+        value["attack_bonus"] = json!(attack.modifier());
+        value["damage_dice"] = json!(dice.join("+"));
+        value["damage_bonus"] = json!(Some(bonus).filter(|x| *x != 0));
+    }
+    value
+}
+
+fn snake_case(name: &str) -> String {
+    let mut text = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            text.push('_');
+        }
+        text.push(c.to_ascii_lowercase());
+    }
+    text
+}
+
+pub fn import_spell(value: &Value) -> Result<(Spell, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let mut description = reader.text("desc").unwrap_or_default();
+    if let Some(higher_level) = reader.text("higher_level") {
+        description.push_str("\n\nAt Higher Levels. ");
+        description.push_str(&higher_level);
+    }
+    let level = match reader.int("level_int") {
+        Some(x) => x,
+        None => match reader.str("level") {
+            Some(x) if x.to_lowercase().contains("cantrip") => 0,
+            Some(x) => super::leading_int(&json!(x))
+                .ok_or_else(|| ParseError::new(x, "invalid spell level"))?,
+            None => return Err(ConvertError::MissingField("level")),
+        },
+    };
+    reader.value("level");
+    let level = spell_level(level)
+        .ok_or_else(|| ParseError::new(&level.to_string(), "invalid spell level"))?;
+
+    let text = reader
+        .str("casting_time")
+        .ok_or(ConvertError::MissingField("casting_time"))?;
+    let (casting_time, exact) =
+        casting_time(text).ok_or_else(|| ParseError::new(text, "unknown casting time"))?;
+    if !exact {
+        report.push(&entity, "casting_time", text, "only the unit was kept");
+    }
+
+    let concentration = reader.bool("concentration");
+    let text = reader
+        .str("duration")
+        .ok_or(ConvertError::MissingField("duration"))?;
+    let duration =
+        duration(text, concentration).ok_or_else(|| ParseError::new(text, "unknown duration"))?;
+    if !duration_text(&duration).eq_ignore_ascii_case(text) {
+        report.push(&entity, "duration", text, "only the unit was kept");
+    }
+
+    let text = reader
+        .str("range")
+        .ok_or(ConvertError::MissingField("range"))?;
+    let (range, area) = spell_range(text, &entity, &mut report)
+        .ok_or_else(|| ParseError::new(text, "unknown spell range"))?;
+
+    let text = reader
+        .str("components")
+        .ok_or(ConvertError::MissingField("components"))?;
+    let components = components(text).ok_or_else(|| ParseError::new(text, "no components"))?;
+    for key in [
+        "requires_verbal_components",
+        "requires_somatic_components",
+        "requires_material_components",
+    ] {
+        reader.value(key);
+    }
+    reader.skip(&mut report, "material", "no matching field");
+    if reader.bool("ritual") {
+        report.push(&entity, "ritual", "yes", "no matching field");
+    }
+    for key in ["school", "dnd_class", "spell_lists", "archetype", "circles"] {
+        reader.skip(&mut report, key, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    let damage = damage_from_text(&description);
+    let save = save_from_text(&description);
+    Ok((
+        Spell {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            level,
+            casting_time,
+            duration,
+            damage: damage.as_ref().map(|(x, _)| x.clone()),
+            range,
+            area,
+            damage_type: damage.map(|(_, x)| x),
+            components,
+            attack_bonus: None,
+            save,
+            description,
+        },
+        report,
+    ))
+}
+
+pub fn export_spell(spell: &Spell) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let (description, higher_level) = match spell.description.split_once("\n\nAt Higher Levels. ") {
+        Some((description, higher_level)) => (description, higher_level),
+        None => (spell.description.as_str(), ""),
+    };
+    let level = spell_level_number(&spell.level);
+    let components = spell.components.to_string();
+    if spell.range != SpellRange::S {
+        if let Some(area) = &spell.area {
+            let (shape, size) = area_shape(area);
+            report.push(
+                &spell.name,
+                "area",
+                format!("{} {}", size, shape),
+                "no matching field",
+            );
+        }
+    }
+    if let Some(bonus) = spell.attack_bonus {
+        report.push(&spell.name, "attack_bonus", bonus, "no matching field");
+    }
+    let damage = spell.damage.clone().zip(spell.damage_type.clone());
+    if damage.is_some() && damage != damage_from_text(description) {
+        report.push(
+            &spell.name,
+            "damage",
+            spell.damage.as_ref().map(compact_dice).unwrap_or_default(),
+            "not mentioned in the description",
+        );
+    }
+    if spell.save.is_some()
+        && spell.save.as_ref().map(|x| super::save_parts(x).0)
+            != save_from_text(description)
+                .as_ref()
+                .map(|x| super::save_parts(x).0)
+    {
+        report.push(
+            &spell.name,
+            "save",
+            spell
+                .save
+                .as_ref()
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            "not mentioned in the description",
+        );
+    }
+    let value = json!({
+        "slug": slug(&spell.name),
+        "name": spell.name,
+        "desc": description,
+        "higher_level": higher_level,
+        "range": spell_range_text(&spell.range, spell.area.as_ref()),
+        "components": components,
+        "requires_verbal_components": components.contains('V'),
+        "requires_somatic_components": components.contains('S'),
+        "requires_material_components": components.contains('M'),
+        "ritual": "no",
+        "duration": duration_text(&spell.duration),
+        "concentration": if spell.duration == crate::spell::Duration::Concentration { "yes" } else { "no" },
+        "casting_time": casting_time_text(&spell.casting_time),
+        "level": match level {
+            0 => String::from("Cantrip"),
+            _ => format!("{}-level", spell.level.to_string().trim_end_matches(" Level")),
+        },
+        "level_int": level,
+    });
+    (value, report)
+}
+
+pub fn import_item(value: &Value) -> Result<(Item, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let text = reader
+        .str("type")
+        .ok_or(ConvertError::MissingField("type"))?;
+    let (item_type, weapon_type, armor_type) =
+        item_type(text).ok_or_else(|| ParseError::new(text, "unknown item type"))?;
+    if text.contains('(') && weapon_type.is_none() && armor_type.is_none() {
+        report.push(&entity, "type", text, "only the item type was kept");
+    }
+    let rarity = match reader.str("rarity") {
+        Some(text) => rarity(text).unwrap_or_else(|| {
+            report.push(&entity, "rarity", text, "kept as varies");
+            crate::item::ItemRarity::Varies
+        }),
+        None => crate::item::ItemRarity::Unknown,
+    };
+    let attunement = reader.str("requires_attunement").and_then(attunement);
+    let others = reader.text("desc").map(|description| {
+        vec![OtherAttribute {
+            title: String::from("Description"),
+            description,
+            value: String::new(),
+        }]
+    });
+    reader.finish(&mut report);
+
+    Ok((
+        Item {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            item_type,
+            rarity,
+            attunement,
+            weapon_type,
+            armor_type,
+            conditions: None,
+            attached_spell: None,
+            has_charges: None,
+            inventory: None,
+            others,
+            actions: None,
+        },
+        report,
+    ))
+}
+
+pub fn export_item(item: &Item) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut description = Vec::new();
+    for other in item.others.iter().flatten() {
+        if other.title == "Description" {
+            description.push(other.description.clone());
+        } else {
+            report.push(&item.name, "others", other, "no matching field");
+        }
+    }
+    super::report_item_extras(item, &mut report);
+    let value = json!({
+        "slug": slug(&item.name),
+        "name": item.name,
+        "type": item_type_text(&item.item_type, item.weapon_type.as_ref(), item.armor_type.as_ref()),
+        "desc": description.join("\n\n"),
+        "rarity": item.rarity.to_string().to_lowercase(),
+        "requires_attunement": item.attunement.as_ref().map(attunement_text).unwrap_or_default(),
+    });
+    (value, report)
+}
+
+mod test {
+    #[test]
+    fn test_import_creature() {
+        use crate::creature::{CreatureType, MonsterType, Size};
+
+        let value = serde_json::json!({
+            "slug": "young-red-dragon",
+            "name": "Young Red Dragon",
+            "size": "Large",
+            "type": "dragon",
+            "subtype": "",
+            "alignment": "chaotic evil",
+            "armor_class": "18 (natural armor)",
+            "hit_points": 178,
+            "hit_dice": "17d10+85",
+            "speed": { "walk": 40, "climb": 40, "fly": 80 },
+            "strength": 23, "dexterity": 10, "constitution": 21,
+            "intelligence": 14, "wisdom": 11, "charisma": 19,
+            "strength_save": null, "dexterity_save": 4, "constitution_save": 9,
+            "intelligence_save": null, "wisdom_save": 4, "charisma_save": 8,
+            "perception": 8,
+            "skills": { "perception": 8, "stealth": 4 },
+            "damage_vulnerabilities": "",
+            "damage_resistances": "",
+            "damage_immunities": "fire",
+            "condition_immunities": "",
+            "senses": "blindsight 30 ft., darkvision 120 ft., passive Perception 18",
+            "languages": "Common, Draconic",
+            "challenge_rating": "10",
+            "cr": 10.0,
+            "actions": [{
+                "name": "Bite",
+                "desc": "Melee Weapon Attack: +10 to hit, reach 10 ft., one target. Hit: 17 (2d10 + 6) piercing damage plus 3 (1d6) fire damage.",
+                "attack_bonus": 10,
+                "damage_dice": "2d10+1d6",
+                "damage_bonus": 6
+            }, {
+                "name": "Fire Breath (Recharge 5-6)",
+                "desc": "The dragon exhales fire in a 30-foot cone."
+            }],
+            "reactions": "",
+            "legendary_desc": "",
+            "legendary_actions": "",
+            "special_abilities": "",
+            "spell_list": [],
+            "environments": ["Mountain"],
+            "document__slug": "wotc-srd"
+        });
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(creature.name(), "Young Red Dragon");
+        assert_eq!(creature.size(), &Size::Large);
+        assert_eq!(
+            creature.creature_type(),
+            &CreatureType::Monster(MonsterType::Dragon)
+        );
+        assert_eq!(*creature.armor_class(), 18);
+        assert_eq!(creature.health_points().health.value(), 178);
+        assert_eq!(creature.saving_throws().unwrap().len(), 4);
+        assert_eq!(creature.senses().unwrap().len(), 2);
+        assert_eq!(
+            crate::render::speeds(&creature),
+            "40 ft., climb 40 ft., fly 80 ft."
+        );
+        let actions = creature.actions().unwrap();
+        assert!(matches!(
+            &actions[0].action,
+            crate::action::ActionType::Attack(x) if x.average_damage() == 20
+        ));
+        assert!(matches!(
+            &actions[1].action,
+            crate::action::ActionType::Special(_)
+        ));
+        assert_eq!(
+            report
+                .unmapped
+                .iter()
+                .map(|x| x.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["armor_class", "environments"]
+        );
+
+        let mut value = value;
+        value["strength"] = serde_json::json!(-2147483648_i64);
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(creature.stats()[0].value, 1);
+        assert!(report.unmapped.iter().any(|x| x.field == "strength"));
+
+        value["armor_class"] = serde_json::json!(4294967297_i64);
+        value["dexterity_save"] = serde_json::json!(-4294967296_i64);
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(*creature.armor_class(), 10);
+        assert!(report
+            .unmapped
+            .iter()
+            .any(|x| x.field == "armor_class" && x.value == "4294967297"));
+        assert!(report.unmapped.iter().any(|x| x.field == "dexterity_save"));
+    }
+
+    #[test]
+    fn test_creature_round_trip() {
+        let value = serde_json::json!({
+            "name": "Goblin",
+            "size": "Small",
+            "type": "humanoid",
+            "alignment": "neutral evil",
+            "armor_class": 15,
+            "hit_dice": "2d6",
+            "speed": { "walk": 30 },
+            "strength": 8, "dexterity": 14, "constitution": 10,
+            "intelligence": 10, "wisdom": 8, "charisma": 8,
+            "skills": { "stealth": 6 },
+            "senses": "darkvision 60 ft., passive Perception 9",
+            "languages": "Common, Goblin",
+            "challenge_rating": "1/4",
+            "actions": [{
+                "name": "Scimitar",
+                "desc": "Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage."
+            }],
+            "special_abilities": [{
+                "name": "Nimble Escape",
+                "desc": "The goblin can take the Disengage or Hide action as a bonus action on each of its turns."
+            }]
+        });
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert!(report.is_empty());
+        let (exported, report) = super::export_creature(&creature);
+        assert!(report.is_empty());
+        assert_eq!(exported["hit_dice"], "2d6");
+        assert_eq!(exported["hit_points"], 7);
+        assert_eq!(exported["skills"]["stealth"], 6);
+        assert_eq!(exported["cr"], 0.25);
+        assert_eq!(exported["actions"][0]["attack_bonus"], 4);
+        assert_eq!(exported["actions"][0]["damage_dice"], "1d6");
+        assert_eq!(exported["actions"][0]["damage_bonus"], 2);
+
+        let (again, report) = super::import_creature(&exported).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(
+            crate::render::markdown::creature(&again),
+            crate::render::markdown::creature(&creature)
+        );
+    }
+
+    #[test]
+    fn test_spell_and_item() {
+        use crate::spell::{Area, CastingTime, Duration, Save, SpellLevel, SpellRange};
+
+        let value = serde_json::json!({
+            "slug": "burning-hands",
+            "name": "Burning Hands",
+            "desc": "Each creature in a 15-foot cone must make a Dexterity saving throw. A creature takes 3d6 fire damage on a failed save, or half as much damage on a successful one.",
+            "higher_level": "The damage increases by 1d6 for each slot level above 1st.",
+            "range": "Self (15-foot cone)",
+            "components": "V, S",
+            "material": "",
+            "ritual": "no",
+            "duration": "Instantaneous",
+            "concentration": "no",
+            "casting_time": "1 action",
+            "level": "1st-level",
+            "level_int": 1,
+            "school": "Evocation",
+            "dnd_class": "Sorcerer, Wizard"
+        });
+        let (spell, report) = super::import_spell(&value).unwrap();
+        assert_eq!(spell.level, SpellLevel::Level1);
+        assert_eq!(spell.casting_time, CastingTime::Action);
+        assert_eq!(spell.duration, Duration::Instantaneous);
+        assert_eq!(spell.range, SpellRange::S);
+        assert_eq!(spell.area, Some(Area::Cone(15)));
+        assert_eq!(spell.damage_type, Some(crate::DamageType::Fire));
+        assert_eq!(spell.save, Some(Save::Dexterity(None)));
+        assert_eq!(report.unmapped.len(), 2);
+
+        let (exported, report) = super::export_spell(&spell);
+        assert!(report.is_empty());
+        assert_eq!(exported["range"], "Self (15-foot cone)");
+        assert_eq!(exported["level"], "1st-level");
+        assert_eq!(exported["higher_level"], value["higher_level"]);
+
+        let mut far = value.clone();
+        far["range"] = serde_json::json!("999999 miles");
+        let (spell, report) = super::import_spell(&far).unwrap();
+        assert_eq!(spell.range, SpellRange::Range(i32::MAX));
+        assert!(report
+            .unmapped
+            .iter()
+            .any(|x| x.field == "range" && x.value == "999999 miles"));
+
+        let value = serde_json::json!({
+            "name": "Flame Tongue",
+            "type": "Weapon (any sword)",
+            "desc": "You can use a bonus action to speak this magic sword's command word.",
+            "rarity": "rare",
+            "requires_attunement": "requires attunement"
+        });
+        let (item, report) = super::import_item(&value).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(item.weapon_type, Some(crate::item::WeaponType::Sword));
+        assert!(item.attunement.is_some());
+        let (exported, report) = super::export_item(&item);
+        assert!(report.is_empty());
+        assert_eq!(exported["type"], "Weapon (sword)");
+        assert_eq!(exported["desc"], value["desc"]);
+        assert_eq!(exported["requires_attunement"], "requires attunement");
+    }
+}
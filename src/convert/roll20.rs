@@ -0,0 +1,481 @@
+//! Roll20 characters for the D&D 5E by Roll20 sheet.
+//!
+//! [`export_player`] produces the character JSON used by the Roll20 character vault, where every
+//! value on the sheet is an attribute. Lists such as the inventory and spells are repeating
+//! sections whose attributes are named `repeating_<section>_<row id>_<field>`.
+
+use serde_json::{json, Value};
+
+use crate::{
+    action::{attack::Attack, ActionType},
+    creature::StatType,
+    item::{Item, ItemType},
+    render,
+    spell::{Spell, SpellLevel},
+    world::campaign::player::Player,
+};
+
+use super::{
+    area_phrase, attack_ability, attunement_text, casting_time_text, compact_dice, duration_text,
+    hit_die, item_type_text, proficiency_bonus, race_text, report_item_extras, save_parts,
+    skill_proficiency, spell_range_text, stable_hash, ConversionReport,
+};
+
+/// A 20 character Roll20 style object id, derived from `seed` so that exports are repeatable.
+fn row_id(seed: &str) -> String {
+    format!("-{:019x}", stable_hash(seed))
+}
+
+/// The attributes of one character, each with a current and max value.
+struct Attributes {
+    seed: String,
+    attributes: Vec<Value>,
+}
+
+impl Attributes {
+    fn set(&mut self, name: &str, current: impl ToString) {
+        self.set_max(name, current, "");
+    }
+
+    fn set_max(&mut self, name: &str, current: impl ToString, max: impl ToString) {
+        self.attributes.push(json!({
+            "name": name,
+            "current": current.to_string(),
+            "max": max.to_string(),
+            "id": row_id(&format!("{}{}", self.seed, name)),
+        }));
+    }
+
+    /// Adds a row to a repeating section.
+    fn row(&mut self, section: &str, seed: &str, fields: Vec<(&str, String)>) {
+        let row = row_id(&format!("{}{}{}", self.seed, section, seed));
+        for (field, value) in fields {
+            self.set(&format!("repeating_{}_{}_{}", section, row, field), value);
+        }
+    }
+}
+
+fn skill_name(skill: &crate::creature::SkillType) -> String {
+    let name = skill.to_string();
+    name.split(" (")
+        .next()
+        .unwrap_or(&name)
+        .to_lowercase()
+        .replace(' ', "_")
+}
+
+fn attack(attributes: &mut Attributes, seed: &str, attack: &Attack, player: &Player) {
+    let creature = player.creature();
+    let ability = attack_ability(attack, creature);
+    let modifier = creature
+        .stat(&ability)
+        .map(|x| x.modifier)
+        .unwrap_or_default();
+    let proficiency = proficiency_bonus(*player.level());
+    let text = render::attack_text(attack);
+    let mut fields = vec![
+        ("atkname", text.name.clone()),
+        (
+            "atkattr_base",
+            format!("@{{{}_mod}}", ability.to_string().to_lowercase()),
+        ),
+        (
+            "atkbonus",
+            render::modifier(
+                attack
+                    .modifier()
+                    .saturating_sub(modifier)
+                    .saturating_sub(proficiency),
+            ),
+        ),
+        ("atkprofflag", String::from("(@{pb})")),
+        ("atkrange", text.details.clone()),
+        ("atk_desc", text.description.clone()),
+    ];
+    let mut damage = attack.damage().iter().filter(|x| x.condition.is_none());
+    if let Some(first) = damage.next() {
+        let dice = crate::DieStat {
+            extra: 0,
+            ..first.damage.clone()
+        };
+        fields.push(("dmgbase", compact_dice(&dice)));
+        fields.push(("dmgattr", String::from("@{atkattr_base}")));
+        fields.push(("dmgmod", (first.damage.extra - modifier).to_string()));
+        fields.push(("dmgtype", first.damage_type.to_string()));
+    }
+    if let Some(second) = damage.next() {
+        fields.push(("dmg2flag", String::from("{{dmg2flag=1}}")));
+        fields.push(("dmg2base", compact_dice(&second.damage)));
+        fields.push(("dmg2type", second.damage_type.to_string()));
+    }
+    attributes.row("attack", seed, fields);
+}
+
+fn item(attributes: &mut Attributes, item: &Item, report: &mut ConversionReport) {
+    report_item_extras(item, report);
+    let description = item
+        .others
+        .iter()
+        .flatten()
+        .filter(|x| x.title == "Description")
+        .map(|x| x.description.clone())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let mut properties = vec![
+        item_type_text(
+            &item.item_type,
+            item.weapon_type.as_ref(),
+            item.armor_type.as_ref(),
+        ),
+        item.rarity.to_string().to_lowercase(),
+    ];
+    if let Some(attunement) = &item.attunement {
+        properties.push(attunement_text(attunement));
+    }
+    let kind = match &item.item_type {
+        ItemType::WondrousItem => String::from("Wondrous Item"),
+        other => other.to_string(),
+    };
+    attributes.row(
+        "inventory",
+        &item.id,
+        vec![
+            ("itemname", item.name.clone()),
+            ("itemcount", String::from("1")),
+            ("itemcontent", description),
+            ("itemproperties", properties.join(", ")),
+            ("itemmodifiers", format!("Item Type: {}", kind)),
+            ("equipped", String::from("1")),
+        ],
+    );
+}
+
+fn spell(attributes: &mut Attributes, spell: &Spell) {
+    let (section, level) = match spell.level {
+        SpellLevel::Cantrip => (String::from("spell-cantrip"), String::from("cantrip")),
+        ref other => {
+            let number = super::spell_level_number(other);
+            (format!("spell-{}", number), number.to_string())
+        }
+    };
+    let letters = spell.components.to_string();
+    let flag = |letter: char, name: &str| {
+        if letters.contains(letter) {
+            format!("{{{{{}=1}}}}", name)
+        } else {
+            String::from("0")
+        }
+    };
+    let mut fields = vec![
+        ("spellname", spell.name.clone()),
+        ("spelllevel", level),
+        (
+            "spellcastingtime",
+            casting_time_text(&spell.casting_time).to_string(),
+        ),
+        ("spellrange", spell_range_text(&spell.range, None)),
+        (
+            "spelltarget",
+            spell.area.as_ref().map(area_phrase).unwrap_or_default(),
+        ),
+        ("spellcomp_v", flag('V', "v")),
+        ("spellcomp_s", flag('S', "s")),
+        ("spellcomp_m", flag('M', "m")),
+        (
+            "spellconcentration",
+            if spell.duration == crate::spell::Duration::Concentration {
+                String::from("{{concentration=1}}")
+            } else {
+                String::from("0")
+            },
+        ),
+        ("spellduration", duration_text(&spell.duration).to_string()),
+        ("spelldescription", spell.description.clone()),
+        (
+            "spellattack",
+            String::from(if spell.attack_bonus.is_some() {
+                "Ranged"
+            } else {
+                "None"
+            }),
+        ),
+        ("spellprepared", String::from("1")),
+    ];
+    if let Some(save) = &spell.save {
+        let (ability, _) = save_parts(save);
+        let ability = ability
+            .parse::<StatType>()
+            .map(|x| x.to_string())
+            .unwrap_or_default();
+        fields.push(("spellsave", ability));
+    }
+    if let Some(damage) = &spell.damage {
+        fields.push(("spelldamage", compact_dice(damage)));
+        fields.push((
+            "spelldamagetype",
+            spell
+                .damage_type
+                .as_ref()
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+        ));
+    }
+    attributes.row(&section, &spell.id, fields);
+}
+
+/// Exports a player as a Roll20 character.
+pub fn export_player(player: &Player) -> (Value, ConversionReport) {
+    use strum::IntoEnumIterator;
+
+    let mut report = ConversionReport::default();
+    let creature = player.creature();
+    let mut attributes = Attributes {
+        seed: player.id().to_string(),
+        attributes: Vec::new(),
+    };
+
+    attributes.set("npc", 0);
+    attributes.set("race", race_text(player.race()));
+    attributes.set("class", player.class());
+    attributes.set("base_level", player.level());
+    attributes.set("level", player.level());
+    attributes.set("hitdietype", hit_die(player.class()).to_i32());
+    attributes.set("experience", player.experience());
+    attributes.set("alignment", creature.alignment());
+    attributes.set("size", creature.size());
+    attributes.set("pb", proficiency_bonus(*player.level()));
+    attributes.set("ac", creature.armor_class());
+    let health = creature.health_points().health.value();
+    attributes.set_max("hp", health, health);
+    attributes.set("speed", render::speed(creature.speed()));
+
+    for stat_type in StatType::iter() {
+        let name = stat_type.to_string().to_lowercase();
+        let stat = creature.stat(&stat_type);
+        let score = stat.map(|x| x.value).unwrap_or(10);
+        attributes.set(&name, score);
+        attributes.set(&format!("{}_base", name), score);
+        let proficient = creature
+            .saving_throws()
+            .into_iter()
+            .flatten()
+            .any(|x| x.stat_type == stat_type);
+        attributes.set(
+            &format!("{}_save_prof", name),
+            if proficient { "(@{pb})" } else { "0" },
+        );
+    }
+    for skill in creature.skills().into_iter().flatten() {
+        let name = skill_name(&skill.skill_type);
+        let level = skill_proficiency(
+            creature,
+            skill,
+            proficiency_bonus(*player.level()),
+            &mut report,
+        );
+        if level >= 1.0 {
+            attributes.set(
+                &format!("{}_prof", name),
+                format!("(@{{pb}}*@{{{}_type}})", name),
+            );
+            attributes.set(&format!("{}_type", name), level);
+        } else if level > 0.0 {
+            report.push(
+                player.name(),
+                "skills",
+                render::skill(skill),
+                "half proficiency is set for all skills at once",
+            );
+        }
+    }
+
+    for language in creature.languages().into_iter().flatten() {
+        attributes.row(
+            "proficiencies",
+            &language.to_string(),
+            vec![
+                ("prof_type", String::from("LANGUAGE")),
+                ("name", language.to_string()),
+            ],
+        );
+    }
+    for racial_trait in creature.racial_traits().into_iter().flatten() {
+        attributes.row(
+            "traits",
+            &racial_trait.name,
+            vec![
+                ("name", racial_trait.name.clone()),
+                ("source", String::from("Racial")),
+                ("source_type", race_text(player.race()).to_string()),
+                ("description", racial_trait.description.clone()),
+            ],
+        );
+    }
+    for action in creature.actions().into_iter().flatten() {
+        match &action.action {
+            ActionType::Attack(x) => {
+                for damage in x.damage().iter().filter(|x| x.condition.is_some()) {
+                    report.push(
+                        player.name(),
+                        "damage.condition",
+                        damage,
+                        "no matching field",
+                    );
+                }
+                if x.damage().iter().filter(|x| x.condition.is_none()).count() > 2 {
+                    report.push(
+                        player.name(),
+                        "damage",
+                        render::attack_text(x).hit,
+                        "only two damage rolls are kept",
+                    );
+                }
+                attack(&mut attributes, &action.id, x, player)
+            }
+            ActionType::Special(x) | ActionType::Reaction(x) | ActionType::LegendaryAction(x) => {
+                attributes.row(
+                    "traits",
+                    &action.id,
+                    vec![
+                        ("name", x.name.clone()),
+                        ("source", String::from("Class")),
+                        ("source_type", player.class().to_string()),
+                        ("description", x.description.clone()),
+                    ],
+                )
+            }
+        }
+    }
+    for x in player.inventory() {
+        item(&mut attributes, x, &mut report);
+    }
+    for x in player.spells() {
+        spell(&mut attributes, x);
+    }
+
+    for (field, values) in [
+        ("damage_immunities", creature.damage_immunities()),
+        ("damage_resistances", creature.damage_resistances()),
+        ("damage_vulnerabilities", creature.damage_vulnerabilities()),
+    ] {
+        if let Some(values) = values {
+            report.push(
+                player.name(),
+                field,
+                render::lowercase_list(values),
+                "no matching field",
+            );
+        }
+    }
+    if let Some(conditions) = creature.condition_immunities() {
+        report.push(
+            player.name(),
+            "condition_immunities",
+            render::lowercase_list(conditions),
+            "no matching field",
+        );
+    }
+    if let Some(senses) = creature.senses() {
+        report.push(
+            player.name(),
+            "senses",
+            render::list(senses),
+            "no matching field",
+        );
+    }
+    super::report_extra_attributes(creature, &mut report);
+
+    let character = json!({
+        "schema_version": 3,
+        "type": "character",
+        "character": {
+            "oldId": row_id(&player.id().to_string()),
+            "name": player.name(),
+            "avatar": "",
+            "bio": creature.description().unwrap_or_default(),
+            "gmnotes": "",
+            "defaulttoken": "",
+            "tags": "[]",
+            "controlledby": "",
+            "inplayerjournals": "",
+            "attribs": attributes.attributes,
+            "abilities": [],
+        },
+    });
+    (character, report)
+}
+
+mod test {
+    #[test]
+    fn test_export_player() {
+        use std::collections::BTreeSet;
+
+        /// Attribute names with the row ids of repeating sections replaced by `*`.
+        fn names(character: &serde_json::Value) -> BTreeSet<String> {
+            character["character"]["attribs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|x| {
+                    let name = x["name"].as_str().unwrap();
+                    match name.strip_prefix("repeating_") {
+                        Some(rest) => {
+                            let mut parts = rest.splitn(2, "_-");
+                            let section = parts.next().unwrap();
+                            // Row ids are a dash followed by 19 characters.
+                            let field = &parts.next().unwrap()[20..];
+                            format!("repeating_{}_*_{}", section, field)
+                        }
+                        None => name.to_string(),
+                    }
+                })
+                .collect()
+        }
+
+        let player = crate::convert::test::tessa();
+        let (character, report) = super::export_player(&player);
+        let fields: Vec<&str> = report.unmapped.iter().map(|x| x.field.as_str()).collect();
+        assert_eq!(fields, vec!["senses"]);
+
+        let sample: serde_json::Value =
+            serde_json::from_str(include_str!("samples/roll20_character.json")).unwrap();
+        let keys =
+            |x: &serde_json::Value| x.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&character), keys(&sample));
+        assert_eq!(keys(&character["character"]), keys(&sample["character"]));
+        for attribute in character["character"]["attribs"].as_array().unwrap() {
+            assert_eq!(keys(attribute), keys(&sample["character"]["attribs"][0]));
+            assert!(attribute["current"].is_string() && attribute["max"].is_string());
+        }
+        let sample = names(&sample);
+        for name in names(&character) {
+            assert!(
+                sample.contains(&name),
+                "{} is not in the sample character",
+                name
+            );
+        }
+
+        let attribute = |name: &str| {
+            character["character"]["attribs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|x| x["name"].as_str().unwrap().ends_with(name))
+                .map(|x| x["current"].as_str().unwrap().to_string())
+                .unwrap()
+        };
+        assert_eq!(attribute("race"), "Elf");
+        assert_eq!(attribute("hitdietype"), "6");
+        assert_eq!(attribute("intelligence_save_prof"), "(@{pb})");
+        assert_eq!(attribute("arcana_type"), "1");
+        assert_eq!(attribute("history_type"), "2");
+        assert_eq!(attribute("_atkbonus"), "-1");
+        assert_eq!(attribute("_dmgbase"), "1d6");
+        assert_eq!(attribute("_itemname"), "Wand of Magic Missiles");
+        assert_eq!(attribute("_spellname"), "Shield");
+
+        let (again, _) = super::export_player(&player);
+        assert_eq!(again, character);
+    }
+}
@@ -0,0 +1,852 @@
+//! The 5e SRD API's (<https://www.dnd5eapi.co>) monster, spell and magic item JSON.
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    action::{Action, ActionType},
+    creature::{Creature, CreatureType, Health, RacialTrait, Skill, SkillType, Stat, StatType},
+    id::CreatureId,
+    item::{Item, ItemRarity},
+    render,
+    spell::Spell,
+    DamageType, OtherAttribute, ParseError,
+};
+
+use super::{
+    action, action_text, area, area_shape, attunement, attunement_text, casting_time,
+    casting_time_text, challenge_rating_from_f64, compact_dice, components, conditions,
+    damage_types, display, duration, duration_text, item_type, item_type_text, languages,
+    leading_int, narrow, rarity, report_extra_attributes, report_item_extras, save, save_parts,
+    senses, slug, special, speed_entries, speeds, spell_level, spell_level_number, spell_range,
+    spell_range_text, ConversionReport, ConvertError, Reader, ABILITIES,
+};
+
+/// Fields that only describe where an entry lives in the API.
+const PROVENANCE: [&str; 4] = ["index", "url", "image", "updated_at"];
+
+/// An `{ "index": ..., "name": ... }` reference to another API resource.
+fn reference(name: impl ToString) -> Value {
+    let name = name.to_string();
+    json!({ "index": slug(&name), "name": name })
+}
+
+fn reference_name(value: &Value) -> Option<&str> {
+    value.get("name").and_then(Value::as_str)
+}
+
+pub fn import_creature(value: &Value) -> Result<(Creature, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let size = reader.size(&mut report);
+    let creature_type = reader.creature_type(&mut report);
+    reader.skip(&mut report, "subtype", "no matching field");
+    let alignment = reader.alignment(&mut report);
+
+    let armor_class = match reader.value("armor_class") {
+        Some(Value::Array(entries)) => {
+            for entry in entries.iter().skip(1) {
+                report.push(
+                    &entity,
+                    "armor_class",
+                    entry,
+                    "only the first armor class was kept",
+                );
+            }
+            let first = entries.first();
+            if let Some(kind) = first.and_then(|x| x.get("type")).and_then(Value::as_str) {
+                if kind != "dex" {
+                    report.push(
+                        &entity,
+                        "armor_class",
+                        kind,
+                        "armor description has no matching field",
+                    );
+                }
+            }
+            first.and_then(|x| x.get("value")).and_then(leading_int)
+        }
+        Some(x) => leading_int(x),
+        None => None,
+    }
+    .and_then(|x| {
+        narrow(
+            x,
+            Creature::ARMOR_CLASS,
+            &entity,
+            "armor_class",
+            &mut report,
+        )
+    })
+    .unwrap_or(10);
+
+    let hit_points = reader.int("hit_points");
+    let health = match (reader.str("hit_points_roll"), reader.str("hit_dice")) {
+        (Some(x), _) => x
+            .parse()
+            .map_err(|_| ParseError::new(x, "invalid hit dice"))?,
+        (None, Some(x)) => {
+            let mut dice: crate::DieStat = x
+                .parse()
+                .map_err(|_| ParseError::new(x, "invalid hit dice"))?;
+            if let Some(hit_points) = hit_points {
+                let max = crate::DieStat::MAX_EXTRA;
+                match i32::try_from(hit_points)
+                    .ok()
+                    .and_then(|x| x.checked_sub(dice.value()))
+                    .filter(|x| (-max..=max).contains(x))
+                {
+                    Some(extra) => dice.extra = extra,
+                    None => report.push(
+                        &entity,
+                        "hit_points",
+                        hit_points,
+                        "out of range for the hit dice",
+                    ),
+                }
+            }
+            dice
+        }
+        (None, None) => format!("{}", hit_points.unwrap_or_default()).parse()?,
+    };
+    let health_points = Health { health };
+
+    let speed_list = reader.speed_map(&mut report);
+    let (speed, mut others) = speeds(&speed_list, &entity, &mut report);
+
+    let stats: Vec<Stat> = ABILITIES
+        .iter()
+        .map(|(key, stat_type)| {
+            Stat::from_value(stat_type.clone(), reader.ability_score(&mut report, key))
+        })
+        .collect();
+    let mut saving_throws = Vec::new();
+    let mut skills = Vec::new();
+    for proficiency in reader.array("proficiencies") {
+        let index = proficiency
+            .get("proficiency")
+            .and_then(|x| x.get("index"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let Some(modifier) = proficiency
+            .get("value")
+            .and_then(leading_int)
+            .map_or(Some(0), |x| {
+                narrow(x, .., &entity, "proficiencies", &mut report)
+            })
+        else {
+            continue;
+        };
+        if let Some(ability) = index.strip_prefix("saving-throw-") {
+            match ability.parse::<StatType>() {
+                Ok(stat_type) => saving_throws.push(Stat {
+                    value: stats
+                        .iter()
+                        .find(|x| x.stat_type == stat_type)
+                        .map(|x| x.value)
+                        .unwrap_or(10),
+                    stat_type,
+                    modifier,
+                }),
+                Err(e) => report.push(&entity, "proficiencies", index, &e.message),
+            }
+        } else if let Some(skill) = index.strip_prefix("skill-") {
+            match skill.parse::<SkillType>() {
+                Ok(skill_type) => skills.push(Skill {
+                    skill_type,
+                    modifier,
+                }),
+                Err(e) => report.push(&entity, "proficiencies", index, &e.message),
+            }
+        } else {
+            report.push(&entity, "proficiencies", index, "unknown proficiency");
+        }
+    }
+
+    let [damage_vulnerabilities, damage_resistances, damage_immunities] = [
+        "damage_vulnerabilities",
+        "damage_resistances",
+        "damage_immunities",
+    ]
+    .map(|key| {
+        let text = reader
+            .array(key)
+            .iter()
+            .map(display)
+            .collect::<Vec<_>>()
+            .join(", ");
+        damage_types(&text, key, &entity, &mut report)
+    });
+    let condition_immunities = conditions(
+        reader
+            .array("condition_immunities")
+            .iter()
+            .filter_map(reference_name),
+        &entity,
+        &mut report,
+    );
+    let sense_list: Vec<String> = match reader.value("senses") {
+        Some(Value::Object(map)) => map
+            .iter()
+            .map(|(kind, range)| format!("{} {}", kind.replace('_', " "), display(range)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let senses = senses(sense_list.iter().map(String::as_str), &entity, &mut report);
+    let languages = reader
+        .str("languages")
+        .and_then(|x| languages(x, &entity, &mut report));
+    let challenge_rating = reader
+        .f64("challenge_rating")
+        .map(challenge_rating_from_f64)
+        .unwrap_or_default();
+    reader.value("proficiency_bonus");
+    reader.value("xp");
+
+    let racial_traits: Vec<RacialTrait> = entries(reader.array("special_abilities"))
+        .map(|(name, description)| RacialTrait {
+            name: name.to_string(),
+            description: description.to_string(),
+        })
+        .collect();
+    let mut actions: Vec<Action> = entries(reader.array("actions"))
+        .map(|(name, description)| action(name, description))
+        .collect();
+    actions.extend(
+        entries(reader.array("reactions"))
+            .map(|(name, x)| Action::new(ActionType::Reaction(special(name, x)))),
+    );
+    actions.extend(
+        entries(reader.array("legendary_actions"))
+            .map(|(name, x)| Action::new(ActionType::LegendaryAction(special(name, x)))),
+    );
+    if let Some(intro) = reader.str("legendary_desc") {
+        others.push(OtherAttribute {
+            title: String::from("Legendary Actions"),
+            description: intro.to_string(),
+            value: String::new(),
+        });
+    }
+    let description = reader.text("desc");
+    reader.skip(&mut report, "forms", "no matching field");
+    reader.finish(&mut report);
+
+    let mut creature = Creature::new(
+        CreatureId::new(),
+        entity,
+        creature_type,
+        alignment,
+        armor_class,
+        health_points,
+        speed,
+        stats,
+        Some(saving_throws).filter(|x| !x.is_empty()),
+        damage_resistances,
+        damage_immunities,
+        damage_vulnerabilities,
+        condition_immunities,
+        Some(skills).filter(|x| !x.is_empty()),
+        senses,
+        languages,
+        challenge_rating,
+        Some(racial_traits).filter(|x| !x.is_empty()),
+        description,
+        Some(actions).filter(|x| !x.is_empty()),
+        None,
+        Some(others).filter(|x| !x.is_empty()),
+    );
+    creature.set_size(size);
+    Ok((creature, report))
+}
+
+/// The `name` and `desc` of each entry in a list of traits or actions.
+fn entries(values: &[Value]) -> impl Iterator<Item = (&str, &str)> {
+    values.iter().filter_map(|x| {
+        Some((
+            x.get("name")?.as_str()?,
+            x.get("desc").and_then(Value::as_str).unwrap_or_default(),
+        ))
+    })
+}
+
+pub fn export_creature(creature: &Creature) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut value = Map::new();
+    value.insert(String::from("index"), json!(slug(creature.name())));
+    value.insert(String::from("name"), json!(creature.name()));
+    value.insert(String::from("size"), json!(creature.size().to_string()));
+    value.insert(
+        String::from("type"),
+        json!(match creature.creature_type() {
+            CreatureType::Monster(x) => x.to_string().to_lowercase(),
+            _ => String::from("humanoid"),
+        }),
+    );
+    value.insert(
+        String::from("alignment"),
+        json!(creature.alignment().to_string()),
+    );
+    value.insert(
+        String::from("armor_class"),
+        json!([{ "type": "natural", "value": creature.armor_class() }]),
+    );
+    let health = &creature.health_points().health;
+    value.insert(String::from("hit_points"), json!(health.value()));
+    value.insert(
+        String::from("hit_dice"),
+        json!(format!("{}{}", health.die_count, health.die_type)),
+    );
+    value.insert(String::from("hit_points_roll"), json!(compact_dice(health)));
+
+    let mut speed = Map::new();
+    for (kind, feet, hover) in speed_entries(creature, &mut report) {
+        speed.insert(kind.to_string(), json!(format!("{} ft.", feet)));
+        if hover {
+            speed.insert(String::from("hover"), json!(true));
+        }
+    }
+    value.insert(String::from("speed"), Value::Object(speed));
+
+    for (key, stat_type) in ABILITIES {
+        let score = creature.stat(&stat_type).map(|x| x.value).unwrap_or(10);
+        value.insert(key.to_string(), json!(score));
+    }
+    let saves = creature.saving_throws().into_iter().flatten().map(|x| {
+        let name = x.stat_type.abbreviation();
+        json!({
+            "value": x.modifier,
+            "proficiency": {
+                "index": format!("saving-throw-{}", name.to_lowercase()),
+                "name": format!("Saving Throw: {}", name),
+            },
+        })
+    });
+    let skills = creature.skills().into_iter().flatten().map(|x| {
+        let name = render::skill(x);
+        let name = name.rsplit_once(' ').map(|(x, _)| x).unwrap_or_default();
+        json!({
+            "value": x.modifier,
+            "proficiency": {
+                "index": format!("skill-{}", slug(name)),
+                "name": format!("Skill: {}", name),
+            },
+        })
+    });
+    value.insert(
+        String::from("proficiencies"),
+        Value::Array(saves.chain(skills).collect()),
+    );
+
+    for (key, values) in [
+        ("damage_vulnerabilities", creature.damage_vulnerabilities()),
+        ("damage_resistances", creature.damage_resistances()),
+        ("damage_immunities", creature.damage_immunities()),
+    ] {
+        let names: Vec<String> = values
+            .into_iter()
+            .flatten()
+            .map(|x| x.to_string().to_lowercase())
+            .collect();
+        value.insert(key.to_string(), json!(names));
+    }
+    value.insert(
+        String::from("condition_immunities"),
+        Value::Array(
+            creature
+                .condition_immunities()
+                .into_iter()
+                .flatten()
+                .map(reference)
+                .collect(),
+        ),
+    );
+    let mut senses = Map::new();
+    for sense in creature.senses().into_iter().flatten() {
+        let text = sense.to_string().to_lowercase();
+        if let Some((kind, range)) = text.split_once(' ') {
+            senses.insert(kind.to_string(), json!(range));
+        }
+    }
+    senses.insert(
+        String::from("passive_perception"),
+        json!(creature.passive_perception()),
+    );
+    value.insert(String::from("senses"), Value::Object(senses));
+    value.insert(
+        String::from("languages"),
+        json!(creature
+            .languages()
+            .map(|x| render::list(x))
+            .unwrap_or_default()),
+    );
+    value.insert(
+        String::from("challenge_rating"),
+        json!(creature.challenge_rating_value()),
+    );
+    value.insert(
+        String::from("proficiency_bonus"),
+        json!(creature.proficiency_bonus()),
+    );
+    value.insert(String::from("xp"), json!(creature.experience_points()));
+
+    let sections = render::action_sections(creature);
+    value.insert(
+        String::from("special_abilities"),
+        Value::Array(
+            creature
+                .racial_traits()
+                .into_iter()
+                .flatten()
+                .map(|x| json!({ "name": x.name, "desc": x.description }))
+                .collect(),
+        ),
+    );
+    for (key, actions) in [
+        ("actions", &sections.actions),
+        ("reactions", &sections.reactions),
+        ("legendary_actions", &sections.legendary_actions),
+    ] {
+        value.insert(
+            key.to_string(),
+            Value::Array(actions.iter().map(|x| action_entry(x)).collect()),
+        );
+    }
+    if !sections.legendary_actions.is_empty() {
+        value.insert(
+            String::from("legendary_desc"),
+            json!(render::legendary_intro(creature)),
+        );
+    }
+    if let Some(description) = creature.description() {
+        value.insert(String::from("desc"), json!(description));
+    }
+    report_extra_attributes(creature, &mut report);
+    (Value::Object(value), report)
+}
+
+fn action_entry(action: &ActionType) -> Value {
+    let (name, description) = action_text(action);
+    let mut value = json!({ "name": name, "desc": description });
+    if let ActionType::Attack(attack) = action {
+        value["attack_bonus"] = json!(attack.modifier());
+        value["damage"] = attack
+            .damage()
+            .iter()
+            .map(|x| {
+                json!({
+                    "damage_type": reference(&x.damage_type),
+                    "damage_dice": compact_dice(&x.damage),
+                })
+            })
+            .collect();
+    }
+    value
+}
+
+pub fn import_spell(value: &Value) -> Result<(Spell, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let mut description = reader.text("desc").unwrap_or_default();
+    if let Some(higher_level) = reader.text("higher_level") {
+        description.push_str("\n\nAt Higher Levels. ");
+        description.push_str(&higher_level);
+    }
+    let level = reader
+        .int("level")
+        .ok_or(ConvertError::MissingField("level"))?;
+    let level = spell_level(level)
+        .ok_or_else(|| ParseError::new(&level.to_string(), "invalid spell level"))?;
+
+    let text = reader
+        .str("casting_time")
+        .ok_or(ConvertError::MissingField("casting_time"))?;
+    let (casting_time, exact) =
+        casting_time(text).ok_or_else(|| ParseError::new(text, "unknown casting time"))?;
+    if !exact {
+        report.push(&entity, "casting_time", text, "only the unit was kept");
+    }
+
+    let concentration = reader.bool("concentration");
+    let text = reader
+        .str("duration")
+        .ok_or(ConvertError::MissingField("duration"))?;
+    let duration =
+        duration(text, concentration).ok_or_else(|| ParseError::new(text, "unknown duration"))?;
+    if !duration_text(&duration).eq_ignore_ascii_case(text) {
+        report.push(&entity, "duration", text, "only the unit was kept");
+    }
+
+    let text = reader
+        .str("range")
+        .ok_or(ConvertError::MissingField("range"))?;
+    let (range, mut spell_area) = spell_range(text, &entity, &mut report)
+        .ok_or_else(|| ParseError::new(text, "unknown spell range"))?;
+    if let Some(value) = reader.value("area_of_effect") {
+        let shape = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let size = value.get("size").and_then(leading_int).unwrap_or_default();
+        match i32::try_from(size).ok().and_then(|size| area(shape, size)) {
+            Some(x) => spell_area = Some(x),
+            None => report.push(&entity, "area_of_effect", value, "unknown area"),
+        }
+    }
+
+    let letters: String = reader.array("components").iter().map(display).collect();
+    let components = components(&letters).ok_or(ConvertError::MissingField("components"))?;
+    reader.skip(&mut report, "material", "no matching field");
+    if reader.bool("ritual") {
+        report.push(&entity, "ritual", "true", "no matching field");
+    }
+    reader.skip(&mut report, "attack_type", "no matching field");
+
+    let mut damage = None;
+    let mut damage_type = None;
+    if let Some(value) = reader.value("damage") {
+        damage_type = value
+            .get("damage_type")
+            .and_then(reference_name)
+            .and_then(|x| x.parse::<DamageType>().ok());
+        let scaling = value
+            .get("damage_at_slot_level")
+            .or_else(|| value.get("damage_at_character_level"))
+            .and_then(Value::as_object);
+        if let Some(scaling) = scaling {
+            let lowest = scaling
+                .iter()
+                .filter_map(|(level, dice)| Some((level.parse::<i32>().ok()?, dice.as_str()?)))
+                .min_by_key(|(level, _)| *level);
+            damage = lowest.and_then(|(_, dice)| dice.parse().ok());
+            if scaling.len() > 1 {
+                report.push(
+                    &entity,
+                    "damage",
+                    value,
+                    "only the lowest level damage was kept",
+                );
+            }
+        }
+    }
+    let save = reader.value("dc").and_then(|value| {
+        let ability = value.get("dc_type")?.get("index")?.as_str()?;
+        save(ability, None)
+    });
+    for key in ["heal_at_slot_level", "school", "classes", "subclasses"] {
+        reader.skip(&mut report, key, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    Ok((
+        Spell {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            description,
+            level,
+            casting_time,
+            duration,
+            damage,
+            range,
+            area: spell_area,
+            damage_type,
+            components,
+            attack_bonus: None,
+            save,
+        },
+        report,
+    ))
+}
+
+pub fn export_spell(spell: &Spell) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let (description, higher_level) = match spell.description.split_once("\n\nAt Higher Levels. ") {
+        Some((description, higher_level)) => (description, vec![higher_level]),
+        None => (spell.description.as_str(), Vec::new()),
+    };
+    let level = spell_level_number(&spell.level);
+    let mut value = json!({
+        "index": slug(&spell.name),
+        "name": spell.name,
+        "desc": description.split("\n\n").collect::<Vec<_>>(),
+        "higher_level": higher_level,
+        "range": spell_range_text(&spell.range, None),
+        "components": spell.components.to_string().split(", ").collect::<Vec<_>>(),
+        "ritual": false,
+        "duration": duration_text(&spell.duration),
+        "concentration": spell.duration == crate::spell::Duration::Concentration,
+        "casting_time": casting_time_text(&spell.casting_time),
+        "level": level,
+    });
+    if let Some(area) = &spell.area {
+        let (shape, size) = area_shape(area);
+        value["area_of_effect"] = json!({ "type": shape, "size": size });
+    }
+    if let Some(damage) = &spell.damage {
+        let scaling = if level == 0 {
+            "damage_at_character_level"
+        } else {
+            "damage_at_slot_level"
+        };
+        value["damage"] = json!({ scaling: { level.max(1).to_string(): compact_dice(damage) } });
+        if let Some(damage_type) = &spell.damage_type {
+            value["damage"]["damage_type"] = reference(damage_type);
+        }
+    }
+    if let Some(save) = &spell.save {
+        let (ability, dc) = save_parts(save);
+        value["dc"] = json!({ "dc_type": { "index": ability, "name": ability.to_uppercase() } });
+        if let Some(dc) = dc {
+            report.push(&spell.name, "save", dc, "the DC comes from the caster");
+        }
+    }
+    if let Some(bonus) = spell.attack_bonus {
+        report.push(&spell.name, "attack_bonus", bonus, "no matching field");
+    }
+    (value, report)
+}
+
+pub fn import_item(value: &Value) -> Result<(Item, ConversionReport), ConvertError> {
+    let mut report = ConversionReport::default();
+    let mut reader = Reader::ignoring(value, &PROVENANCE)?;
+    let entity = reader.entity.clone();
+
+    let paragraphs: Vec<String> = reader.array("desc").iter().map(display).collect();
+    let (summary, description) = match paragraphs.split_first() {
+        Some((summary, rest)) => (summary.as_str(), rest.join("\n\n")),
+        None => ("", String::new()),
+    };
+    // The first paragraph reads like `Weapon (any sword), rare (requires attunement)`.
+    let (summary, attunement_text) = match summary.find("(requires attunement") {
+        Some(i) => summary.split_at(i),
+        None => (summary, ""),
+    };
+    let (kind, rarity_text) = summary.rsplit_once(',').unwrap_or((summary, ""));
+
+    let category = reader
+        .value("equipment_category")
+        .and_then(reference_name)
+        .unwrap_or_default();
+    let (item_type, weapon_type, armor_type) = item_type(kind)
+        .or_else(|| item_type(category))
+        .ok_or_else(|| ParseError::new(kind, "unknown item type"))?;
+    let rarity = match reader.value("rarity").and_then(reference_name) {
+        Some(text) => rarity(text),
+        None => rarity(rarity_text),
+    }
+    .unwrap_or_else(|| {
+        report.push(&entity, "rarity", rarity_text, "kept as varies");
+        ItemRarity::Varies
+    });
+    reader.value("variant");
+    for variant in reader.array("variants") {
+        let name = reference_name(variant).unwrap_or_default();
+        report.push(&entity, "variants", name, "no matching field");
+    }
+    reader.finish(&mut report);
+
+    Ok((
+        Item {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: entity,
+            item_type,
+            rarity,
+            attunement: attunement(attunement_text),
+            weapon_type,
+            armor_type,
+            conditions: None,
+            attached_spell: None,
+            has_charges: None,
+            inventory: None,
+            others: Some(vec![OtherAttribute {
+                title: String::from("Description"),
+                description,
+                value: String::new(),
+            }])
+            .filter(|_| paragraphs.len() > 1),
+            actions: None,
+        },
+        report,
+    ))
+}
+
+pub fn export_item(item: &Item) -> (Value, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let kind = item_type_text(
+        &item.item_type,
+        item.weapon_type.as_ref(),
+        item.armor_type.as_ref(),
+    );
+    let mut summary = format!("{}, {}", kind, item.rarity.to_string().to_lowercase());
+    if let Some(attunement) = &item.attunement {
+        summary.push_str(&format!(" ({})", attunement_text(attunement)));
+    }
+    let mut desc = vec![summary];
+    for other in item.others.iter().flatten() {
+        if other.title == "Description" {
+            desc.extend(other.description.split("\n\n").map(String::from));
+        } else {
+            report.push(&item.name, "others", other, "no matching field");
+        }
+    }
+    report_item_extras(item, &mut report);
+    let value = json!({
+        "index": slug(&item.name),
+        "name": item.name,
+        "equipment_category": reference(item_type_text(&item.item_type, None, None)),
+        "rarity": { "name": item.rarity.to_string() },
+        "variants": [],
+        "variant": false,
+        "desc": desc,
+    });
+    (value, report)
+}
+
+mod test {
+    #[test]
+    fn test_import_creature() {
+        use crate::creature::{SkillType, StatType};
+
+        let value = serde_json::json!({
+            "index": "adult-black-dragon",
+            "name": "Adult Black Dragon",
+            "size": "Huge",
+            "type": "dragon",
+            "alignment": "chaotic evil",
+            "armor_class": [{ "type": "natural", "value": 19 }],
+            "hit_points": 195,
+            "hit_dice": "17d12",
+            "hit_points_roll": "17d12+85",
+            "speed": { "walk": "40 ft.", "fly": "80 ft.", "swim": "40 ft." },
+            "strength": 23, "dexterity": 14, "constitution": 21,
+            "intelligence": 14, "wisdom": 13, "charisma": 17,
+            "proficiencies": [
+                { "value": 7, "proficiency": { "index": "saving-throw-dex", "name": "Saving Throw: DEX" } },
+                { "value": 11, "proficiency": { "index": "skill-perception", "name": "Skill: Perception" } }
+            ],
+            "damage_vulnerabilities": [],
+            "damage_resistances": [],
+            "damage_immunities": ["acid"],
+            "condition_immunities": [],
+            "senses": { "blindsight": "60 ft.", "darkvision": "120 ft.", "passive_perception": 21 },
+            "languages": "Common, Draconic",
+            "challenge_rating": 14,
+            "proficiency_bonus": 5,
+            "xp": 11500,
+            "legendary_actions": [{ "name": "Detect", "desc": "The dragon makes a Wisdom (Perception) check." }],
+            "url": "/api/monsters/adult-black-dragon"
+        });
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(*creature.armor_class(), 19);
+        assert_eq!(
+            crate::render::dice(&creature.health_points().health),
+            "17d12 + 85"
+        );
+        assert_eq!(
+            creature.saving_throws().unwrap()[0].stat_type,
+            StatType::Dexterity
+        );
+        assert_eq!(
+            creature.skills().unwrap()[0].skill_type,
+            SkillType::Perception
+        );
+        assert_eq!(creature.challenge_rating(), "14");
+        assert_eq!(creature.experience_points(), Some(11500));
+        assert_eq!(report.unmapped.len(), 1);
+        assert_eq!(report.unmapped[0].value, "natural");
+
+        let (exported, report) = super::export_creature(&creature);
+        assert!(report.is_empty());
+        assert_eq!(exported["hit_points_roll"], "17d12+85");
+        assert_eq!(exported["senses"]["passive_perception"], 21);
+        let (again, _) = super::import_creature(&exported).unwrap();
+        assert_eq!(
+            crate::render::markdown::creature(&again),
+            crate::render::markdown::creature(&creature)
+        );
+
+        // Numbers that do not fit the creature are reported and left out.
+        for hit_points in [i64::from(i32::MIN), 4294967300] {
+            let value = serde_json::json!({
+                "name": "X",
+                "hit_dice": "1000d100",
+                "hit_points": hit_points,
+                "armor_class": 4294967297_i64
+            });
+            let (creature, report) = super::import_creature(&value).unwrap();
+            assert_eq!(creature.health_points().health.extra, 0);
+            assert_eq!(*creature.armor_class(), 10);
+            let fields: Vec<&str> = report.unmapped.iter().map(|x| x.field.as_str()).collect();
+            assert_eq!(fields, vec!["armor_class", "hit_points"]);
+        }
+        let value = serde_json::json!({"name": "X", "armor_class": -3});
+        let (creature, report) = super::import_creature(&value).unwrap();
+        assert_eq!(*creature.armor_class(), 10);
+        assert_eq!(report.unmapped[0].value, "-3");
+    }
+
+    #[test]
+    fn test_spell_and_item() {
+        use crate::spell::{Area, Save};
+
+        let value = serde_json::json!({
+            "index": "fireball",
+            "name": "Fireball",
+            "desc": ["A bright streak flashes from your pointing finger to a point you choose within range and then blossoms with a low roar into an explosion of flame."],
+            "higher_level": ["When you cast this spell using a spell slot of 4th level or higher, the damage increases by 1d6 for each slot level above 3rd."],
+            "range": "150 feet",
+            "components": ["V", "S", "M"],
+            "material": "A tiny ball of bat guano and sulfur.",
+            "ritual": false,
+            "duration": "Instantaneous",
+            "concentration": false,
+            "casting_time": "1 action",
+            "level": 3,
+            "damage": {
+                "damage_type": { "index": "fire", "name": "Fire" },
+                "damage_at_slot_level": { "3": "8d6", "4": "9d6" }
+            },
+            "dc": { "dc_type": { "index": "dex", "name": "DEX" }, "dc_success": "half" },
+            "area_of_effect": { "type": "sphere", "size": 20 },
+            "school": { "index": "evocation", "name": "Evocation" }
+        });
+        let (spell, report) = super::import_spell(&value).unwrap();
+        assert_eq!(spell.area, Some(Area::Sphere(20)));
+        assert_eq!(spell.damage, Some("8d6".parse().unwrap()));
+        assert_eq!(spell.save, Some(Save::Dexterity(None)));
+        assert_eq!(
+            report
+                .unmapped
+                .iter()
+                .map(|x| x.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["material", "damage", "school"]
+        );
+        let (exported, report) = super::export_spell(&spell);
+        assert!(report.is_empty());
+        assert_eq!(exported["damage"]["damage_at_slot_level"]["3"], "8d6");
+        assert_eq!(exported["components"], value["components"]);
+
+        let value = serde_json::json!({
+            "index": "ring-of-protection",
+            "name": "Ring of Protection",
+            "equipment_category": { "index": "ring", "name": "Ring" },
+            "rarity": { "name": "Rare" },
+            "variants": [],
+            "variant": false,
+            "desc": [
+                "Ring, rare (requires attunement)",
+                "You gain a +1 bonus to AC and saving throws while wearing this ring."
+            ]
+        });
+        let (item, report) = super::import_item(&value).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(item.item_type, crate::item::ItemType::Ring);
+        assert!(item.attunement.is_some());
+        let (exported, report) = super::export_item(&item);
+        assert!(report.is_empty());
+        assert_eq!(exported["desc"], value["desc"]);
+    }
+}
@@ -0,0 +1,890 @@
+//! Replicated text and note lists that several people can edit at the same time.
+//!
+//! [`Text`] is a sequence CRDT in the style of RGA: every character keeps the id it was
+//! inserted with and the id of the character it was inserted after, and removed characters
+//! stay behind as tombstones. Replicas that have seen the same edits hold the same text,
+//! whatever order the edits arrived in. Edits can be exchanged one at a time as [`TextOp`]s or
+//! by merging whole states.
+//!
+//! [`SharedSession`] holds the notes, plan and recap of a session in this form: every summary,
+//! scene and list entry is a [`Text`] or a whole value in a [`SharedList`], so any merge of
+//! valid edits is again a valid plan and recap. It is created once from a [`Session`] and then
+//! shared; replicas that each convert the same session would hold unrelated copies of its text,
+//! which merge into duplicates.
+
+use core::{fmt, ops::Range};
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    id::{LocationId, NoteId},
+    world::campaign::session::{Award, Loot, Note, Plan, Recap, Scene, Session},
+};
+
+/// The id of an inserted character: a Lamport counter and the replica that made the insert.
+/// Ids are ordered by counter first, so later inserts compare greater.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(from = "(u64, u32)", into = "(u64, u32)")]
+pub struct Stamp {
+    pub counter: u64,
+    pub replica: u32,
+}
+
+impl From<(u64, u32)> for Stamp {
+    fn from((counter, replica): (u64, u32)) -> Self {
+        Self { counter, replica }
+    }
+}
+
+impl From<Stamp> for (u64, u32) {
+    fn from(stamp: Stamp) -> Self {
+        (stamp.counter, stamp.replica)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    id: Stamp,
+    /// The character this one was inserted after, or `None` at the start of the text.
+    origin: Option<Stamp>,
+    value: char,
+    deleted: bool,
+}
+
+/// An edit of a [`Text`], to be applied on the other replicas.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TextOp {
+    Insert {
+        id: Stamp,
+        origin: Option<Stamp>,
+        value: char,
+    },
+    Delete {
+        id: Stamp,
+    },
+}
+
+/// Text that merges concurrent edits deterministically.
+///
+/// It serializes as runs of characters typed one after another by the same replica, so
+/// ordinary typing is stored about as compactly as the plain text.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(try_from = "Vec<Run>", into = "Vec<Run>")]
+pub struct Text {
+    elements: Vec<Element>,
+    clock: u64,
+}
+
+impl Text {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text holding `text`, as if it had been typed by `replica`.
+    pub fn from_plain(replica: u32, text: &str) -> Self {
+        let mut shared = Self::new();
+        shared.insert(replica, 0, text);
+        shared
+    }
+
+    /// The number of characters, not counting removed ones.
+    pub fn len(&self) -> usize {
+        self.elements.iter().filter(|x| !x.deleted).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn find(&self, id: &Stamp) -> Option<usize> {
+        self.elements.iter().position(|x| &x.id == id)
+    }
+
+    /// Inserts `text` before the character at `index`, counted in characters and clamped to
+    /// the end, returning the edits for the other replicas.
+    pub fn insert(&mut self, replica: u32, index: usize, text: &str) -> Vec<TextOp> {
+        let index = index.min(self.len());
+        let mut origin = index
+            .checked_sub(1)
+            .and_then(|i| self.elements.iter().filter(|x| !x.deleted).nth(i))
+            .map(|x| x.id);
+        let mut operations = Vec::new();
+        for value in text.chars() {
+            let id = Stamp {
+                counter: self.clock + 1,
+                replica,
+            };
+            let element = Element {
+                id,
+                origin,
+                value,
+                deleted: false,
+            };
+            // The origin was just inserted or already present, so integrating cannot fail.
+            if self.integrate(element).is_ok() {
+                operations.push(TextOp::Insert { id, origin, value });
+            }
+            origin = Some(id);
+        }
+        operations
+    }
+
+    /// Removes the characters in `range`, counted in characters, returning the edits for the
+    /// other replicas.
+    pub fn delete(&mut self, range: Range<usize>) -> Vec<TextOp> {
+        let mut operations = Vec::new();
+        let visible = self.elements.iter_mut().filter(|x| !x.deleted);
+        for element in visible.skip(range.start).take(range.len()) {
+            element.deleted = true;
+            operations.push(TextOp::Delete { id: element.id });
+        }
+        operations
+    }
+
+    /// Applies an edit from another replica. Applying an edit twice has no further effect, but
+    /// an insert must arrive after the insert of its origin.
+    pub fn apply(&mut self, operation: &TextOp) -> Result<(), CrdtError> {
+        match operation {
+            TextOp::Insert { id, origin, value } => self.integrate(Element {
+                id: *id,
+                origin: *origin,
+                value: *value,
+                deleted: false,
+            }),
+            TextOp::Delete { id } => {
+                let i = self.find(id).ok_or(CrdtError::MissingElement(*id))?;
+                self.elements[i].deleted = true;
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds every insert and removal of `other` that this text has not seen.
+    pub fn merge(&mut self, other: &Text) -> Result<(), CrdtError> {
+        for element in &other.elements {
+            self.integrate(Element {
+                deleted: false,
+                ..element.clone()
+            })?;
+            if element.deleted {
+                if let Some(i) = self.find(&element.id) {
+                    self.elements[i].deleted = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn integrate(&mut self, element: Element) -> Result<(), CrdtError> {
+        if self.find(&element.id).is_some() {
+            return Ok(());
+        }
+        // The next local insert takes the counter after the greatest one seen.
+        if element.id.counter == u64::MAX {
+            return Err(CrdtError::CounterOverflow(element.id));
+        }
+        let mut i = match &element.origin {
+            None => 0,
+            Some(origin) => self.find(origin).ok_or(CrdtError::MissingOrigin(*origin))? + 1,
+        };
+        // Concurrent inserts after the same origin are ordered by id, newest first. Anything
+        // inserted after those has a greater counter still, so it is skipped along with them.
+        while i < self.elements.len() && self.elements[i].id > element.id {
+            i += 1;
+        }
+        self.clock = self.clock.max(element.id.counter);
+        self.elements.insert(i, element);
+        Ok(())
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text: String = self
+            .elements
+            .iter()
+            .filter(|x| !x.deleted)
+            .map(|x| x.value)
+            .collect();
+        write!(f, "{}", text)
+    }
+}
+
+/// Characters typed one after another by one replica, each with the next counter and inserted
+/// after the previous one.
+#[derive(Serialize, Deserialize)]
+struct Run {
+    id: Stamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    origin: Option<Stamp>,
+    text: String,
+    /// Ranges of removed characters, as the index in the run and the length.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    deleted: Vec<(usize, usize)>,
+}
+
+impl From<Text> for Vec<Run> {
+    fn from(text: Text) -> Self {
+        let mut runs: Vec<Run> = Vec::new();
+        let mut previous: Option<Stamp> = None;
+        let mut length = 0;
+        for element in text.elements {
+            let continues = previous.is_some_and(|x| {
+                element.origin == Some(x)
+                    && element.id.replica == x.replica
+                    && x.counter.checked_add(1) == Some(element.id.counter)
+            });
+            match runs.last_mut() {
+                Some(run) if continues => {
+                    run.text.push(element.value);
+                    length += 1;
+                }
+                _ => {
+                    runs.push(Run {
+                        id: element.id,
+                        origin: element.origin,
+                        text: element.value.to_string(),
+                        deleted: Vec::new(),
+                    });
+                    length = 1;
+                }
+            }
+            if element.deleted {
+                let run = runs.last_mut().expect("a run was just pushed or extended");
+                match run.deleted.last_mut() {
+                    Some((start, count)) if *start + *count == length - 1 => *count += 1,
+                    _ => run.deleted.push((length - 1, 1)),
+                }
+            }
+            previous = Some(element.id);
+        }
+        runs
+    }
+}
+
+impl TryFrom<Vec<Run>> for Text {
+    type Error = CrdtError;
+
+    /// Rebuilds the text from runs that may come from another replica, so every origin must
+    /// precede the characters inserted after it and every id must be new.
+    fn try_from(runs: Vec<Run>) -> Result<Self, Self::Error> {
+        let mut text = Text::new();
+        let mut seen = HashSet::new();
+        for run in runs {
+            let length = run.text.chars().count();
+            for (start, count) in &run.deleted {
+                if start.checked_add(*count).is_none_or(|end| end > length) {
+                    return Err(CrdtError::InvalidRun(run.id));
+                }
+            }
+            let mut origin = run.origin;
+            for (i, value) in run.text.chars().enumerate() {
+                let counter = (i as u64)
+                    .checked_add(run.id.counter)
+                    .filter(|x| *x < u64::MAX)
+                    .ok_or(CrdtError::CounterOverflow(run.id))?;
+                let id = Stamp {
+                    counter,
+                    replica: run.id.replica,
+                };
+                if let Some(origin) = origin.filter(|x| !seen.contains(x)) {
+                    return Err(CrdtError::MissingOrigin(origin));
+                }
+                if !seen.insert(id) {
+                    return Err(CrdtError::DuplicateElement(id));
+                }
+                let deleted = run
+                    .deleted
+                    .iter()
+                    .any(|(start, count)| (*start..start + count).contains(&i));
+                text.clock = text.clock.max(id.counter);
+                text.elements.push(Element {
+                    id,
+                    origin,
+                    value,
+                    deleted,
+                });
+                origin = Some(id);
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// A note whose name and description can be edited concurrently.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SharedNote {
+    /// When the note was added, which orders the notes of a list.
+    pub created: Stamp,
+    pub name: Text,
+    pub description: Text,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub removed: bool,
+}
+
+/// The notes of a session. Notes are added and removed as a whole; once removed on any
+/// replica, a note stays removed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct SharedNotes(BTreeMap<NoteId, SharedNote>);
+
+impl SharedNotes {
+    pub fn from_notes(replica: u32, notes: &[Note]) -> Self {
+        let mut shared = Self::default();
+        for note in notes {
+            shared.add(replica, note);
+        }
+        shared
+    }
+
+    /// Adds a note after the ones this replica has seen.
+    pub fn add(&mut self, replica: u32, note: &Note) {
+        let counter = self.0.values().map(|x| x.created.counter).max();
+        let created = Stamp {
+            counter: counter.map_or(1, |x| x.saturating_add(1)),
+            replica,
+        };
+        self.0.entry(note.id).or_insert_with(|| SharedNote {
+            created,
+            name: Text::from_plain(replica, &note.name),
+            description: Text::from_plain(replica, &note.description),
+            removed: false,
+        });
+    }
+
+    pub fn remove(&mut self, id: &NoteId) {
+        if let Some(note) = self.0.get_mut(id) {
+            note.removed = true;
+        }
+    }
+
+    pub fn get_mut(&mut self, id: &NoteId) -> Option<&mut SharedNote> {
+        self.0.get_mut(id).filter(|x| !x.removed)
+    }
+
+    /// The notes that have not been removed, in the order they were added.
+    pub fn notes(&self) -> Vec<Note> {
+        let mut notes: Vec<(&NoteId, &SharedNote)> =
+            self.0.iter().filter(|(_, x)| !x.removed).collect();
+        notes.sort_by_key(|(_, x)| x.created);
+        notes
+            .into_iter()
+            .map(|(id, x)| Note {
+                id: *id,
+                name: x.name.to_string(),
+                description: x.description.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn merge(&mut self, other: &SharedNotes) -> Result<(), CrdtError> {
+        for (id, theirs) in &other.0 {
+            match self.0.get_mut(id) {
+                Some(ours) => {
+                    ours.created = ours.created.min(theirs.created);
+                    ours.name.merge(&theirs.name)?;
+                    ours.description.merge(&theirs.description)?;
+                    ours.removed |= theirs.removed;
+                }
+                None => {
+                    self.0.insert(*id, theirs.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A value that several replicas can edit and then combine.
+pub trait Merge {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError>;
+}
+
+impl Merge for Text {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError> {
+        Text::merge(self, other)
+    }
+}
+
+/// Values that are only ever added and removed as a whole, so replicas never hold different
+/// versions of the same entry.
+macro_rules! whole {
+    ($($t:ty),*) => {
+        $(impl Merge for $t {
+            fn merge(&mut self, _: &Self) -> Result<(), CrdtError> {
+                Ok(())
+            }
+        })*
+    };
+}
+
+whole!(LocationId, Loot, Award);
+
+/// An entry of a [`SharedList`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SharedEntry<T> {
+    /// When the entry was added, which orders the entries of a list.
+    pub id: Stamp,
+    pub value: T,
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub removed: bool,
+}
+
+/// A list that entries are appended to and removed from concurrently. Entries added at the
+/// same time on different replicas are both kept, ordered by their ids; once removed on any
+/// replica, an entry stays removed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct SharedList<T>(Vec<SharedEntry<T>>);
+
+/// Entries are looked up by binary search, so a list from another replica must be sorted by id
+/// without duplicates.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<SharedEntry<T>>::deserialize(deserializer)?;
+        if let Some(x) = entries.windows(2).find(|x| x[0].id >= x[1].id) {
+            return Err(de::Error::custom(CrdtError::Unordered(x[1].id)));
+        }
+        Ok(Self(entries))
+    }
+}
+
+impl<T> Default for SharedList<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> SharedList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_values(replica: u32, values: impl IntoIterator<Item = T>) -> Self {
+        let mut shared = Self::new();
+        for value in values {
+            shared.push(replica, value);
+        }
+        shared
+    }
+
+    /// Adds `value` after the entries this replica has seen, returning the id of its entry.
+    pub fn push(&mut self, replica: u32, value: T) -> Stamp {
+        let counter = self.0.iter().map(|x| x.id.counter).max();
+        let id = Stamp {
+            counter: counter.map_or(1, |x| x.saturating_add(1)),
+            replica,
+        };
+        if let Err(i) = self.0.binary_search_by_key(&id, |x| x.id) {
+            self.0.insert(
+                i,
+                SharedEntry {
+                    id,
+                    value,
+                    removed: false,
+                },
+            );
+        }
+        id
+    }
+
+    pub fn remove(&mut self, id: &Stamp) {
+        if let Ok(i) = self.0.binary_search_by_key(id, |x| x.id) {
+            self.0[i].removed = true;
+        }
+    }
+
+    pub fn get_mut(&mut self, id: &Stamp) -> Option<&mut T> {
+        let i = self.0.binary_search_by_key(id, |x| x.id).ok()?;
+        Some(&mut self.0[i])
+            .filter(|x| !x.removed)
+            .map(|x| &mut x.value)
+    }
+
+    /// The entries that have not been removed, in order, with their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (Stamp, &T)> {
+        self.0
+            .iter()
+            .filter(|x| !x.removed)
+            .map(|x| (x.id, &x.value))
+    }
+
+    fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, x)| x)
+    }
+}
+
+impl<T: Merge + Clone> Merge for SharedList<T> {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError> {
+        for theirs in &other.0 {
+            match self.0.binary_search_by_key(&theirs.id, |x| x.id) {
+                Ok(i) => {
+                    let ours = &mut self.0[i];
+                    ours.value.merge(&theirs.value)?;
+                    ours.removed |= theirs.removed;
+                }
+                Err(i) => self.0.insert(i, theirs.clone()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedScene {
+    pub title: Text,
+    pub description: Text,
+}
+
+impl Merge for SharedScene {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError> {
+        self.title.merge(&other.title)?;
+        self.description.merge(&other.description)
+    }
+}
+
+/// The fields of a [`Plan`], each edited on its own.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedPlan {
+    pub summary: Text,
+    pub scenes: SharedList<SharedScene>,
+    pub encounters: SharedList<Text>,
+    pub npcs: SharedList<Text>,
+    pub locations: SharedList<LocationId>,
+}
+
+impl SharedPlan {
+    pub fn from_plan(replica: u32, plan: &Plan) -> Self {
+        let text = |x: &String| Text::from_plain(replica, x);
+        let scenes = plan.scenes.iter().map(|x| SharedScene {
+            title: text(&x.title),
+            description: text(&x.description),
+        });
+        Self {
+            summary: text(&plan.summary),
+            scenes: SharedList::from_values(replica, scenes),
+            encounters: SharedList::from_values(replica, plan.encounters.iter().map(text)),
+            npcs: SharedList::from_values(replica, plan.npcs.iter().map(text)),
+            locations: SharedList::from_values(replica, plan.locations.iter().copied()),
+        }
+    }
+
+    pub fn plan(&self) -> Plan {
+        Plan {
+            summary: self.summary.to_string(),
+            scenes: self
+                .scenes
+                .values()
+                .map(|x| Scene {
+                    title: x.title.to_string(),
+                    description: x.description.to_string(),
+                })
+                .collect(),
+            encounters: self.encounters.values().map(Text::to_string).collect(),
+            npcs: self.npcs.values().map(Text::to_string).collect(),
+            locations: self.locations.values().copied().collect(),
+        }
+    }
+}
+
+impl Merge for SharedPlan {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError> {
+        self.summary.merge(&other.summary)?;
+        self.scenes.merge(&other.scenes)?;
+        self.encounters.merge(&other.encounters)?;
+        self.npcs.merge(&other.npcs)?;
+        self.locations.merge(&other.locations)
+    }
+}
+
+/// The fields of a [`Recap`], each edited on its own. Loot and experience awards are added and
+/// removed as a whole.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedRecap {
+    pub summary: Text,
+    pub events: SharedList<Text>,
+    pub loot: SharedList<Loot>,
+    pub experience: SharedList<Award>,
+    pub open_threads: SharedList<Text>,
+}
+
+impl SharedRecap {
+    pub fn from_recap(replica: u32, recap: &Recap) -> Self {
+        let text = |x: &String| Text::from_plain(replica, x);
+        Self {
+            summary: text(&recap.summary),
+            events: SharedList::from_values(replica, recap.events.iter().map(text)),
+            loot: SharedList::from_values(replica, recap.loot.iter().cloned()),
+            experience: SharedList::from_values(replica, recap.experience.iter().cloned()),
+            open_threads: SharedList::from_values(replica, recap.open_threads.iter().map(text)),
+        }
+    }
+
+    pub fn recap(&self) -> Recap {
+        Recap {
+            summary: self.summary.to_string(),
+            events: self.events.values().map(Text::to_string).collect(),
+            loot: self.loot.values().cloned().collect(),
+            experience: self.experience.values().cloned().collect(),
+            open_threads: self.open_threads.values().map(Text::to_string).collect(),
+        }
+    }
+}
+
+impl Merge for SharedRecap {
+    fn merge(&mut self, other: &Self) -> Result<(), CrdtError> {
+        self.summary.merge(&other.summary)?;
+        self.events.merge(&other.events)?;
+        self.loot.merge(&other.loot)?;
+        self.experience.merge(&other.experience)?;
+        self.open_threads.merge(&other.open_threads)
+    }
+}
+
+/// The collaboratively edited parts of a session: its notes, plan and recap.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SharedSession {
+    pub notes: SharedNotes,
+    pub plan: SharedPlan,
+    pub recap: SharedRecap,
+}
+
+impl SharedSession {
+    pub fn from_session(replica: u32, session: &Session) -> Self {
+        Self {
+            notes: SharedNotes::from_notes(replica, &session.notes),
+            plan: SharedPlan::from_plan(replica, &session.plan),
+            recap: SharedRecap::from_recap(replica, &session.recap),
+        }
+    }
+
+    /// Writes the current notes, plan and recap into `session`.
+    pub fn update(&self, session: &mut Session) {
+        session.notes = self.notes.notes();
+        session.plan = self.plan.plan();
+        session.recap = self.recap.recap();
+    }
+
+    pub fn merge(&mut self, other: &SharedSession) -> Result<(), CrdtError> {
+        self.notes.merge(&other.notes)?;
+        self.plan.merge(&other.plan)?;
+        self.recap.merge(&other.recap)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CrdtError {
+    /// An insert arrived before the insert of the character it follows.
+    MissingOrigin(Stamp),
+    /// A removal arrived before the insert of its character.
+    MissingElement(Stamp),
+    /// A character's counter would leave no counter for the next insert.
+    CounterOverflow(Stamp),
+    /// A run of characters marks characters past its end as removed.
+    InvalidRun(Stamp),
+    /// Two characters have the same id.
+    DuplicateElement(Stamp),
+    /// A list entry does not come after the one before it.
+    Unordered(Stamp),
+}
+
+impl fmt::Display for CrdtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            CrdtError::MissingOrigin(x) => {
+                write!(
+                    f,
+                    "insert after unknown character {}@{}",
+                    x.counter, x.replica
+                )
+            }
+            CrdtError::MissingElement(x) => {
+                write!(
+                    f,
+                    "removal of unknown character {}@{}",
+                    x.counter, x.replica
+                )
+            }
+            CrdtError::InvalidRun(x) => {
+                write!(f, "removal past the end of run {}@{}", x.counter, x.replica)
+            }
+            CrdtError::DuplicateElement(x) => {
+                write!(f, "duplicate character {}@{}", x.counter, x.replica)
+            }
+            CrdtError::Unordered(x) => {
+                write!(f, "list entry {}@{} is out of order", x.counter, x.replica)
+            }
+            CrdtError::CounterOverflow(x) => {
+                write!(
+                    f,
+                    "character counter overflows at {}@{}",
+                    x.counter, x.replica
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CrdtError {}
+
+mod test {
+    #[test]
+    fn test_concurrent_text_edits_converge() {
+        use super::{CrdtError, Text, TextOp};
+
+        let base = Text::from_plain(1, "Hello world");
+        let mut alice = base.clone();
+        let mut bob = base.clone();
+        let alice_ops = alice.insert(2, 5, ", dear");
+        let mut bob_ops = bob.insert(3, 5, " there");
+        bob_ops.extend(bob.delete(11..17));
+        assert_eq!(alice.to_string(), "Hello, dear world");
+        assert_eq!(bob.to_string(), "Hello there");
+
+        // The same edits give the same text, whether merged as states or applied as edits.
+        let mut merged = alice.clone();
+        merged.merge(&bob).unwrap();
+        let mut other = bob.clone();
+        other.merge(&alice).unwrap();
+        assert_eq!(merged.to_string(), other.to_string());
+        let mut replayed = base.clone();
+        for op in bob_ops.iter().chain(&alice_ops) {
+            replayed.apply(op).unwrap();
+            replayed.apply(op).unwrap();
+        }
+        assert_eq!(replayed.to_string(), merged.to_string());
+        assert_eq!(merged.to_string(), "Hello there, dear");
+
+        let orphan = TextOp::Delete {
+            id: super::Stamp {
+                counter: 99,
+                replica: 9,
+            },
+        };
+        assert!(matches!(
+            base.clone().apply(&orphan),
+            Err(CrdtError::MissingElement(_))
+        ));
+
+        // Typing is stored as runs, and removed characters as ranges within them.
+        let json = serde_json::to_value(&merged).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 4);
+        assert_eq!(json[0], serde_json::json!({"id": [1, 1], "text": "Hello"}));
+        assert_eq!(
+            json[3],
+            serde_json::json!({"id": [6, 1], "origin": [5, 1], "text": " world", "deleted": [[0, 6]]})
+        );
+        let loaded: Text = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded, merged);
+
+        // Runs come from other replicas, so malformed ones are rejected.
+        for json in [
+            serde_json::json!([{"id": [1, 1], "text": "ab", "deleted": [[usize::MAX, 2]]}]),
+            serde_json::json!([{"id": [1, 1], "text": "ab", "deleted": [[1, 2]]}]),
+            serde_json::json!([{"id": [1, 1], "origin": [7, 7], "text": "a"}]),
+            serde_json::json!([{"id": [1, 1], "text": "a"}, {"id": [1, 1], "text": "b"}]),
+        ] {
+            assert!(serde_json::from_value::<Text>(json).is_err());
+        }
+
+        // Counters come from other replicas, so the last one is rejected rather than overflowed.
+        let json = serde_json::json!([{"id": [u64::MAX - 1, 1], "text": "ab"}]);
+        assert!(serde_json::from_value::<Text>(json).is_err());
+        let overflow = TextOp::Insert {
+            id: super::Stamp {
+                counter: u64::MAX,
+                replica: 9,
+            },
+            origin: None,
+            value: 'a',
+        };
+        assert!(matches!(
+            base.clone().apply(&overflow),
+            Err(CrdtError::CounterOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_shared_session() {
+        use super::{SharedSession, Text};
+        use crate::{
+            id::{CampaignId, CreatureId},
+            world::campaign::session::{Award, Note, Plan, Session},
+        };
+
+        let mut session = Session::new(String::from("Session 1"), String::new(), CampaignId::new());
+        session.notes = vec![Note::new(String::from("Loot"), String::from("A key"))];
+        session.plan = Plan::new(String::from("Ravine, then the citadel."));
+        let loot = session.notes[0].id;
+
+        let shared = SharedSession::from_session(1, &session);
+        let stored = serde_json::to_string(&shared).unwrap();
+        let mut alice: SharedSession = serde_json::from_str(&stored).unwrap();
+        let mut bob = shared.clone();
+
+        let note = alice.notes.get_mut(&loot).unwrap();
+        let end = note.description.len();
+        note.description.insert(2, end, " (silver)");
+        alice.recap.summary.insert(2, 0, "The party met Meepo.");
+        let meepo = alice
+            .recap
+            .events
+            .push(2, Text::from_plain(2, "Meepo joined"));
+        let map = Note::new(String::from("Map"), String::new());
+        bob.notes.add(3, &map);
+        bob.notes
+            .get_mut(&loot)
+            .unwrap()
+            .name
+            .insert(3, 0, "Citadel ");
+        bob.plan.summary.delete(0..8);
+        bob.recap
+            .events
+            .push(3, Text::from_plain(3, "The party rested"));
+        let award = Award {
+            player_id: CreatureId::new(),
+            experience: 50,
+        };
+        bob.recap.experience.push(3, award.clone());
+
+        alice.merge(&bob).unwrap();
+        bob.merge(&alice).unwrap();
+        assert_eq!(alice, bob);
+
+        alice.update(&mut session);
+        let names: Vec<&str> = session.notes.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, vec!["Citadel Loot", "Map"]);
+        assert_eq!(session.notes[0].description, "A key (silver)");
+        assert_eq!(session.plan.summary, "then the citadel.");
+        assert_eq!(session.recap.summary, "The party met Meepo.");
+        assert_eq!(session.recap.experience, vec![award]);
+
+        // Edits that meet inside a list entry still give a valid recap.
+        alice
+            .recap
+            .events
+            .get_mut(&meepo)
+            .unwrap()
+            .insert(2, 0, "## ");
+        bob.recap.events.remove(&meepo);
+        alice.merge(&bob).unwrap();
+        bob.merge(&alice).unwrap();
+        assert_eq!(alice, bob);
+        alice.update(&mut session);
+        assert_eq!(session.recap.events, vec!["The party rested"]);
+
+        // Lists are searched by id, so ones that are out of order are rejected.
+        for json in [
+            serde_json::json!([{"id": [2, 1], "value": 1}, {"id": [1, 1], "value": 2}]),
+            serde_json::json!([{"id": [1, 1], "value": 1}, {"id": [1, 1], "value": 2}]),
+        ] {
+            assert!(serde_json::from_value::<super::SharedList<u32>>(json).is_err());
+        }
+
+        bob.notes.remove(&loot);
+        alice.merge(&bob).unwrap();
+        alice.update(&mut session);
+        assert_eq!(session.notes.len(), 1);
+    }
+}
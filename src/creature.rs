@@ -1,16 +1,26 @@
-use core::fmt;
+use core::{fmt, str::FromStr};
 
-use serde::{Deserialize, Serialize};
-use strum::EnumIter;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 
-use crate::{action::Action, Alignment, ConditionType, DamageType, Die, DieStat, OtherAttribute};
+use crate::{
+    action::Action, error::ValidationError, id::CreatureId, Alignment, ConditionType, DamageType,
+    Die, DieStat, OtherAttribute, ParseError,
+};
+
+pub mod parse;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Creature {
-    id: String,
+    id: CreatureId,
     name: String,
+    #[serde(default)]
+    size: Size,
     creature_type: CreatureType,
     alignment: Alignment,
+    #[serde(deserialize_with = "armor_class")]
     armor_class: i32,
     health_points: Health,
     speed: MovementSpeed,
@@ -31,10 +41,29 @@ pub struct Creature {
     others: Option<Vec<OtherAttribute>>,
 }
 
+fn check_armor_class(armor_class: i32) -> Result<i32, ValidationError> {
+    if !Creature::ARMOR_CLASS.contains(&armor_class) {
+        return Err(ValidationError::new(
+            "armor_class",
+            format!("{} is negative", armor_class),
+        ));
+    }
+    Ok(armor_class)
+}
+
+fn armor_class<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    check_armor_class(i32::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
 impl Creature {
+    /// The armor classes a creature can have.
+    pub const ARMOR_CLASS: core::ops::RangeFrom<i32> = 0..;
+
+    /// Creates a creature without checking the armor class, which
+    /// [`set_armor_class`](Self::set_armor_class) and deserialization reject when negative.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        mut id: String,
+        id: CreatureId,
         name: String,
         creature_type: CreatureType,
         alignment: Alignment,
@@ -57,12 +86,10 @@ impl Creature {
         lair: Option<Lair>,
         others: Option<Vec<OtherAttribute>>,
     ) -> Self {
-        if id.is_empty() {
-            id = uuid::Uuid::new_v4().to_string();
-        }
         Self {
             id,
             name,
+            size: Size::default(),
             creature_type,
             alignment,
             armor_class,
@@ -86,7 +113,7 @@ impl Creature {
         }
     }
 
-    pub fn id(&self) -> &String {
+    pub fn id(&self) -> &CreatureId {
         &self.id
     }
 
@@ -94,6 +121,10 @@ impl Creature {
         &self.name
     }
 
+    pub fn size(&self) -> &Size {
+        &self.size
+    }
+
     pub fn creature_type(&self) -> &CreatureType {
         &self.creature_type
     }
@@ -178,6 +209,10 @@ impl Creature {
         self.name = name;
     }
 
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+
     pub fn set_creature_type(&mut self, creature_type: CreatureType) {
         self.creature_type = creature_type;
     }
@@ -186,8 +221,10 @@ impl Creature {
         self.alignment = alignment;
     }
 
-    pub fn set_armor_class(&mut self, armor_class: i32) {
-        self.armor_class = armor_class;
+    /// Sets the armor class, which cannot be negative.
+    pub fn set_armor_class(&mut self, armor_class: i32) -> Result<(), ValidationError> {
+        self.armor_class = check_armor_class(armor_class)?;
+        Ok(())
     }
 
     pub fn set_health_points(&mut self, health_points: Health) {
@@ -257,9 +294,88 @@ impl Creature {
     pub fn set_others(&mut self, others: Option<Vec<OtherAttribute>>) {
         self.others = others;
     }
+
+    /// The challenge rating as a number, accepting fractions like `1/4`.
+    pub fn challenge_rating_value(&self) -> Option<f64> {
+        let text = self.challenge_rating.split_whitespace().next()?;
+        match text.split_once('/') {
+            Some((numerator, denominator)) => {
+                Some(numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?)
+            }
+            None => text.parse().ok(),
+        }
+    }
+
+    pub fn experience_points(&self) -> Option<u32> {
+        let rating = self.challenge_rating_value()?;
+        let xp = match rating {
+            x if x < 0.125 => 10,
+            x if x < 0.25 => 25,
+            x if x < 0.5 => 50,
+            x if x < 1.0 => 100,
+            x => match x as u32 {
+                1 => 200,
+                2 => 450,
+                3 => 700,
+                4 => 1100,
+                5 => 1800,
+                6 => 2300,
+                7 => 2900,
+                8 => 3900,
+                9 => 5000,
+                10 => 5900,
+                11 => 7200,
+                12 => 8400,
+                13 => 10000,
+                14 => 11500,
+                15 => 13000,
+                16 => 15000,
+                17 => 18000,
+                18 => 20000,
+                19 => 22000,
+                20 => 25000,
+                21 => 33000,
+                22 => 41000,
+                23 => 50000,
+                24 => 62000,
+                25 => 75000,
+                26 => 90000,
+                27 => 105000,
+                28 => 120000,
+                29 => 135000,
+                _ => 155000,
+            },
+        };
+        Some(xp)
+    }
+
+    /// Proficiency bonus derived from the challenge rating.
+    pub fn proficiency_bonus(&self) -> Option<i32> {
+        let rating = self.challenge_rating_value()?;
+        Some(2 + (rating.max(1.0) as i32 - 1) / 4)
+    }
+
+    pub fn stat(&self, stat_type: &StatType) -> Option<&Stat> {
+        self.stats.iter().find(|x| &x.stat_type == stat_type)
+    }
+
+    /// 10 plus the Perception skill modifier, or the Wisdom modifier without the skill.
+    pub fn passive_perception(&self) -> i32 {
+        let perception = self
+            .skills
+            .iter()
+            .flatten()
+            .find(|x| x.skill_type == SkillType::Perception)
+            .map(|x| x.modifier);
+        10 + perception
+            .or_else(|| self.stat(&StatType::Wisdom).map(|x| x.modifier))
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Serialize, Deserialize, EnumIter, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum MovementSpeed {
     Walk(u8),
     Swim(u8),
@@ -293,7 +409,39 @@ impl fmt::Display for MovementSpeed {
     }
 }
 
+/// Parses a single speed entry such as `30 ft.`, `swim 40 ft.` or `fly 60 ft. (hover)`.
+impl FromStr for MovementSpeed {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_lowercase();
+        let hover = text.contains("(hover)");
+        let text = text.replace("(hover)", "");
+        let (kind, rest) = match text.trim().split_once(' ') {
+            Some((kind, rest)) if kind.chars().all(char::is_alphabetic) => (kind, rest),
+            _ => ("walk", text.trim()),
+        };
+        let speed = rest
+            .trim()
+            .trim_end_matches('.')
+            .trim_end_matches("ft")
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| ParseError::new(s, "invalid speed"))?;
+        match kind {
+            "walk" => Ok(MovementSpeed::Walk(speed)),
+            "swim" => Ok(MovementSpeed::Swim(speed)),
+            "fly" => Ok(MovementSpeed::Fly { speed, hover }),
+            "burrow" => Ok(MovementSpeed::Burrow(speed)),
+            "climb" => Ok(MovementSpeed::Climb(speed)),
+            _ => Err(ParseError::new(s, "unknown movement type")),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Stat {
     pub stat_type: StatType,
     pub value: i32,
@@ -301,16 +449,21 @@ pub struct Stat {
 }
 
 impl Stat {
+    /// The ability scores a creature can have.
+    pub const SCORES: core::ops::RangeInclusive<i32> = 1..=30;
+
     pub fn from_value(stat_type: StatType, value: i32) -> Self {
         Self {
             stat_type,
             value,
-            modifier: ((value - 10) as f64 / 2_f64).floor() as i32,
+            modifier: ((value as f64 - 10_f64) / 2_f64).floor() as i32,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum StatType {
     Strength,
     Dexterity,
@@ -320,7 +473,40 @@ pub enum StatType {
     Charisma,
 }
 
+impl StatType {
+    pub fn abbreviation(&self) -> &'static str {
+        match &self {
+            StatType::Strength => "STR",
+            StatType::Dexterity => "DEX",
+            StatType::Constitution => "CON",
+            StatType::Intelligence => "INT",
+            StatType::Wisdom => "WIS",
+            StatType::Charisma => "CHA",
+        }
+    }
+}
+
+impl fmt::Display for StatType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Accepts the full name or the three letter abbreviation, in any case.
+impl FromStr for StatType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_uppercase();
+        StatType::iter()
+            .find(|x| x.abbreviation() == text || x.to_string().to_uppercase() == text)
+            .ok_or_else(|| ParseError::new(s, "unknown ability"))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Health {
     pub health: DieStat,
 }
@@ -344,6 +530,8 @@ impl fmt::Display for Health {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Skill {
     pub skill_type: SkillType,
     pub modifier: i32,
@@ -355,7 +543,9 @@ impl fmt::Display for Skill {
     }
 }
 
-#[derive(Serialize, Deserialize, EnumIter, Debug, Clone)]
+#[derive(Serialize, Deserialize, EnumIter, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum SkillType {
     Acrobatics,
     AnimalHandling,
@@ -377,6 +567,25 @@ pub enum SkillType {
     Survival,
 }
 
+/// Accepts names like `Sleight of Hand` or `AnimalHandling`, with or without the ability suffix.
+impl FromStr for SkillType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text: String = s
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .collect::<String>()
+            .to_lowercase();
+        SkillType::iter()
+            .find(|x| format!("{:?}", x).to_lowercase() == text)
+            .ok_or_else(|| ParseError::new(s, "unknown skill"))
+    }
+}
+
 impl fmt::Display for SkillType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self {
@@ -403,6 +612,8 @@ impl fmt::Display for SkillType {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Sense {
     Blindsight(u32),
     Darkvision(u32),
@@ -421,7 +632,34 @@ impl fmt::Display for Sense {
     }
 }
 
+/// Parses a sense such as `darkvision 60 ft.`.
+impl FromStr for Sense {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_lowercase();
+        let (kind, range) = text
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new(s, "missing sense range"))?;
+        let range = range
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .parse::<u32>()
+            .map_err(|_| ParseError::new(s, "invalid sense range"))?;
+        match kind {
+            "blindsight" => Ok(Sense::Blindsight(range)),
+            "darkvision" => Ok(Sense::Darkvision(range)),
+            "tremorsense" => Ok(Sense::Tremorsense(range)),
+            "truesight" => Ok(Sense::Truesight(range)),
+            _ => Err(ParseError::new(s, "unknown sense")),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Language {
     Abanasinia,
     Abyssal,
@@ -479,7 +717,40 @@ impl fmt::Display for Language {
     }
 }
 
+impl FromStr for Language {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_lowercase();
+        Language::iter()
+            .find(|x| x.to_string().to_lowercase() == text)
+            .ok_or_else(|| ParseError::new(s, "unknown language"))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, EnumIter, EnumString, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[strum(ascii_case_insensitive)]
+pub enum Size {
+    Tiny,
+    Small,
+    #[default]
+    Medium,
+    Large,
+    Huge,
+    Gargantuan,
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum CreatureType {
     Monster(MonsterType),
     Player,
@@ -493,12 +764,16 @@ impl Default for CreatureType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct RacialTrait {
     pub name: String,
     pub description: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Lair {
     pub name: String,
     pub description: String,
@@ -507,12 +782,17 @@ pub struct Lair {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Paragraph {
     pub paragraph: String,
     pub bullet: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq, Default)]
+#[derive(Serialize, Deserialize, Debug, EnumIter, EnumString, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[strum(ascii_case_insensitive)]
 pub enum MonsterType {
     #[default]
     Aberration,
@@ -0,0 +1,781 @@
+use crate::{
+    action::{attack::Attack, Action, ActionType, SpecialAction},
+    id::CreatureId,
+    Alignment, ConditionType, DamageType, Die, DieStat, OtherAttribute,
+};
+
+use super::{
+    Creature, CreatureType, Health, Lair, Language, MonsterType, MovementSpeed, Paragraph,
+    RacialTrait, Sense, Size, Skill, SkillType, Stat, StatType,
+};
+
+/// Something in a stat block that was missing or could not be understood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Warning {
+    fn at(line: usize, message: String) -> Self {
+        Self {
+            line: Some(line),
+            message,
+        }
+    }
+
+    fn missing(field: &str) -> Self {
+        Self {
+            line: None,
+            message: format!("missing {}", field),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Section {
+    Header,
+    Traits,
+    Actions,
+    BonusActions,
+    Reactions,
+    LegendaryActions,
+    LairActions,
+    RegionalEffects,
+}
+
+struct Block {
+    line: usize,
+    text: String,
+    bullet: bool,
+    named: bool,
+}
+
+/// Parses an SRD-style monster stat block into a [`Creature`].
+///
+/// Parsing never fails: fields that are missing or not understood are left at their defaults and
+/// reported as warnings alongside the partially filled creature.
+pub fn parse_stat_block(text: &str) -> (Creature, Vec<Warning>) {
+    let mut creature = Creature {
+        id: CreatureId::new(),
+        ..Default::default()
+    };
+    let mut warnings = Vec::new();
+    let mut others = Vec::new();
+
+    let mut section = Section::Header;
+    let mut found = Found::default();
+    let mut pending_scores: Option<Vec<i32>> = None;
+    let mut blocks: Vec<(Section, Block)> = Vec::new();
+
+    for (i, raw) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let bullet = is_bullet(raw);
+        let line = clean(raw);
+        if line.is_empty() {
+            continue;
+        }
+        let lower = line.to_lowercase();
+
+        if let Some(scores) = pending_scores.as_mut() {
+            scores.extend(numbers(&line));
+            if scores.len() >= 6 {
+                creature.stats = to_stats(scores, line_no, &mut warnings);
+                found.stats = true;
+                pending_scores = None;
+            }
+            continue;
+        }
+
+        if let Some(next) = heading(&lower) {
+            section = next;
+            continue;
+        }
+
+        if !found.name {
+            creature.name = line;
+            found.name = true;
+            continue;
+        }
+
+        if section == Section::Header {
+            if is_ability_header(&line) {
+                let scores = numbers(&line);
+                if scores.len() >= 6 {
+                    creature.stats = to_stats(&scores, line_no, &mut warnings);
+                    found.stats = true;
+                } else {
+                    pending_scores = Some(scores);
+                }
+                continue;
+            }
+            if let Some((field, value)) = field(&line) {
+                match field {
+                    "armor class" => match numbers(value).first() {
+                        Some(x) => {
+                            creature.armor_class = *x;
+                            found.armor_class = true;
+                        }
+                        None => warnings.push(Warning::at(
+                            line_no,
+                            format!("invalid armor class \"{}\"", value),
+                        )),
+                    },
+                    "hit points" => match parse_health(value) {
+                        Some(x) => {
+                            creature.health_points = x;
+                            found.health_points = true;
+                        }
+                        None => warnings.push(Warning::at(
+                            line_no,
+                            format!("invalid hit points \"{}\"", value),
+                        )),
+                    },
+                    "speed" => {
+                        for (n, speed) in split_list(value).into_iter().enumerate() {
+                            match speed.parse::<MovementSpeed>() {
+                                Ok(x) if n == 0 => {
+                                    creature.speed = x;
+                                    found.speed = true;
+                                }
+                                Ok(_) => others.push(OtherAttribute {
+                                    title: String::from("Speed"),
+                                    description: String::new(),
+                                    value: speed.to_string(),
+                                }),
+                                Err(e) => warnings.push(Warning::at(line_no, e.to_string())),
+                            }
+                        }
+                    }
+                    "saving throws" => {
+                        let saves =
+                            parse_saving_throws(value, &creature.stats, line_no, &mut warnings);
+                        creature.saving_throws = Some(saves);
+                    }
+                    "skills" => {
+                        let skills = parse_skills(value, line_no, &mut warnings);
+                        creature.skills = Some(skills);
+                    }
+                    "damage resistances" => {
+                        creature.damage_resistances =
+                            Some(parse_damage_types(value, line_no, &mut warnings));
+                    }
+                    "damage immunities" => {
+                        creature.damage_immunities =
+                            Some(parse_damage_types(value, line_no, &mut warnings));
+                    }
+                    "damage vulnerabilities" => {
+                        creature.damage_vulnerabilities =
+                            Some(parse_damage_types(value, line_no, &mut warnings));
+                    }
+                    "condition immunities" => {
+                        let mut conditions = Vec::new();
+                        for x in split_list(value) {
+                            match x.parse::<ConditionType>() {
+                                Ok(x) => conditions.push(x),
+                                Err(_) => warnings.push(Warning::at(
+                                    line_no,
+                                    format!("unknown condition \"{}\"", x),
+                                )),
+                            }
+                        }
+                        creature.condition_immunities = Some(conditions);
+                    }
+                    "senses" => {
+                        let mut senses = Vec::new();
+                        for x in split_list(value) {
+                            if x.to_lowercase().starts_with("passive") {
+                                continue;
+                            }
+                            match x.parse::<Sense>() {
+                                Ok(x) => senses.push(x),
+                                Err(e) => warnings.push(Warning::at(line_no, e.to_string())),
+                            }
+                        }
+                        creature.senses = Some(senses);
+                    }
+                    "languages" => {
+                        let mut languages = Vec::new();
+                        for x in split_list(value) {
+                            if x.chars().all(|c| matches!(c, '-' | '—' | '–')) {
+                                continue;
+                            }
+                            match x.parse::<Language>() {
+                                Ok(x) => languages.push(x),
+                                Err(e) => warnings.push(Warning::at(line_no, e.to_string())),
+                            }
+                        }
+                        creature.languages = Some(languages);
+                    }
+                    "challenge" => {
+                        creature.challenge_rating = value
+                            .split_whitespace()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string();
+                        found.challenge_rating = true;
+                        section = Section::Traits;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            if !found.size {
+                if let Some((size, creature_type, alignment)) =
+                    parse_type_line(&line, line_no, &mut warnings)
+                {
+                    creature.size = size;
+                    creature.creature_type = creature_type;
+                    if let Some(alignment) = alignment {
+                        creature.alignment = alignment;
+                    }
+                    found.size = true;
+                    continue;
+                }
+            }
+            if found.stats {
+                section = Section::Traits;
+            } else {
+                warnings.push(Warning::at(
+                    line_no,
+                    format!("unrecognized line \"{}\"", line),
+                ));
+                continue;
+            }
+        }
+
+        blocks.push((
+            section,
+            Block {
+                line: line_no,
+                named: split_name(&line).is_some(),
+                text: line,
+                bullet,
+            },
+        ));
+    }
+
+    if pending_scores.is_some() {
+        warnings.push(Warning::missing("ability scores"));
+    }
+    for (missing, field) in [
+        (!found.size, "size, type and alignment"),
+        (!found.armor_class, "armor class"),
+        (!found.health_points, "hit points"),
+        (!found.speed, "speed"),
+        (!found.stats && pending_scores.is_none(), "ability scores"),
+        (!found.challenge_rating, "challenge rating"),
+    ] {
+        if missing {
+            warnings.push(Warning::missing(field));
+        }
+    }
+
+    let mut traits = Vec::new();
+    let mut actions = Vec::new();
+    let mut lair: Option<Lair> = None;
+    for (section, paragraph) in paragraphs(blocks) {
+        let named = split_name(&paragraph.text);
+        match section {
+            Section::Header | Section::Traits => match named {
+                Some((name, description)) => traits.push(RacialTrait { name, description }),
+                None => match creature.description.as_mut() {
+                    Some(x) => {
+                        x.push('\n');
+                        x.push_str(&paragraph.text);
+                    }
+                    None => creature.description = Some(paragraph.text),
+                },
+            },
+            Section::Actions | Section::BonusActions => match paragraph.text.parse::<Attack>() {
+                Ok(attack) => actions.push(Action::new(ActionType::Attack(attack))),
+                Err(_) => match named {
+                    Some((name, description)) => {
+                        actions.push(Action::new(ActionType::Special(SpecialAction {
+                            name,
+                            description,
+                        })))
+                    }
+                    None => warnings.push(Warning::at(
+                        paragraph.line,
+                        format!("unrecognized action \"{}\"", paragraph.text),
+                    )),
+                },
+            },
+            Section::Reactions => match named {
+                Some((name, description)) => {
+                    actions.push(Action::new(ActionType::Reaction(SpecialAction {
+                        name,
+                        description,
+                    })))
+                }
+                None => warnings.push(Warning::at(
+                    paragraph.line,
+                    format!("unrecognized reaction \"{}\"", paragraph.text),
+                )),
+            },
+            Section::LegendaryActions => match named {
+                Some((name, description)) => {
+                    actions.push(Action::new(ActionType::LegendaryAction(SpecialAction {
+                        name,
+                        description,
+                    })))
+                }
+                None => others.push(OtherAttribute {
+                    title: String::from("Legendary Actions"),
+                    description: paragraph.text,
+                    value: String::new(),
+                }),
+            },
+            Section::LairActions | Section::RegionalEffects => {
+                let lair = lair.get_or_insert_with(|| Lair {
+                    name: creature.name.clone(),
+                    description: String::new(),
+                    lair_actions: Vec::new(),
+                    regional_effects: Vec::new(),
+                });
+                let paragraph = Paragraph {
+                    paragraph: paragraph.text,
+                    bullet: paragraph.bullet,
+                };
+                if section == Section::LairActions {
+                    if lair.description.is_empty() && !paragraph.bullet {
+                        lair.description = paragraph.paragraph;
+                    } else {
+                        lair.lair_actions.push(paragraph);
+                    }
+                } else {
+                    lair.regional_effects.push(paragraph);
+                }
+            }
+        }
+    }
+
+    if !traits.is_empty() {
+        creature.racial_traits = Some(traits);
+    }
+    if !actions.is_empty() {
+        creature.actions = Some(actions);
+    }
+    creature.lair = lair;
+    if !others.is_empty() {
+        creature.others = Some(others);
+    }
+    (creature, warnings)
+}
+
+#[derive(Default)]
+struct Found {
+    name: bool,
+    size: bool,
+    armor_class: bool,
+    health_points: bool,
+    speed: bool,
+    stats: bool,
+    challenge_rating: bool,
+}
+
+fn is_bullet(raw: &str) -> bool {
+    let line = raw.trim_start().trim_start_matches('>').trim_start();
+    line.starts_with("- ") || line.starts_with("• ") || line.starts_with("* ")
+}
+
+/// Strips Markdown decoration (block quotes, headings, bullets, emphasis and rules).
+fn clean(raw: &str) -> String {
+    let mut line = raw.trim();
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+    let line = line.trim_start_matches('#').trim();
+    let line = ["- ", "• ", "* "]
+        .iter()
+        .find_map(|x| line.strip_prefix(x))
+        .unwrap_or(line);
+    if line
+        .chars()
+        .all(|c| matches!(c, '|' | ':' | '-' | '_' | '*' | ' '))
+    {
+        return String::new();
+    }
+    line.replace(['*', '_'], "").trim().to_string()
+}
+
+fn heading(lower: &str) -> Option<Section> {
+    match lower.trim_end_matches(':') {
+        "traits" | "special traits" => Some(Section::Traits),
+        "actions" => Some(Section::Actions),
+        "bonus actions" => Some(Section::BonusActions),
+        "reactions" => Some(Section::Reactions),
+        "legendary actions" => Some(Section::LegendaryActions),
+        "lair actions" => Some(Section::LairActions),
+        "regional effects" => Some(Section::RegionalEffects),
+        _ => None,
+    }
+}
+
+const FIELDS: [&str; 14] = [
+    "armor class",
+    "hit points",
+    "speed",
+    "saving throws",
+    "skills",
+    "damage resistances",
+    "damage immunities",
+    "damage vulnerabilities",
+    "condition immunities",
+    "senses",
+    "languages",
+    "challenge",
+    "proficiency bonus",
+    "saves",
+];
+
+fn field(line: &str) -> Option<(&'static str, &str)> {
+    FIELDS.iter().find_map(|field| {
+        if line
+            .get(..field.len())
+            .is_some_and(|x| x.eq_ignore_ascii_case(field))
+        {
+            let value = line[field.len()..].trim_start_matches(':').trim();
+            Some((
+                if *field == "saves" {
+                    "saving throws"
+                } else {
+                    *field
+                },
+                value,
+            ))
+        } else {
+            None
+        }
+    })
+}
+
+fn is_ability_header(line: &str) -> bool {
+    let upper = line.to_uppercase();
+    ["STR", "DEX", "CON", "INT", "WIS", "CHA"]
+        .iter()
+        .all(|x| upper.contains(x))
+}
+
+fn numbers(text: &str) -> Vec<i32> {
+    text.replace('|', " ")
+        .split_whitespace()
+        .filter_map(|x| x.parse().ok())
+        .collect()
+}
+
+/// Turns six ability scores into stats, clamping scores outside 1 to 30.
+fn to_stats(scores: &[i32], line_no: usize, warnings: &mut Vec<Warning>) -> Vec<Stat> {
+    use strum::IntoEnumIterator;
+
+    StatType::iter()
+        .zip(scores)
+        .map(|(stat_type, value)| {
+            if !Stat::SCORES.contains(value) {
+                warnings.push(Warning::at(
+                    line_no,
+                    format!("ability score {} is not between 1 and 30", value),
+                ));
+            }
+            let value = (*value).clamp(*Stat::SCORES.start(), *Stat::SCORES.end());
+            Stat::from_value(stat_type, value)
+        })
+        .collect()
+}
+
+/// Splits comma or semicolon separated stat block values.
+pub(crate) fn split_list(text: &str) -> Vec<&str> {
+    text.split([',', ';'])
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// Parses hit points such as `7 (2d6)` or a flat `12`.
+pub(crate) fn parse_health(text: &str) -> Option<Health> {
+    if let Some((_, rest)) = text.split_once('(') {
+        if let Some(Ok(health)) = rest.split_once(')').map(|x| x.0.parse::<DieStat>()) {
+            return Some(Health { health });
+        }
+    }
+    numbers(text).first().map(|x| Health {
+        health: DieStat {
+            die_count: 0,
+            die_type: Die::default(),
+            extra: *x,
+        },
+    })
+}
+
+fn parse_type_line(
+    line: &str,
+    line_no: usize,
+    warnings: &mut Vec<Warning>,
+) -> Option<(Size, CreatureType, Option<Alignment>)> {
+    let (kind, alignment) = match line.rsplit_once(',') {
+        Some((kind, alignment)) => (kind, Some(alignment)),
+        None => (line, None),
+    };
+    let mut words = kind.split_whitespace();
+    let size = words.next()?.parse::<Size>().ok()?;
+    let kind: String = words.collect::<Vec<_>>().join(" ");
+    let kind = kind
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let word = kind.split_whitespace().last().unwrap_or_default();
+    let monster_type = word
+        .parse::<MonsterType>()
+        .or_else(|_| word.trim_end_matches('s').parse::<MonsterType>());
+    let creature_type = match monster_type {
+        Ok(x) => CreatureType::Monster(x),
+        Err(_) => {
+            warnings.push(Warning::at(
+                line_no,
+                format!("unknown creature type \"{}\"", kind),
+            ));
+            CreatureType::default()
+        }
+    };
+    let alignment = alignment.and_then(|x| match x.parse::<Alignment>() {
+        Ok(x) => Some(x),
+        Err(e) => {
+            warnings.push(Warning::at(line_no, e.to_string()));
+            None
+        }
+    });
+    Some((size, creature_type, alignment))
+}
+
+fn parse_saving_throws(
+    text: &str,
+    stats: &[Stat],
+    line_no: usize,
+    warnings: &mut Vec<Warning>,
+) -> Vec<Stat> {
+    let mut saves = Vec::new();
+    for x in split_list(text) {
+        let parsed = x.rsplit_once(' ').and_then(|(stat, modifier)| {
+            Some((
+                stat.parse::<StatType>().ok()?,
+                modifier.parse::<i32>().ok()?,
+            ))
+        });
+        match parsed {
+            Some((stat_type, modifier)) => saves.push(Stat {
+                value: stats
+                    .iter()
+                    .find(|x| x.stat_type == stat_type)
+                    .map(|x| x.value)
+                    .unwrap_or_default(),
+                stat_type,
+                modifier,
+            }),
+            None => warnings.push(Warning::at(
+                line_no,
+                format!("invalid saving throw \"{}\"", x),
+            )),
+        }
+    }
+    saves
+}
+
+fn parse_skills(text: &str, line_no: usize, warnings: &mut Vec<Warning>) -> Vec<Skill> {
+    let mut skills = Vec::new();
+    for x in split_list(text) {
+        let parsed = x.rsplit_once(' ').and_then(|(skill, modifier)| {
+            Some(Skill {
+                skill_type: skill.parse::<SkillType>().ok()?,
+                modifier: modifier.parse::<i32>().ok()?,
+            })
+        });
+        match parsed {
+            Some(skill) => skills.push(skill),
+            None => warnings.push(Warning::at(line_no, format!("invalid skill \"{}\"", x))),
+        }
+    }
+    skills
+}
+
+/// Picks the damage types out of text like `bludgeoning, piercing, and slashing from
+/// nonmagical attacks`, warning about any qualifier that can't be represented.
+pub(crate) fn parse_damage_types(
+    text: &str,
+    line_no: usize,
+    warnings: &mut Vec<Warning>,
+) -> Vec<DamageType> {
+    let mut types = Vec::new();
+    let mut ignored = Vec::new();
+    for word in text.split(|c: char| !c.is_alphabetic()) {
+        if word.is_empty() || word == "and" {
+            continue;
+        }
+        match word.parse::<DamageType>() {
+            Ok(x) if !types.contains(&x) => types.push(x),
+            Ok(_) => {}
+            Err(_) => ignored.push(word),
+        }
+    }
+    if !ignored.is_empty() {
+        warnings.push(Warning::at(
+            line_no,
+            format!("ignored damage qualifier \"{}\"", ignored.join(" ")),
+        ));
+    }
+    types
+}
+
+/// Splits `Name. Description` paragraphs, returning `None` when the text before the first
+/// period is too long to be a name.
+pub(crate) fn split_name(text: &str) -> Option<(String, String)> {
+    let (name, description) = text.split_once(". ")?;
+    let name = name.trim();
+    if name.is_empty() || name.split_whitespace().count() > 6 {
+        return None;
+    }
+    Some((name.to_string(), description.trim().to_string()))
+}
+
+/// Joins wrapped lines back into paragraphs; a line starts a new paragraph when it names a
+/// trait or action, is a bullet, or the previous line ended a sentence.
+fn paragraphs(blocks: Vec<(Section, Block)>) -> Vec<(Section, Block)> {
+    let mut result: Vec<(Section, Block)> = Vec::new();
+    for (section, block) in blocks {
+        match result.last_mut() {
+            Some((last_section, last))
+                if *last_section == section
+                    && !block.named
+                    && !block.bullet
+                    && !last.text.ends_with('.') =>
+            {
+                last.text.push(' ');
+                last.text.push_str(&block.text);
+            }
+            _ => result.push((section, block)),
+        }
+    }
+    result
+}
+
+mod test {
+    #[test]
+    fn test_parse_goblin() {
+        let text = "Goblin
+Small humanoid (goblinoid), neutral evil
+
+Armor Class 15 (leather armor, shield)
+Hit Points 7 (2d6)
+Speed 30 ft.
+
+STR DEX CON INT WIS CHA
+8 (-1) 14 (+2) 10 (+0) 10 (+0) 8 (-1) 8 (-1)
+
+Skills Stealth +6
+Senses darkvision 60 ft., passive Perception 9
+Languages Common, Goblin
+Challenge 1/4 (50 XP)
+
+Nimble Escape. The goblin can take the Disengage or Hide action as a bonus action on each of
+its turns.
+
+Actions
+Scimitar. Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage.
+Shortbow. Ranged Weapon Attack: +4 to hit, range 80/320 ft., one target. Hit: 5 (1d6 + 2) piercing damage.
+";
+        let (creature, warnings) = super::parse_stat_block(text);
+        assert_eq!(warnings, vec![]);
+        assert_eq!(creature.name(), "Goblin");
+        assert_eq!(creature.size(), &super::Size::Small);
+        assert_eq!(
+            creature.creature_type(),
+            &super::CreatureType::Monster(super::MonsterType::Humanoid)
+        );
+        assert_eq!(creature.alignment(), &crate::Alignment::NeutralEvil);
+        assert_eq!(creature.armor_class(), &15);
+        assert_eq!(creature.health_points().health.value(), 7);
+        assert_eq!(creature.stats().len(), 6);
+        assert_eq!(creature.stats()[1].modifier, 2);
+        assert_eq!(creature.skills().unwrap()[0].modifier, 6);
+        assert_eq!(creature.languages().unwrap().len(), 2);
+        assert_eq!(creature.challenge_rating(), "1/4");
+        let traits = creature.racial_traits().unwrap();
+        assert_eq!(traits.len(), 1);
+        assert_eq!(traits[0].name, "Nimble Escape");
+        assert!(traits[0].description.ends_with("on each of its turns."));
+        assert_eq!(creature.actions().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_markdown_stat_block() {
+        let text = "> ## Young Dragon
+> *Large dragon, chaotic evil*
+> ___
+> - **Armor Class** 18 (natural armor)
+> - **Hit Points** 178 (17d10 + 85)
+> - **Speed** 40 ft., fly 80 ft., swim 40 ft.
+>___
+>|STR|DEX|CON|INT|WIS|CHA|
+>|:---:|:---:|:---:|:---:|:---:|:---:|
+>|23 (+6)|10 (+0)|21 (+5)|14 (+2)|11 (+0)|19 (+4)|
+>___
+> - **Saving Throws** Dex +4, Con +9
+> - **Damage Immunities** fire
+> - **Senses** blindsight 30 ft., darkvision 120 ft., passive Perception 18
+> - **Languages** Common, Draconic, telepathy 60 ft.
+> - **Challenge** 10 (5,900 XP)
+> ### Actions
+> ***Multiattack.*** The dragon makes three attacks.
+> ***Bite.*** *Melee Weapon Attack:* +10 to hit, reach 10 ft., one target. *Hit:* 17 (2d10 + 6) piercing damage plus 3 (1d6) fire damage.
+> ### Legendary Actions
+> The dragon can take 3 legendary actions, choosing from the options below.
+> ***Tail Attack.*** The dragon makes a tail attack.
+";
+        let (creature, warnings) = super::parse_stat_block(text);
+        assert_eq!(creature.name(), "Young Dragon");
+        assert_eq!(creature.size(), &super::Size::Large);
+        assert_eq!(creature.armor_class(), &18);
+        assert_eq!(creature.stats()[0].value, 23);
+        assert_eq!(creature.saving_throws().unwrap()[1].modifier, 9);
+        assert_eq!(creature.saving_throws().unwrap()[1].value, 21);
+        assert_eq!(creature.senses().unwrap().len(), 2);
+        assert_eq!(creature.challenge_rating(), "10");
+        assert_eq!(creature.others().unwrap().len(), 3);
+        let actions = creature.actions().unwrap();
+        assert_eq!(actions.len(), 3);
+        assert!(matches!(actions[1].action, super::ActionType::Attack(_)));
+        assert!(matches!(
+            actions[2].action,
+            super::ActionType::LegendaryAction(_)
+        ));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("telepathy"));
+    }
+
+    #[test]
+    fn test_parse_partial_stat_block() {
+        let (creature, warnings) = super::parse_stat_block("Mystery\nArmor Class 12\n");
+        assert_eq!(creature.name(), "Mystery");
+        assert_eq!(creature.armor_class(), &12);
+        assert!(warnings
+            .iter()
+            .any(|x| x.message == "missing hit points" && x.line.is_none()));
+        assert_eq!(super::parse_health("7 ) (2d6").unwrap().health.value(), 7);
+
+        let (creature, warnings) =
+            super::parse_stat_block("Titan\nSTR DEX CON INT WIS CHA\n-2147483648 10 10 10 10 99\n");
+        let stats = creature.stats();
+        assert_eq!((stats[0].value, stats[0].modifier), (1, -5));
+        assert_eq!((stats[5].value, stats[5].modifier), (30, 10));
+        let clamped = warnings.iter().filter(|x| x.line == Some(3)).count();
+        assert_eq!(clamped, 2);
+
+        assert_eq!(
+            super::field("SKILLS: Stealth +6"),
+            Some(("skills", "Stealth +6"))
+        );
+        assert_eq!(super::field("S\u{212A}ills Stealth +6"), None);
+    }
+}
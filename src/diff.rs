@@ -0,0 +1,490 @@
+//! Structural diffs and three-way merges of the serialized types.
+//!
+//! [`diff`] compares two values field by field and yields a [`Patch`] that serializes as a
+//! JSON Patch (RFC 6902) document, with paths written as JSON Pointers into the serialized
+//! form. [`merge`] combines two edited copies of a common base: edits to different fields are
+//! both kept, and fields that both sides changed differently are reported as conflicts.
+
+use core::fmt;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One change of a [`Patch`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+impl Operation {
+    pub fn path(&self) -> &str {
+        match &self {
+            Operation::Add { path, .. }
+            | Operation::Remove { path }
+            | Operation::Replace { path, .. } => path,
+        }
+    }
+}
+
+/// A list of changes, in the order they are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct Patch(pub Vec<Operation>);
+
+impl Patch {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Applies the patch to a serialized value. If an operation fails, the value is left as
+    /// it was.
+    pub fn apply(&self, value: &mut Value) -> Result<(), DiffError> {
+        let mut patched = value.clone();
+        self.0
+            .iter()
+            .try_for_each(|x| apply_operation(&mut patched, x))?;
+        *value = patched;
+        Ok(())
+    }
+
+    /// Applies the patch to a copy of `value`.
+    pub fn apply_to<T: Serialize + DeserializeOwned>(&self, value: &T) -> Result<T, DiffError> {
+        let mut value = serde_json::to_value(value)?;
+        self.apply(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// The changes that turn `old` into `new`.
+pub fn diff<T: Serialize>(old: &T, new: &T) -> Result<Patch, DiffError> {
+    Ok(diff_values(
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(new)?,
+    ))
+}
+
+/// The changes that turn one serialized value into another. Objects are compared by key and
+/// lists by position, so a change deep inside a value yields one operation at that place.
+pub fn diff_values(old: &Value, new: &Value) -> Patch {
+    let mut operations = Vec::new();
+    diff_at(String::new(), old, new, &mut operations);
+    Patch(operations)
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, operations: &mut Vec<Operation>) {
+    match (old, new) {
+        _ if old == new => {}
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in old {
+                let path = child(&path, key);
+                match new.get(key) {
+                    Some(new) => diff_at(path, value, new, operations),
+                    None => operations.push(Operation::Remove { path }),
+                }
+            }
+            for (key, value) in new {
+                if !old.contains_key(key) {
+                    operations.push(Operation::Add {
+                        path: child(&path, key),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for (i, (old, new)) in old.iter().zip(new).enumerate() {
+                diff_at(child(&path, &i.to_string()), old, new, operations);
+            }
+            // Removing from the end keeps the indices of the remaining elements valid.
+            for i in (new.len()..old.len()).rev() {
+                operations.push(Operation::Remove {
+                    path: child(&path, &i.to_string()),
+                });
+            }
+            for (i, value) in new.iter().enumerate().skip(old.len()) {
+                operations.push(Operation::Add {
+                    path: child(&path, &i.to_string()),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => operations.push(Operation::Replace {
+            path,
+            value: new.clone(),
+        }),
+    }
+}
+
+/// Appends a key to a JSON Pointer, escaping `~` and `/` as RFC 6901 requires.
+fn child(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn apply_operation(root: &mut Value, operation: &Operation) -> Result<(), DiffError> {
+    let path = operation.path();
+    if !path.is_empty() && !path.starts_with('/') {
+        return Err(DiffError::path(path, "not a JSON Pointer"));
+    }
+    let Some((parent, key)) = path.rsplit_once('/') else {
+        return match operation {
+            Operation::Add { value, .. } | Operation::Replace { value, .. } => {
+                *root = value.clone();
+                Ok(())
+            }
+            Operation::Remove { .. } => Err(DiffError::path(path, "cannot remove the root")),
+        };
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    let target = root
+        .pointer_mut(parent)
+        .ok_or_else(|| DiffError::path(path, "no such location"))?;
+    match (target, operation) {
+        (Value::Object(fields), Operation::Add { value, .. }) => {
+            fields.insert(key, value.clone());
+        }
+        (Value::Object(fields), Operation::Replace { value, .. }) => match fields.get_mut(&key) {
+            Some(field) => *field = value.clone(),
+            None => return Err(DiffError::path(path, "no such field")),
+        },
+        (Value::Object(fields), Operation::Remove { .. }) => {
+            fields
+                .remove(&key)
+                .ok_or_else(|| DiffError::path(path, "no such field"))?;
+        }
+        (Value::Array(values), operation) => {
+            let index = match (key.as_str(), operation) {
+                ("-", Operation::Add { .. }) => values.len(),
+                _ => key
+                    .parse::<usize>()
+                    .map_err(|_| DiffError::path(path, "invalid index"))?,
+            };
+            match operation {
+                Operation::Add { value, .. } if index <= values.len() => {
+                    values.insert(index, value.clone())
+                }
+                Operation::Replace { value, .. } if index < values.len() => {
+                    values[index] = value.clone()
+                }
+                Operation::Remove { .. } if index < values.len() => {
+                    values.remove(index);
+                }
+                _ => return Err(DiffError::path(path, "index out of range")),
+            }
+        }
+        _ => return Err(DiffError::path(path, "not inside an object or a list")),
+    }
+    Ok(())
+}
+
+/// A place both sides of a merge changed differently. Absent values were removed, or did not
+/// exist yet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: String,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// The result of a three-way merge. Where there are conflicts, `merged` keeps our side.
+#[derive(Debug, Clone)]
+pub struct Merge<T> {
+    pub merged: T,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl<T> Merge<T> {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges two edited copies, `ours` and `theirs`, of a common `base`.
+pub fn merge<T: Serialize + DeserializeOwned>(
+    base: &T,
+    ours: &T,
+    theirs: &T,
+) -> Result<Merge<T>, DiffError> {
+    let base = serde_json::to_value(base)?;
+    let ours = serde_json::to_value(ours)?;
+    let theirs = serde_json::to_value(theirs)?;
+    let mut conflicts = Vec::new();
+    let merged = merge_at(
+        String::new(),
+        Some(&base),
+        Some(&ours),
+        Some(&theirs),
+        &mut conflicts,
+    )
+    .unwrap_or(Value::Null);
+    Ok(Merge {
+        merged: serde_json::from_value(merged)?,
+        conflicts,
+    })
+}
+
+fn merge_at(
+    path: String,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        // Enum variants serialize as objects with a single key, so a changed key on either
+        // side is a different variant, which cannot be merged field by field.
+        (Some(Value::Object(base)), Some(Value::Object(ours)), Some(Value::Object(theirs)))
+            if [base, ours, theirs].iter().all(|x| x.len() == 1)
+                && !(base.keys().eq(ours.keys()) && base.keys().eq(theirs.keys())) =>
+        {
+            conflicts.push(Conflict {
+                path,
+                base: Some(Value::Object(base.clone())),
+                ours: Some(Value::Object(ours.clone())),
+                theirs: Some(Value::Object(theirs.clone())),
+            });
+            Some(Value::Object(ours.clone()))
+        }
+        (Some(Value::Object(base)), Some(Value::Object(ours)), Some(Value::Object(theirs))) => {
+            let mut merged = Map::new();
+            let keys = ours
+                .keys()
+                .chain(theirs.keys().filter(|x| !ours.contains_key(*x)));
+            for key in keys {
+                let value = merge_at(
+                    child(&path, key),
+                    base.get(key),
+                    ours.get(key),
+                    theirs.get(key),
+                    conflicts,
+                );
+                if let Some(value) = value {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Object(merged))
+        }
+        // Elements both sides kept are merged by position. Past the shortest of the three
+        // lists, only one side may have added or removed elements.
+        (Some(Value::Array(base)), Some(Value::Array(ours)), Some(Value::Array(theirs)))
+            if merge_tail(base, ours, theirs).is_some() =>
+        {
+            let shared = base.len().min(ours.len()).min(theirs.len());
+            let mut merged: Vec<Value> = (0..shared)
+                .filter_map(|i| {
+                    merge_at(
+                        child(&path, &i.to_string()),
+                        Some(&base[i]),
+                        Some(&ours[i]),
+                        Some(&theirs[i]),
+                        conflicts,
+                    )
+                })
+                .collect();
+            merged.extend_from_slice(merge_tail(base, ours, theirs).unwrap_or_default());
+            Some(Value::Array(merged))
+        }
+        _ => {
+            conflicts.push(Conflict {
+                path,
+                base: base.cloned(),
+                ours: ours.cloned(),
+                theirs: theirs.cloned(),
+            });
+            ours.cloned()
+        }
+    }
+}
+
+/// The elements after the ones all three lists share, if at most one side changed them.
+fn merge_tail<'a>(
+    base: &'a [Value],
+    ours: &'a [Value],
+    theirs: &'a [Value],
+) -> Option<&'a [Value]> {
+    let shared = base.len().min(ours.len()).min(theirs.len());
+    let (base, ours, theirs) = (&base[shared..], &ours[shared..], &theirs[shared..]);
+    if ours == theirs || theirs == base {
+        Some(ours)
+    } else if ours == base {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+    Json(serde_json::Error),
+    /// An operation of a patch does not fit the value it is applied to.
+    Path {
+        path: String,
+        message: String,
+    },
+}
+
+impl DiffError {
+    fn path(path: &str, message: &str) -> Self {
+        DiffError::Path {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            DiffError::Json(e) => write!(f, "{}", e),
+            DiffError::Path { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+impl From<serde_json::Error> for DiffError {
+    fn from(e: serde_json::Error) -> Self {
+        DiffError::Json(e)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_diff_and_apply() {
+        use serde_json::json;
+
+        let (mut creature, _) = crate::creature::parse::parse_stat_block(
+            "Goblin
+Small humanoid (goblinoid), neutral evil
+Armor Class 15 (leather armor, shield)
+Hit Points 7 (2d6)
+Speed 30 ft.
+STR DEX CON INT WIS CHA
+8 (-1) 14 (+2) 10 (+0) 10 (+0) 8 (-1) 8 (-1)
+Languages Common, Goblin
+Challenge 1/4 (50 XP)",
+        );
+        let old = creature.clone();
+        creature.set_armor_class(17).unwrap();
+        creature.set_description(Some(String::from("A small, black-hearted humanoid.")));
+        creature.set_languages(None);
+
+        let patch = super::diff(&old, &creature).unwrap();
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            json!([
+                {"op": "replace", "path": "/armor_class", "value": 17},
+                {"op": "replace", "path": "/description",
+                    "value": "A small, black-hearted humanoid."},
+                {"op": "replace", "path": "/languages", "value": null},
+            ])
+        );
+        let patched = patch.apply_to(&old).unwrap();
+        assert!(super::diff(&patched, &creature).unwrap().is_empty());
+
+        // Lists shrink from the end and grow at it; keys are escaped as JSON Pointers.
+        let old = json!({"a/b": [1, 2, 3], "c~": {"d": true}});
+        let new = json!({"a/b": [1, 5], "c~": {}, "e": [4]});
+        let patch = super::diff_values(&old, &new);
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            json!([
+                {"op": "replace", "path": "/a~1b/1", "value": 5},
+                {"op": "remove", "path": "/a~1b/2"},
+                {"op": "remove", "path": "/c~0/d"},
+                {"op": "add", "path": "/e", "value": [4]},
+            ])
+        );
+        let mut patched = old.clone();
+        patch.apply(&mut patched).unwrap();
+        assert_eq!(patched, new);
+
+        // A failed patch leaves the value untouched, and paths must be JSON Pointers.
+        for operation in [
+            json!({"op": "remove", "path": "/missing"}),
+            json!({"op": "replace", "path": "a", "value": 1}),
+        ] {
+            let patch: super::Patch = serde_json::from_value(json!([
+                {"op": "remove", "path": "/e"},
+                operation,
+            ]))
+            .unwrap();
+            assert!(matches!(
+                patch.apply(&mut patched),
+                Err(super::DiffError::Path { .. })
+            ));
+            assert_eq!(patched, new);
+        }
+    }
+
+    #[test]
+    fn test_three_way_merge() {
+        use crate::{
+            id::CampaignId,
+            world::campaign::session::{Note, Session},
+        };
+
+        let mut base = Session::new(String::from("Session 1"), String::new(), CampaignId::new());
+        base.notes = vec![Note::new(String::from("Loot"), String::from("A key"))];
+
+        let mut ours = base.clone();
+        ours.name = String::from("The Sunless Citadel");
+        ours.notes[0].description = String::from("A silver key");
+        let mut theirs = base.clone();
+        theirs.description = String::from("The party enters the ravine.");
+        theirs.notes[0].name = String::from("Treasure");
+
+        let merge = super::merge(&base, &ours, &theirs).unwrap();
+        assert!(merge.is_clean());
+        assert_eq!(merge.merged.name, "The Sunless Citadel");
+        assert_eq!(merge.merged.description, "The party enters the ravine.");
+        assert_eq!(merge.merged.notes[0].name, "Treasure");
+        assert_eq!(merge.merged.notes[0].description, "A silver key");
+
+        theirs
+            .notes
+            .push(Note::new(String::from("Map"), String::new()));
+        let merge = super::merge(&base, &ours, &theirs).unwrap();
+        assert!(merge.is_clean());
+        assert_eq!(merge.merged.notes.len(), 2);
+        assert_eq!(merge.merged.notes[0].description, "A silver key");
+
+        theirs.notes[0].description = String::from("A golden key");
+        let merge = super::merge(&base, &ours, &theirs).unwrap();
+        let paths: Vec<&str> = merge.conflicts.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(paths, vec!["/notes/0/description"]);
+        assert_eq!(merge.merged.notes.len(), 2);
+        assert_eq!(
+            merge.conflicts[0].theirs,
+            Some(serde_json::json!("A golden key"))
+        );
+        assert_eq!(merge.merged.notes[0].description, "A silver key");
+
+        // Changing an enum to different variants on both sides is one conflict.
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        enum Movement {
+            Walk(u32),
+            Burrow(u32),
+            Swim(u32),
+        }
+        let merge = super::merge(
+            &Movement::Walk(30),
+            &Movement::Burrow(60),
+            &Movement::Swim(20),
+        )
+        .unwrap();
+        assert_eq!(merge.merged, Movement::Burrow(60));
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].path, "");
+    }
+}
@@ -0,0 +1,159 @@
+//! The error type shared by the whole crate.
+//!
+//! Each module reports its own failures with its own error type, such as
+//! [`MigrationError`](crate::migration::MigrationError) or
+//! [`HistoryError`](crate::world::campaign::history::HistoryError). [`Error`] wraps all of
+//! them, so code that calls into several modules can return one type and still match on the
+//! cause.
+
+use core::fmt;
+
+use crate::{
+    compendium::CompendiumError,
+    convert::ConvertError,
+    crdt::CrdtError,
+    diff::DiffError,
+    migration::MigrationError,
+    sync::SyncError,
+    world::campaign::{history::HistoryError, ResolveError},
+    ParseError,
+};
+
+/// A value that the type it was given to does not allow, such as a level above 20.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &'static str, message: String) -> Self {
+        Self { field, message }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug)]
+pub enum Error {
+    Validation(ValidationError),
+    Parse(ParseError),
+    Convert(ConvertError),
+    Compendium(CompendiumError),
+    Migration(MigrationError),
+    Resolve(ResolveError),
+    History(HistoryError),
+    Diff(DiffError),
+    Crdt(CrdtError),
+    Sync(SyncError),
+    #[cfg(feature = "storage")]
+    Storage(crate::storage::StorageError),
+    #[cfg(feature = "client")]
+    Client(crate::client::ClientError),
+}
+
+impl Error {
+    fn inner(&self) -> &(dyn std::error::Error + 'static) {
+        match &self {
+            Error::Validation(e) => e,
+            Error::Parse(e) => e,
+            Error::Convert(e) => e,
+            Error::Compendium(e) => e,
+            Error::Migration(e) => e,
+            Error::Resolve(e) => e,
+            Error::History(e) => e,
+            Error::Diff(e) => e,
+            Error::Crdt(e) => e,
+            Error::Sync(e) => e,
+            #[cfg(feature = "storage")]
+            Error::Storage(e) => e,
+            #[cfg(feature = "client")]
+            Error::Client(e) => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+/// The wrapped error is displayed as this one, so the chain continues with its source.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+macro_rules! from_error {
+    ($($(#[$attr:meta])* $variant:ident($error:ty)),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl From<$error> for Error {
+                fn from(e: $error) -> Self {
+                    Error::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+from_error!(
+    Validation(ValidationError),
+    Parse(ParseError),
+    Convert(ConvertError),
+    Compendium(CompendiumError),
+    Migration(MigrationError),
+    Resolve(ResolveError),
+    History(HistoryError),
+    Diff(DiffError),
+    Crdt(CrdtError),
+    Sync(SyncError),
+    #[cfg(feature = "storage")]
+    Storage(crate::storage::StorageError),
+    #[cfg(feature = "client")]
+    Client(crate::client::ClientError),
+);
+
+mod test {
+    #[test]
+    fn test_errors_convert() {
+        use super::Error;
+        use crate::{
+            creature::{parse::parse_stat_block, CreatureType},
+            id::CampaignId,
+            world::campaign::player::{Class, Player, Race},
+        };
+
+        fn recruit(creature_type: CreatureType, level: u8) -> Result<Player, Error> {
+            let (mut creature, _) = parse_stat_block("Ada\nArmor Class 16\n");
+            creature.set_creature_type(creature_type);
+            let mut player = Player::try_new(
+                creature,
+                Race::Human,
+                Class::Fighter,
+                1,
+                0,
+                CampaignId::new(),
+            )?;
+            player.set_level(level)?;
+            Ok(player)
+        }
+
+        assert_eq!(*recruit(CreatureType::Player, 3).unwrap().level(), 3);
+        let error = recruit(CreatureType::NPC, 3).unwrap_err();
+        assert!(matches!(&error, Error::Validation(e) if e.field == "creature_type"));
+        assert!(std::error::Error::source(&error).is_none());
+        assert_eq!(
+            recruit(CreatureType::Player, 21).unwrap_err().to_string(),
+            "invalid level: 21 is not between 1 and 20"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Typed identifiers for the entities that refer to each other by id.
+//!
+//! Every id wraps a [`Uuid`] and serializes as its hyphenated string, so stored data keeps the
+//! same shape while a `CampaignId` can no longer be passed where a `WorldId` is expected.
+//! Deserializing rejects strings that are not valid UUIDs; stored data from before typed ids
+//! is upgraded by the [migrations](crate::migration), which replace each old string id with
+//! the id [`from_legacy`](WorldId::from_legacy) derives from it.
+//!
+//! Items, spells and actions keep plain string ids, since compendium packs address them by
+//! human-readable slugs such as `"fireball"`.
+
+use core::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ParseError;
+
+macro_rules! id_type {
+    ($($(#[$doc:meta])* $name:ident,)*) => {$(
+        $(#[$doc])*
+        #[derive(
+            Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default,
+        )]
+        #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+        #[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+        pub struct $name(Uuid);
+
+        impl $name {
+            /// A new random id.
+            pub fn new() -> Self {
+                Self(Uuid::new_v4())
+            }
+
+            pub fn as_uuid(&self) -> &Uuid {
+                &self.0
+            }
+
+            /// The id for an id stored before ids were UUIDs, such as `"goblin"`. The same old
+            /// id always gives the same UUID, so references between entities stay intact;
+            /// strings that already are UUIDs are kept.
+            pub fn from_legacy(id: &str) -> Self {
+                Self(legacy_uuid(id))
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(uuid: Uuid) -> Self {
+                Self(uuid)
+            }
+        }
+
+        impl From<$name> for Uuid {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::parse_str(s)
+                    .map(Self)
+                    .map_err(|_| ParseError::new(s, "invalid uuid"))
+            }
+        }
+    )*};
+}
+
+/// The namespace of the UUIDs derived from ids stored before ids were UUIDs.
+const LEGACY_NAMESPACE: Uuid = Uuid::from_u128(0x6a0c_2f7e_3b1d_4c59_9e84_d2a7_51f0_c3b6);
+
+pub(crate) fn legacy_uuid(id: &str) -> Uuid {
+    Uuid::parse_str(id).unwrap_or_else(|_| Uuid::new_v5(&LEGACY_NAMESPACE, id.as_bytes()))
+}
+
+id_type! {
+    /// Identifies a [`World`](crate::world::World).
+    WorldId,
+    /// Identifies a [`Campaign`](crate::world::campaign::Campaign).
+    CampaignId,
+    /// Identifies a [`Session`](crate::world::campaign::session::Session).
+    SessionId,
+    /// Identifies a [`Note`](crate::world::campaign::session::Note).
+    NoteId,
+    /// Identifies a [`Map`](crate::world::map::Map).
+    MapId,
+    /// Identifies a [`Location`](crate::world::location::Location).
+    LocationId,
+    /// Identifies a [`Creature`](crate::creature::Creature), and through it a player.
+    CreatureId,
+}
+
+mod test {
+    #[test]
+    fn test_ids_serialize_as_strings() {
+        use super::{CampaignId, WorldId};
+
+        let id = WorldId::new();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"{}\"", id));
+        assert_eq!(serde_json::from_str::<WorldId>(&json).unwrap(), id);
+        assert_eq!(id.to_string().parse::<WorldId>().unwrap(), id);
+
+        // The same string reads as any kind of id, but the types keep them apart afterwards.
+        let campaign: CampaignId = serde_json::from_str(&json).unwrap();
+        assert_eq!(campaign.as_uuid(), id.as_uuid());
+
+        let error = serde_json::from_str::<WorldId>("\"not-a-uuid\"").unwrap_err();
+        assert!(error.to_string().contains("UUID"), "{}", error);
+        assert!("w".parse::<WorldId>().is_err());
+
+        // Old string ids always map to the same UUID; UUIDs are kept as they are.
+        assert_eq!(WorldId::from_legacy("w"), WorldId::from_legacy("w"));
+        assert_ne!(WorldId::from_legacy("w"), WorldId::from_legacy("x"));
+        assert_eq!(WorldId::from_legacy(&id.to_string()), id);
+    }
+}
@@ -6,6 +6,8 @@ use strum::EnumIter;
 use crate::{action::Action, spell::Spell, Alignment, ConditionType, OtherAttribute};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Item {
     pub id: String,
     pub name: String,
@@ -23,6 +25,8 @@ pub struct Item {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum ItemType {
     Armor,
     Potion,
@@ -45,6 +49,8 @@ impl fmt::Display for ItemType {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum ItemRarity {
     Common,
     Uncommon,
@@ -67,11 +73,14 @@ impl fmt::Display for ItemRarity {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Attuneable {
     pub alignments: Option<Vec<Alignment>>,
 }
 
 impl fmt::Display for Attuneable {
+    #[allow(clippy::unnecessary_unwrap)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -96,6 +105,8 @@ impl fmt::Display for Attuneable {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum WeaponType {
     Sword,
 }
@@ -107,6 +118,8 @@ impl fmt::Display for WeaponType {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum ArmorType {
     Shield,
 }
@@ -118,6 +131,8 @@ impl fmt::Display for ArmorType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Charge {
     pub num: i32,
     pub time: TimeDivision,
@@ -130,6 +145,8 @@ impl fmt::Display for Charge {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum TimeDivision {
     Round,
     Second,
@@ -7,18 +7,38 @@
 //! gamemstr = "0.1.0"
 //! ```
 
-use core::fmt;
+use core::{fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
-use strum::EnumIter;
+use strum::{EnumIter, EnumString};
 
 pub mod action;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod compendium;
+pub mod convert;
+pub mod crdt;
 pub mod creature;
+pub mod diff;
+pub mod error;
+pub mod id;
 pub mod item;
+pub mod migration;
+pub mod render;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod spell;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod sync;
+#[cfg(feature = "typescript")]
+pub mod typescript;
 pub mod world;
 
-#[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq, Default)]
+#[derive(Serialize, Deserialize, Debug, EnumIter, EnumString, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[strum(ascii_case_insensitive)]
 pub enum DamageType {
     #[default]
     Slashing,
@@ -42,7 +62,10 @@ impl fmt::Display for DamageType {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, EnumIter, EnumString, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[strum(ascii_case_insensitive)]
 pub enum ConditionType {
     Blinded,
     Charmed,
@@ -68,6 +91,8 @@ impl fmt::Display for ConditionType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Die {
     #[default]
     D4,
@@ -101,9 +126,37 @@ impl Die {
     pub fn to_f64(&self) -> f64 {
         self.to_i32() as f64
     }
+
+    pub fn from_i32(sides: i32) -> Option<Self> {
+        match sides {
+            4 => Some(Die::D4),
+            6 => Some(Die::D6),
+            8 => Some(Die::D8),
+            10 => Some(Die::D10),
+            12 => Some(Die::D12),
+            20 => Some(Die::D20),
+            100 => Some(Die::D100),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Die {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sides = s.trim().trim_start_matches(['d', 'D']);
+        sides
+            .parse()
+            .ok()
+            .and_then(Die::from_i32)
+            .ok_or_else(|| ParseError::new(s, "unknown die"))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct DieStat {
     pub die_count: i32,
     pub die_type: Die,
@@ -111,24 +164,102 @@ pub struct DieStat {
 }
 
 impl DieStat {
+    /// The most dice a parsed expression may roll.
+    pub const MAX_COUNT: i32 = 1000;
+    /// The largest modifier, positive or negative, a parsed expression may have.
+    pub const MAX_EXTRA: i32 = 100_000;
+
     pub fn value(&self) -> i32 {
-        (self.die_count as f64 * (self.die_type.to_f64() / 2_f64 + 0.5)).floor() as i32 + self.extra
+        ((self.die_count as f64 * (self.die_type.to_f64() / 2_f64 + 0.5)).floor() as i32)
+            .saturating_add(self.extra)
+    }
+}
+
+/// Parses dice expressions such as `2d6`, `2d6+3`, `1d8 - 1` or a flat `5`.
+impl FromStr for DieStat {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let compact = compact.replace("+-", "-");
+        let (dice, extra) = match compact
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '+' || *c == '-')
+        {
+            Some((i, _)) => compact.split_at(i),
+            None => (compact.as_str(), ""),
+        };
+        let extra = match extra.strip_prefix('+').unwrap_or(extra) {
+            "" => 0,
+            x => x
+                .parse()
+                .map_err(|_| ParseError::new(s, "invalid modifier"))?,
+        };
+        let stat = match dice.split_once(['d', 'D']) {
+            Some((count, die)) => Self {
+                die_count: match count {
+                    "" => 1,
+                    x => x
+                        .parse()
+                        .map_err(|_| ParseError::new(s, "invalid die count"))?,
+                },
+                die_type: die.parse().map_err(|_| ParseError::new(s, "unknown die"))?,
+                extra,
+            },
+            None => Self {
+                die_count: 0,
+                die_type: Die::default(),
+                extra: dice
+                    .parse::<i32>()
+                    .ok()
+                    .and_then(|x| x.checked_add(extra))
+                    .ok_or_else(|| ParseError::new(s, "invalid dice expression"))?,
+            },
+        };
+        if !(0..=Self::MAX_COUNT).contains(&stat.die_count) {
+            return Err(ParseError::new(s, "too many dice"));
+        }
+        if !(-Self::MAX_EXTRA..=Self::MAX_EXTRA).contains(&stat.extra) {
+            return Err(ParseError::new(s, "modifier out of range"));
+        }
+        Ok(stat)
     }
 }
 
 impl fmt::Display for DieStat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}{} + {}",
-            self.die_count,
-            self.die_type,
-            self.extra
-        )
+        write!(f, "{}{} + {}", self.die_count, self.die_type, self.extra)
+    }
+}
+
+/// Error returned when text cannot be turned into one of the crate's types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub input: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, message: &str) -> Self {
+        Self {
+            input: input.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: \"{}\"", self.message, self.input)
     }
 }
 
+impl std::error::Error for ParseError {}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct OtherAttribute {
     pub title: String,
     pub description: String,
@@ -142,6 +273,8 @@ impl fmt::Display for OtherAttribute {
 }
 
 #[derive(Serialize, Deserialize, Debug, EnumIter, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Alignment {
     #[default]
     AnyAlignment,
@@ -212,3 +345,19 @@ impl fmt::Display for Alignment {
         }
     }
 }
+
+impl FromStr for Alignment {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+
+        let text = s.trim().to_lowercase();
+        if text == "neutral" {
+            return Ok(Alignment::TrueNeutral);
+        }
+        Alignment::iter()
+            .find(|x| x.to_string() == text)
+            .ok_or_else(|| ParseError::new(s, "unknown alignment"))
+    }
+}
@@ -0,0 +1,752 @@
+//! Versioned storage of serialized entities.
+//!
+//! Stored entities are wrapped in an [`Envelope`] naming their kind and schema version. Each
+//! kind keeps its own list of migrations; reading older JSON applies them one version at a
+//! time until the data matches the current types. JSON stored before envelopes existed is read
+//! as version 1.
+//!
+//! Versions so far:
+//! - 2: attacks hold a list of damage components instead of one `damage` and `damage_type`,
+//!   and creatures have a `size`. This affects creatures, items (through their actions),
+//!   players, campaigns (through their players), locations (through their NPCs) and maps.
+//! - 3: players refer to their campaign by `campaign_id` and campaigns list their players in
+//!   `player_ids`, instead of embedding each other. This affects players and campaigns; use
+//!   [`split_campaign`] to keep the players an older campaign embedded.
+//! - 4: ids are UUIDs. Ids stored as other strings, such as `"goblin"`, are replaced by the
+//!   UUID derived from them, as [`WorldId::from_legacy`](crate::id::WorldId::from_legacy)
+//!   does. This affects creatures, players, campaigns, maps, locations and worlds.
+//!
+//! Sessions were versioned separately from the above:
+//! - 2: the plan and recap of a session are structured documents instead of plain text. The
+//!   old text becomes their summary.
+//! - 3: sessions have a schedule and record the attendance of players.
+//! - 4: ids are UUIDs, as in version 4 above.
+//!
+//! Entries of a campaign's [history](crate::world::campaign::history) follow the session
+//! versions, since they embed the sessions they add. Entries stored without an envelope are
+//! read as version 1 and only upgraded where a session still has an older shape.
+
+use core::fmt;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    creature::Creature,
+    id::legacy_uuid,
+    item::Item,
+    spell::Spell,
+    world::{
+        campaign::{
+            history::Entry,
+            player::Player,
+            session::{Plan, Recap, Schedule, Session},
+            Campaign,
+        },
+        location::Location,
+        map::Map,
+        World,
+    },
+};
+
+/// Upgrades the data of an entity by one version, in place.
+pub type Migration = fn(&mut Value) -> Result<(), MigrationError>;
+
+/// A type that is stored with a schema version.
+pub trait Versioned: Serialize + DeserializeOwned {
+    const KIND: &'static str;
+    /// The migrations from each version to the next, starting at version 1.
+    const MIGRATIONS: &'static [Migration];
+
+    fn version() -> u32 {
+        Self::MIGRATIONS.len() as u32 + 1
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub kind: String,
+    pub version: u32,
+    pub data: Value,
+}
+
+impl Envelope {
+    /// Wraps a value at the current version of its type.
+    pub fn new<T: Versioned>(value: &T) -> Result<Self, MigrationError> {
+        Ok(Self {
+            kind: T::KIND.to_string(),
+            version: T::version(),
+            data: serde_json::to_value(value)?,
+        })
+    }
+
+    /// Reads an envelope, or bare data from before envelopes existed, as version 1.
+    pub fn from_value<T: Versioned>(value: Value) -> Result<Self, MigrationError> {
+        let is_envelope = value
+            .as_object()
+            .is_some_and(|x| x.len() == 3 && x.contains_key("kind") && x.contains_key("version"));
+        if is_envelope {
+            Ok(serde_json::from_value(value)?)
+        } else {
+            Ok(Self {
+                kind: T::KIND.to_string(),
+                version: 1,
+                data: value,
+            })
+        }
+    }
+
+    /// Applies the migrations needed to bring the data up to the current version.
+    pub fn migrate<T: Versioned>(mut self) -> Result<Self, MigrationError> {
+        if self.kind != T::KIND {
+            return Err(MigrationError::WrongKind {
+                expected: T::KIND,
+                found: self.kind,
+            });
+        }
+        if self.version == 0 || self.version > T::version() {
+            return Err(MigrationError::UnsupportedVersion {
+                kind: T::KIND,
+                version: self.version,
+            });
+        }
+        for migration in &T::MIGRATIONS[self.version as usize - 1..] {
+            migration(&mut self.data)?;
+            self.version += 1;
+        }
+        Ok(self)
+    }
+
+    /// Migrates the data and reads it into its type.
+    pub fn into_inner<T: Versioned>(self) -> Result<T, MigrationError> {
+        Ok(serde_json::from_value(self.migrate::<T>()?.data)?)
+    }
+}
+
+/// Serializes a value inside an envelope.
+pub fn to_json<T: Versioned>(value: &T) -> Result<String, MigrationError> {
+    Ok(serde_json::to_string(&Envelope::new(value)?)?)
+}
+
+/// Reads a value stored by any version, with or without an envelope.
+pub fn from_json<T: Versioned>(text: &str) -> Result<T, MigrationError> {
+    Envelope::from_value::<T>(serde_json::from_str(text)?)?.into_inner()
+}
+
+/// Reads a stored campaign along with the players it embedded before version 3, which are
+/// otherwise dropped by the migration. The players are returned pointing at the campaign, ready
+/// to be stored on their own; campaigns from version 3 on return no players.
+pub fn split_campaign(text: &str) -> Result<(Campaign, Vec<Player>), MigrationError> {
+    let envelope = Envelope::from_value::<Campaign>(serde_json::from_str(text)?)?;
+    let embedded = match envelope.data.get("players") {
+        Some(Value::Array(players)) if envelope.version < 3 => players.clone(),
+        _ => Vec::new(),
+    };
+    let version = envelope.version;
+    let campaign: Campaign = envelope.into_inner()?;
+    let players = embedded
+        .into_iter()
+        .map(|data| {
+            let envelope = Envelope {
+                kind: Player::KIND.to_string(),
+                version,
+                data,
+            };
+            let mut player: Player = envelope.into_inner()?;
+            player.set_campaign_id(campaign.id);
+            Ok(player)
+        })
+        .collect::<Result<_, MigrationError>>()?;
+    Ok((campaign, players))
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Json(serde_json::Error),
+    WrongKind {
+        expected: &'static str,
+        found: String,
+    },
+    UnsupportedVersion {
+        kind: &'static str,
+        version: u32,
+    },
+    /// The data did not have the shape its version calls for.
+    Malformed {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            MigrationError::Json(e) => write!(f, "invalid JSON: {}", e),
+            MigrationError::WrongKind { expected, found } => {
+                write!(f, "expected a stored {} but found a {}", expected, found)
+            }
+            MigrationError::UnsupportedVersion { kind, version } => {
+                write!(f, "unsupported {} version {}", kind, version)
+            }
+            MigrationError::Malformed { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<serde_json::Error> for MigrationError {
+    fn from(e: serde_json::Error) -> Self {
+        MigrationError::Json(e)
+    }
+}
+
+impl Versioned for Creature {
+    const KIND: &'static str = "creature";
+    const MIGRATIONS: &'static [Migration] = &[creature_v2, unchanged, creature_v4];
+}
+
+impl Versioned for Item {
+    const KIND: &'static str = "item";
+    const MIGRATIONS: &'static [Migration] = &[item_v2];
+}
+
+impl Versioned for Spell {
+    const KIND: &'static str = "spell";
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+impl Versioned for Player {
+    const KIND: &'static str = "player";
+    const MIGRATIONS: &'static [Migration] = &[player_v2, player_v3, player_v4];
+}
+
+impl Versioned for Campaign {
+    const KIND: &'static str = "campaign";
+    const MIGRATIONS: &'static [Migration] = &[campaign_v2, campaign_v3, campaign_v4];
+}
+
+impl Versioned for Session {
+    const KIND: &'static str = "session";
+    const MIGRATIONS: &'static [Migration] = &[session_v2, session_v3, session_v4];
+}
+
+impl Versioned for Entry {
+    const KIND: &'static str = "history entry";
+    const MIGRATIONS: &'static [Migration] = &[entry_v2, entry_v3, entry_v4];
+}
+
+impl Versioned for Map {
+    const KIND: &'static str = "map";
+    const MIGRATIONS: &'static [Migration] = &[map_v2, unchanged, map_v4];
+}
+
+impl Versioned for Location {
+    const KIND: &'static str = "location";
+    const MIGRATIONS: &'static [Migration] = &[location_v2, unchanged, location_v4];
+}
+
+impl Versioned for World {
+    const KIND: &'static str = "world";
+    const MIGRATIONS: &'static [Migration] = &[unchanged, unchanged, world_v4];
+}
+
+fn malformed(path: &str, message: &str) -> MigrationError {
+    MigrationError::Malformed {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// A step that leaves the value as it is, for a version a kind moved to without its own format
+/// changing. Each kind numbers its versions separately, by the length of its migrations.
+fn unchanged(_: &mut Value) -> Result<(), MigrationError> {
+    Ok(())
+}
+
+/// Runs `migration` on every element of the array at `key`, when there is one.
+fn each(value: &mut Value, key: &str, migration: Migration) -> Result<(), MigrationError> {
+    match value.get_mut(key) {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::Array(values)) => values.iter_mut().try_for_each(migration),
+        Some(_) => Err(malformed(key, "expected a list")),
+    }
+}
+
+/// Turns the single `damage` and `damage_type` of an attack into a list of damage components.
+fn attack_damage_v2(action: &mut Value) -> Result<(), MigrationError> {
+    let Some(attack) = action
+        .get_mut("action")
+        .and_then(|x| x.get_mut("Attack"))
+        .and_then(Value::as_object_mut)
+        .and_then(|x| x.values_mut().next())
+        .and_then(Value::as_object_mut)
+    else {
+        return Ok(());
+    };
+    let damage_type = attack.remove("damage_type").unwrap_or(Value::Null);
+    match attack.get_mut("damage") {
+        Some(damage) if damage.is_object() => {
+            let damage_type = match damage_type {
+                Value::Null => json!(crate::DamageType::default()),
+                x => x,
+            };
+            *damage = json!([{
+                "damage": damage.take(),
+                "damage_type": damage_type,
+                "condition": null,
+            }]);
+            Ok(())
+        }
+        Some(Value::Array(_)) => Ok(()),
+        _ => Err(malformed(
+            "action.damage",
+            "expected the damage of an attack",
+        )),
+    }
+}
+
+fn creature_v2(creature: &mut Value) -> Result<(), MigrationError> {
+    let fields = creature
+        .as_object_mut()
+        .ok_or_else(|| malformed("creature", "expected an object"))?;
+    fields
+        .entry("size")
+        .or_insert_with(|| json!(crate::creature::Size::default()));
+    each(creature, "actions", attack_damage_v2)
+}
+
+fn item_v2(item: &mut Value) -> Result<(), MigrationError> {
+    each(item, "actions", attack_damage_v2)?;
+    each(item, "inventory", item_v2)
+}
+
+fn player_v2(player: &mut Value) -> Result<(), MigrationError> {
+    let creature = player
+        .get_mut("creature")
+        .ok_or_else(|| malformed("player.creature", "missing"))?;
+    creature_v2(creature)?;
+    each(player, "inventory", item_v2)?;
+    match player.get_mut("campaign") {
+        Some(campaign) => campaign_v2(campaign),
+        None => Ok(()),
+    }
+}
+
+fn campaign_v2(campaign: &mut Value) -> Result<(), MigrationError> {
+    each(campaign, "players", player_v2)
+}
+
+/// Turns the plain-text plan and recap of a session into documents with that text as summary.
+fn session_v2(session: &mut Value) -> Result<(), MigrationError> {
+    let fields = session
+        .as_object_mut()
+        .ok_or_else(|| malformed("session", "expected an object"))?;
+    for key in ["plan", "recap"] {
+        let summary = match fields.get(key) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(x)) => x.clone(),
+            Some(_) => return Err(malformed(&format!("session.{}", key), "expected text")),
+        };
+        let value = match key {
+            "plan" => serde_json::to_value(Plan::new(summary))?,
+            _ => serde_json::to_value(Recap::new(summary))?,
+        };
+        fields.insert(key.to_string(), value);
+    }
+    Ok(())
+}
+
+/// Gives a session an empty schedule and attendance.
+fn session_v3(session: &mut Value) -> Result<(), MigrationError> {
+    let fields = session
+        .as_object_mut()
+        .ok_or_else(|| malformed("session", "expected an object"))?;
+    fields.insert(
+        String::from("schedule"),
+        serde_json::to_value(Schedule::default())?,
+    );
+    fields.insert(String::from("attendance"), json!({}));
+    Ok(())
+}
+
+/// The session an entry adds, if it adds one.
+fn added_session(entry: &mut Value) -> Option<&mut Value> {
+    entry.pointer_mut("/event/SessionAdded")
+}
+
+fn entry_v2(entry: &mut Value) -> Result<(), MigrationError> {
+    match added_session(entry) {
+        Some(session) if !session.get("plan").is_some_and(Value::is_object) => session_v2(session),
+        _ => Ok(()),
+    }
+}
+
+fn entry_v3(entry: &mut Value) -> Result<(), MigrationError> {
+    match added_session(entry) {
+        Some(session) if session.get("schedule").is_none() => session_v3(session),
+        _ => Ok(()),
+    }
+}
+
+fn entry_v4(entry: &mut Value) -> Result<(), MigrationError> {
+    match added_session(entry) {
+        Some(session) => session_v4(session),
+        None => Ok(()),
+    }
+}
+
+/// Replaces the embedded campaign of a player with its id.
+fn player_v3(player: &mut Value) -> Result<(), MigrationError> {
+    let fields = player
+        .as_object_mut()
+        .ok_or_else(|| malformed("player", "expected an object"))?;
+    let campaign = fields
+        .remove("campaign")
+        .ok_or_else(|| malformed("player.campaign", "missing"))?;
+    let id = campaign
+        .get("id")
+        .cloned()
+        .ok_or_else(|| malformed("player.campaign.id", "missing"))?;
+    fields.insert(String::from("campaign_id"), id);
+    Ok(())
+}
+
+/// Replaces the embedded players of a campaign with their ids.
+fn campaign_v3(campaign: &mut Value) -> Result<(), MigrationError> {
+    let fields = campaign
+        .as_object_mut()
+        .ok_or_else(|| malformed("campaign", "expected an object"))?;
+    let player_ids = match fields.remove("players") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(players)) => players
+            .iter()
+            .map(|x| {
+                x.pointer("/creature/id")
+                    .cloned()
+                    .ok_or_else(|| malformed("campaign.players.creature.id", "missing"))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(malformed("campaign.players", "expected a list")),
+    };
+    fields.insert(String::from("player_ids"), Value::Array(player_ids));
+    Ok(())
+}
+
+/// Replaces an id stored before ids were UUIDs with the UUID derived from it.
+fn uuid(id: &mut Value) -> Result<(), MigrationError> {
+    match id {
+        Value::Null => Ok(()),
+        Value::String(x) => {
+            *x = legacy_uuid(x).to_string();
+            Ok(())
+        }
+        _ => Err(malformed("id", "expected text")),
+    }
+}
+
+/// Runs [`uuid`] on the ids at each JSON Pointer, when they are there.
+fn uuids(value: &mut Value, pointers: &[&str]) -> Result<(), MigrationError> {
+    for pointer in pointers {
+        if let Some(id) = value.pointer_mut(pointer) {
+            uuid(id)?;
+        }
+    }
+    Ok(())
+}
+
+fn creature_v4(creature: &mut Value) -> Result<(), MigrationError> {
+    uuids(creature, &["/id"])
+}
+
+fn player_v4(player: &mut Value) -> Result<(), MigrationError> {
+    uuids(player, &["/creature/id", "/campaign_id"])
+}
+
+fn campaign_v4(campaign: &mut Value) -> Result<(), MigrationError> {
+    uuids(campaign, &["/id", "/world_id"])?;
+    each(campaign, "player_ids", uuid)
+}
+
+fn map_v4(map: &mut Value) -> Result<(), MigrationError> {
+    uuids(map, &["/id", "/world_id"])?;
+    each(map, "locations", location_v4)
+}
+
+fn location_v4(location: &mut Value) -> Result<(), MigrationError> {
+    uuids(location, &["/id", "/world_id", "/map_coordinates/map_id"])?;
+    each(location, "npcs", creature_v4)
+}
+
+fn world_v4(world: &mut Value) -> Result<(), MigrationError> {
+    uuids(world, &["/id"])
+}
+
+fn session_v4(session: &mut Value) -> Result<(), MigrationError> {
+    uuids(session, &["/id", "/campaign_id"])?;
+    each(session, "notes", |note| uuids(note, &["/id"]))?;
+    if let Some(plan) = session.get_mut("plan") {
+        each(plan, "locations", uuid)?;
+    }
+    if let Some(recap) = session.get_mut("recap") {
+        for key in ["loot", "experience"] {
+            each(recap, key, |x| uuids(x, &["/player_id"]))?;
+        }
+    }
+    if let Some(Value::Object(attendance)) = session.get_mut("attendance") {
+        *attendance = core::mem::take(attendance)
+            .into_iter()
+            .map(|(id, x)| (legacy_uuid(&id).to_string(), x))
+            .collect();
+    }
+    Ok(())
+}
+
+fn map_v2(map: &mut Value) -> Result<(), MigrationError> {
+    each(map, "locations", location_v2)
+}
+
+fn location_v2(location: &mut Value) -> Result<(), MigrationError> {
+    each(location, "npcs", creature_v2)
+}
+
+mod test {
+    #[test]
+    fn test_creature_v2() {
+        use crate::{creature::Size, migration::from_json};
+
+        let v1 = r#"{
+            "id": "goblin", "name": "Goblin", "creature_type": {"Monster": "Humanoid"},
+            "alignment": "NeutralEvil", "armor_class": 15,
+            "health_points": {"health": {"die_count": 2, "die_type": "D6", "extra": 0}},
+            "speed": {"Walk": 30}, "stats": [], "saving_throws": null,
+            "damage_resistances": null, "damage_immunities": null,
+            "damage_vulnerabilities": null, "condition_immunities": null, "skills": null,
+            "senses": null, "languages": null, "challenge_rating": "1/4",
+            "racial_traits": null, "description": null, "lair": null, "others": null,
+            "actions": [{"id": "scimitar", "action": {"Attack": {"MeleeWeaponAttack": {
+                "name": "Scimitar", "modifier": 4, "reach": 5, "target_type": "OneTarget",
+                "damage": {"die_count": 1, "die_type": "D6", "extra": 2},
+                "damage_type": "Slashing", "description": ""
+            }}}}]
+        }"#;
+        let creature: crate::creature::Creature = from_json(v1).unwrap();
+        assert_eq!(creature.size(), &Size::Medium);
+        assert_eq!(creature.id(), &crate::id::CreatureId::from_legacy("goblin"));
+        let damage = creature.actions().unwrap()[0].action.to_string();
+        assert!(damage.contains("Hit: 1d6 + 2 Slashing."), "{}", damage);
+
+        let migrated = super::Envelope::from_value::<crate::creature::Creature>(
+            serde_json::from_str(v1).unwrap(),
+        )
+        .unwrap()
+        .migrate::<crate::creature::Creature>()
+        .unwrap();
+        assert_eq!(migrated.version, 4);
+        let attack = &migrated.data["actions"][0]["action"]["Attack"]["MeleeWeaponAttack"];
+        assert_eq!(attack["damage"][0]["damage_type"], "Slashing");
+        assert!(attack.get("damage_type").is_none());
+
+        let stored = super::to_json(&creature).unwrap();
+        assert!(stored.starts_with(r#"{"kind":"creature","version":4,"#));
+        let again: crate::creature::Creature = from_json(&stored).unwrap();
+        assert_eq!(again.name(), "Goblin");
+    }
+
+    #[test]
+    fn test_item_v2() {
+        let v1 = r#"{
+            "id": "sword", "name": "Flame Tongue", "item_type": "Weapon", "rarity": "Rare",
+            "attunement": {"alignments": null}, "weapon_type": "Sword", "armor_type": null,
+            "conditions": null, "attached_spell": null, "has_charges": null, "others": null,
+            "actions": [{"id": "a", "action": {"Attack": {"MeleeWeaponAttack": {
+                "name": "Flame Tongue", "modifier": 5, "reach": 5, "target_type": "OneTarget",
+                "damage": {"die_count": 2, "die_type": "D6", "extra": 0},
+                "damage_type": "Fire", "description": ""
+            }}}}],
+            "inventory": [{
+                "id": "inner", "name": "Inner", "item_type": "Ring", "rarity": "Common",
+                "attunement": null, "weapon_type": null, "armor_type": null,
+                "conditions": null, "attached_spell": null, "has_charges": null,
+                "others": null, "inventory": null,
+                "actions": [{"id": "b", "action": {"Attack": {"RangedSpellAttack": {
+                    "name": "Ray", "modifier": 3, "range": null, "target_type": "OneTarget",
+                    "damage": {"die_count": 1, "die_type": "D10", "extra": 0},
+                    "damage_type": null, "description": ""
+                }}}}]
+            }]
+        }"#;
+        let envelope = format!(r#"{{"kind": "item", "version": 1, "data": {}}}"#, v1);
+        let item: crate::item::Item = super::from_json(&envelope).unwrap();
+        let damage = |item: &crate::item::Item| match &item.actions.as_ref().unwrap()[0].action {
+            crate::action::ActionType::Attack(x) => x.damage()[0].to_string(),
+            _ => String::new(),
+        };
+        assert_eq!(damage(&item), "2d6 + 0 Fire");
+        assert_eq!(
+            damage(&item.inventory.as_ref().unwrap()[0]),
+            "1d10 + 0 Slashing"
+        );
+    }
+
+    #[test]
+    fn test_spell_and_session_v1() {
+        use crate::world::campaign::session::Session;
+
+        let session = Session::new(
+            String::from("Session 1"),
+            String::new(),
+            crate::id::CampaignId::new(),
+        );
+        let stored = super::to_json(&session).unwrap();
+        assert!(stored.starts_with(r#"{"kind":"session","version":4,"#));
+        let loaded: Session = super::from_json(&stored).unwrap();
+        assert_eq!(loaded.id, session.id);
+        let old = r#"{
+            "id": "session-1", "name": "Session 1", "description": "", "campaign_id": "c",
+            "notes": [{"id": "n", "name": "Loot", "description": ""}],
+            "plan": "Ambush at the ravine", "recap": ""
+        }"#;
+        let loaded: Session = super::from_json(old).unwrap();
+        assert_eq!(loaded.campaign_id, crate::id::CampaignId::from_legacy("c"));
+        assert_eq!(loaded.plan.summary, "Ambush at the ravine");
+        assert!(loaded.plan.scenes.is_empty() && loaded.recap.summary.is_empty());
+        assert!(loaded.schedule.planned.is_none() && loaded.attendance.is_empty());
+
+        let spell = r#"{
+            "id": "light", "name": "Light", "description": "", "level": "Cantrip",
+            "casting_time": "Action", "duration": "Hour", "damage": null, "range": "Touch",
+            "area": null, "damage_type": null, "components": "VM", "attack_bonus": null,
+            "save": null
+        }"#;
+        let loaded: crate::spell::Spell = super::from_json(spell).unwrap();
+        assert_eq!(loaded.name, "Light");
+        assert!(matches!(
+            super::from_json::<crate::spell::Spell>(
+                r#"{"kind": "spell", "version": 2, "data": {}}"#
+            ),
+            Err(super::MigrationError::UnsupportedVersion { version: 2, .. })
+        ));
+        assert!(matches!(
+            super::from_json::<crate::spell::Spell>(&stored),
+            Err(super::MigrationError::WrongKind { .. })
+        ));
+    }
+
+    #[test]
+    fn test_history_entry_v1() {
+        use crate::{
+            id::WorldId,
+            world::campaign::{
+                history::{Entry, History},
+                session::Session,
+                Campaign,
+            },
+        };
+
+        // A log stored before sessions had structured plans, schedules or attendance.
+        let campaign = Campaign::new(String::from("Curse"), String::new(), WorldId::new());
+        let created = serde_json::json!({
+            "author": "dm", "timestamp": 1, "event": {"Created": campaign},
+        });
+        let mut session = serde_json::to_value(Session::new(
+            String::from("Session 1"),
+            String::new(),
+            campaign.id,
+        ))
+        .unwrap();
+        let fields = session.as_object_mut().unwrap();
+        fields.insert(String::from("plan"), serde_json::json!("Ambush"));
+        fields.insert(String::from("recap"), serde_json::json!(""));
+        fields.remove("schedule");
+        fields.remove("attendance");
+        let added = serde_json::json!({
+            "author": "dm", "timestamp": 2, "event": {"SessionAdded": session},
+        });
+
+        let entries = [created, added]
+            .iter()
+            .map(|x| super::from_json::<Entry>(&x.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let history = History::from_entries(entries).unwrap();
+        let session = history.state().sessions.values().next().unwrap();
+        assert_eq!(session.plan.summary, "Ambush");
+        assert!(session.attendance.is_empty());
+
+        // Entries without an envelope that already have the current shape are left alone.
+        let bare = serde_json::to_value(&history.entries()[1]).unwrap();
+        let entry: Entry = super::from_json(&bare.to_string()).unwrap();
+        assert_eq!(serde_json::to_value(&entry).unwrap(), bare);
+    }
+
+    #[test]
+    fn test_player_campaign_and_map_v2() {
+        use crate::{
+            id::{CampaignId, CreatureId, WorldId},
+            world::{
+                campaign::{player::Player, Campaign},
+                map::Map,
+            },
+        };
+
+        let creature = r#"{
+            "id": "tessa", "name": "Tessa", "creature_type": "Player",
+            "alignment": "ChaoticGood", "armor_class": 12,
+            "health_points": {"health": {"die_count": 5, "die_type": "D8", "extra": 5}},
+            "speed": {"Walk": 30}, "stats": [], "saving_throws": null,
+            "damage_resistances": null, "damage_immunities": null,
+            "damage_vulnerabilities": null, "condition_immunities": null, "skills": null,
+            "senses": null, "languages": null, "challenge_rating": "",
+            "racial_traits": null, "description": null, "lair": null, "others": null,
+            "actions": [{"id": "staff", "action": {"Attack": {"MeleeWeaponAttack": {
+                "name": "Quarterstaff", "modifier": 1, "reach": 5, "target_type": "OneTarget",
+                "damage": {"die_count": 1, "die_type": "D6", "extra": -1},
+                "damage_type": "Bludgeoning", "description": ""
+            }}}}]
+        }"#;
+        let player = format!(
+            r#"{{"creature": {}, "race": "Elf", "class": "Wizard", "level": 5,
+                "experience": 6500, "inventory": [], "spells": [],
+                "campaign": {{"id": "c", "name": "C", "description": "", "world_id": "w",
+                    "players": null}}}}"#,
+            creature
+        );
+        let loaded: Player = super::from_json(&player).unwrap();
+        assert_eq!(loaded.creature().size(), &crate::creature::Size::Medium);
+        assert_eq!(loaded.campaign_id(), &CampaignId::from_legacy("c"));
+
+        let campaign = format!(
+            r#"{{"id": "c", "name": "C", "description": "", "world_id": "w",
+                "players": [{}]}}"#,
+            player
+        );
+        let migrated =
+            super::Envelope::from_value::<Campaign>(serde_json::from_str(&campaign).unwrap())
+                .unwrap()
+                .migrate::<Campaign>()
+                .unwrap();
+        assert_eq!(
+            migrated.data["player_ids"],
+            serde_json::json!([CreatureId::from_legacy("tessa")])
+        );
+        let (loaded, players) = super::split_campaign(&campaign).unwrap();
+        assert_eq!(loaded.id, CampaignId::from_legacy("c"));
+        assert_eq!(loaded.player_ids, vec![*players[0].id()]);
+        assert_eq!(players[0].campaign_id(), &loaded.id);
+        assert_eq!(players[0].creature().size(), &crate::creature::Size::Medium);
+
+        let map = format!(
+            r#"{{"id": "m", "name": "M", "description": "", "world_id": "w", "width": 1,
+                "height": 1, "map": [[""]], "locations": [{{"id": "l", "name": "Tavern",
+                "description": "", "world_id": "w",
+                "map_coordinates": {{"map_id": "m", "x": 0, "y": 0}},
+                "npcs": [{}]}}]}}"#,
+            creature
+        );
+        let loaded: Map = super::from_json(&map).unwrap();
+        assert_eq!(loaded.locations[0].npcs[0].name(), "Tessa");
+        assert_eq!(loaded.locations[0].map_coordinates.map_id, loaded.id);
+        assert_eq!(loaded.world_id, WorldId::from_legacy("w"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Text renderers for the crate's types, shared between the output formats.
+
+use crate::{
+    action::{
+        attack::{Attack, Damage, Range, TargetType},
+        ActionType,
+    },
+    creature::{Creature, CreatureType, MovementSpeed, Skill, Stat},
+    DieStat,
+};
+
+pub mod html;
+pub mod markdown;
+
+/// Formats dice the way stat blocks print them: `2d6`, `1d8 + 3`, `1d4 - 1` or a flat `5`.
+pub fn dice(die: &DieStat) -> String {
+    match (die.die_count, die.extra) {
+        (0, extra) => extra.to_string(),
+        (count, 0) => format!("{}{}", count, die.die_type),
+        (count, extra) if extra < 0 => format!("{}{} - {}", count, die.die_type, -extra),
+        (count, extra) => format!("{}{} + {}", count, die.die_type, extra),
+    }
+}
+
+/// Formats dice with their average first, e.g. `7 (2d6)`.
+pub fn dice_with_average(die: &DieStat) -> String {
+    match die.die_count {
+        0 => die.extra.to_string(),
+        _ => format!("{} ({})", die.value(), dice(die)),
+    }
+}
+
+pub fn modifier(value: i32) -> String {
+    format!("{:+}", value)
+}
+
+pub fn ability_score(stat: &Stat) -> String {
+    format!("{} ({})", stat.value, modifier(stat.modifier))
+}
+
+pub fn speed(speed: &MovementSpeed) -> String {
+    match &speed {
+        MovementSpeed::Walk(x) => format!("{} ft.", x),
+        MovementSpeed::Burrow(x) => format!("burrow {} ft.", x),
+        MovementSpeed::Swim(x) => format!("swim {} ft.", x),
+        MovementSpeed::Climb(x) => format!("climb {} ft.", x),
+        MovementSpeed::Fly { speed, hover } => {
+            format!("fly {} ft.{}", speed, if *hover { " (hover)" } else { "" })
+        }
+    }
+}
+
+/// Every speed of a creature, including the extra ones kept in its other attributes.
+pub fn speeds(creature: &Creature) -> String {
+    std::iter::once(speed(creature.speed()))
+        .chain(
+            creature
+                .others()
+                .into_iter()
+                .flatten()
+                .filter(|x| x.title == "Speed")
+                .map(|x| x.value.clone()),
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn saving_throw(stat: &Stat) -> String {
+    let name = stat.stat_type.abbreviation();
+    format!(
+        "{}{} {}",
+        &name[..1],
+        name[1..].to_lowercase(),
+        modifier(stat.modifier)
+    )
+}
+
+pub fn skill(skill: &Skill) -> String {
+    let name = skill.skill_type.to_string();
+    let name = name.split(" (").next().unwrap_or_default();
+    format!("{} {}", name, modifier(skill.modifier))
+}
+
+pub fn lowercase_list<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|x| x.to_string().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn list<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn senses(creature: &Creature) -> String {
+    creature
+        .senses()
+        .into_iter()
+        .flatten()
+        .map(|x| x.to_string().to_lowercase())
+        .chain(std::iter::once(format!(
+            "passive Perception {}",
+            creature.passive_perception()
+        )))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn challenge(creature: &Creature) -> String {
+    match creature.experience_points() {
+        Some(xp) => format!("{} ({} XP)", creature.challenge_rating(), thousands(xp)),
+        None => creature.challenge_rating().to_string(),
+    }
+}
+
+fn thousands(value: u32) -> String {
+    let digits = value.to_string();
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// The pieces of an attack as a stat block lays them out.
+pub struct AttackText {
+    pub name: String,
+    pub kind: &'static str,
+    pub details: String,
+    pub hit: String,
+    pub description: String,
+}
+
+pub fn attack_text(attack: &Attack) -> AttackText {
+    let (kind, name, modifier_value, reach, range, target_type, description) = match &attack {
+        Attack::MeleeWeaponAttack(x) | Attack::MeleeSpellAttack(x) => (
+            if matches!(attack, Attack::MeleeSpellAttack(_)) {
+                "Melee Spell Attack"
+            } else {
+                "Melee Weapon Attack"
+            },
+            &x.name,
+            x.modifier,
+            Some(x.reach.unwrap_or(5)),
+            None,
+            &x.target_type,
+            &x.description,
+        ),
+        Attack::RangedWeaponAttack(x) | Attack::RangedSpellAttack(x) => (
+            if matches!(attack, Attack::RangedSpellAttack(_)) {
+                "Ranged Spell Attack"
+            } else {
+                "Ranged Weapon Attack"
+            },
+            &x.name,
+            x.modifier,
+            None,
+            x.range.as_ref(),
+            &x.target_type,
+            &x.description,
+        ),
+        Attack::MeleeOrRangedWeaponAttack(x) => (
+            "Melee or Ranged Weapon Attack",
+            &x.name,
+            x.modifier,
+            Some(x.reach.unwrap_or(5)),
+            x.range.as_ref(),
+            &x.target_type,
+            &x.description,
+        ),
+    };
+    let distance = match (reach, range) {
+        (Some(reach), Some(range)) => format!("reach {} ft. or range {}", reach, range_text(range)),
+        (Some(reach), None) => format!("reach {} ft.", reach),
+        (None, Some(range)) => format!("range {}", range_text(range)),
+        (None, None) => String::new(),
+    };
+    AttackText {
+        name: name.clone(),
+        kind,
+        details: format!(
+            "{} to hit, {}, {}.",
+            modifier(modifier_value),
+            distance,
+            target(target_type)
+        ),
+        hit: attack
+            .damage()
+            .iter()
+            .map(damage)
+            .collect::<Vec<_>>()
+            .join(" plus "),
+        description: description.clone(),
+    }
+}
+
+fn range_text(range: &Range) -> String {
+    if range.close_range == range.long_range {
+        format!("{} ft.", range.close_range)
+    } else {
+        format!("{}/{} ft.", range.close_range, range.long_range)
+    }
+}
+
+fn target(target_type: &TargetType) -> String {
+    match &target_type {
+        TargetType::OneTarget => String::from("one target"),
+        TargetType::MultipleTargets(x) => format!("{} targets", x),
+        TargetType::Cone(x) => format!("{} ft. cone", x),
+        TargetType::Line(x) => format!("{} ft. line", x),
+        TargetType::Cube(x) => format!("{} ft. cube", x),
+        TargetType::Sphere(x) => format!("{} ft. sphere", x),
+    }
+}
+
+pub fn damage(damage: &Damage) -> String {
+    let mut text = format!(
+        "{} {} damage",
+        dice_with_average(&damage.damage),
+        damage.damage_type.to_string().to_lowercase()
+    );
+    if let Some(condition) = &damage.condition {
+        text.push(' ');
+        text.push_str(condition);
+    }
+    text
+}
+
+/// Splits a creature's actions into the stat block sections they are printed under.
+pub struct ActionSections<'a> {
+    pub actions: Vec<&'a ActionType>,
+    pub reactions: Vec<&'a ActionType>,
+    pub legendary_actions: Vec<&'a ActionType>,
+}
+
+pub fn action_sections(creature: &Creature) -> ActionSections<'_> {
+    let mut sections = ActionSections {
+        actions: Vec::new(),
+        reactions: Vec::new(),
+        legendary_actions: Vec::new(),
+    };
+    for action in creature.actions().into_iter().flatten() {
+        match &action.action {
+            ActionType::Attack(_) | ActionType::Special(_) => sections.actions.push(&action.action),
+            ActionType::Reaction(_) => sections.reactions.push(&action.action),
+            ActionType::LegendaryAction(_) => sections.legendary_actions.push(&action.action),
+        }
+    }
+    sections
+}
+
+/// The introduction printed above legendary actions, taken from the creature's other attributes
+/// when it has one.
+pub fn legendary_intro(creature: &Creature) -> String {
+    creature
+        .others()
+        .into_iter()
+        .flatten()
+        .find(|x| x.title == "Legendary Actions")
+        .map(|x| x.description.clone())
+        .unwrap_or_else(|| {
+            let name = creature.name().to_lowercase();
+            format!(
+                "The {name} can take 3 legendary actions, choosing from the options below. Only one legendary action option can be used at a time and only at the end of another creature's turn. The {name} regains spent legendary actions at the start of its turn."
+            )
+        })
+}
+
+/// Other attributes that aren't already printed as part of another line.
+pub fn extra_attributes(creature: &Creature) -> Vec<&crate::OtherAttribute> {
+    creature
+        .others()
+        .into_iter()
+        .flatten()
+        .filter(|x| x.title != "Speed" && x.title != "Legendary Actions")
+        .collect()
+}
+
+/// The `Small humanoid, neutral evil` line under a creature's name.
+pub fn type_line(creature: &Creature) -> String {
+    let kind = match creature.creature_type() {
+        CreatureType::Monster(x) => x.to_string().to_lowercase(),
+        CreatureType::Player | CreatureType::NPC => String::from("humanoid"),
+    };
+    format!("{} {}, {}", creature.size(), kind, creature.alignment())
+}
@@ -0,0 +1,601 @@
+//! Self-contained HTML fragments for creatures, items and spells.
+//!
+//! Every fragment is produced from the templates of a [`Theme`], so the markup can be changed
+//! without touching the renderer. Text coming from the rendered values is always escaped; only
+//! fragments produced by the renderer itself are inserted as raw HTML.
+
+use crate::{
+    action::ActionType,
+    creature::{Creature, Paragraph},
+    item::Item,
+    spell::Spell,
+};
+
+use super::{
+    ability_score, action_sections, attack_text, challenge, dice, dice_with_average,
+    extra_attributes, legendary_intro, list, lowercase_list, saving_throw, senses, skill, speeds,
+    type_line,
+};
+
+/// Escapes text for use in HTML element content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A fragment of markup with `{{name}}` placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(String);
+
+impl Template {
+    pub fn new(template: &str) -> Self {
+        Self(template.to_string())
+    }
+
+    /// Fills in the placeholders; placeholders without a value are left empty.
+    pub fn render(&self, fields: &Fields) -> String {
+        let mut output = String::new();
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            match rest[start..].find("}}") {
+                Some(end) => {
+                    let name = rest[start + 2..start + end].trim();
+                    if let Some(value) = fields.get(name) {
+                        output.push_str(value);
+                    }
+                    rest = &rest[start + end + 2..];
+                }
+                None => {
+                    output.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Values for a [`Template`], kept in insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct Fields(Vec<(&'static str, String)>);
+
+impl Fields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds plain text, which is escaped.
+    pub fn text(mut self, name: &'static str, value: &str) -> Self {
+        self.0.push((name, escape(value)));
+        self
+    }
+
+    /// Adds markup that was already rendered and must not be escaped again.
+    pub fn html(mut self, name: &'static str, value: String) -> Self {
+        self.0.push((name, value));
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&String> {
+        self.0.iter().find(|(x, _)| *x == name).map(|(_, x)| x)
+    }
+}
+
+/// The templates the renderer is driven by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// `{{name}}`, `{{subtitle}}`, `{{properties}}`, `{{abilities}}`, `{{details}}`, `{{sections}}`, `{{description}}`
+    pub creature: Template,
+    /// `{{name}}`, `{{subtitle}}`, `{{properties}}`, `{{sections}}`
+    pub item: Template,
+    /// `{{name}}`, `{{subtitle}}`, `{{properties}}`, `{{description}}`
+    pub spell: Template,
+    /// `{{label}}`, `{{value}}`
+    pub property: Template,
+    /// `{{label}}`, `{{value}}`
+    pub ability: Template,
+    /// `{{title}}`, `{{body}}`
+    pub section: Template,
+    /// `{{name}}`, `{{body}}`
+    pub entry: Template,
+    /// `{{text}}`
+    pub paragraph: Template,
+    /// `{{items}}`
+    pub list: Template,
+    /// `{{text}}`
+    pub list_item: Template,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            creature: Template::new(
+                "<div class=\"gm-stat-block\">\n<h2 class=\"gm-stat-block__name\">{{name}}</h2>\n<p class=\"gm-stat-block__subtitle\">{{subtitle}}</p>\n<dl class=\"gm-stat-block__properties\">\n{{properties}}</dl>\n<dl class=\"gm-stat-block__abilities\">\n{{abilities}}</dl>\n<dl class=\"gm-stat-block__details\">\n{{details}}</dl>\n{{sections}}{{description}}</div>\n",
+            ),
+            item: Template::new(
+                "<div class=\"gm-item-card\">\n<h2 class=\"gm-item-card__name\">{{name}}</h2>\n<p class=\"gm-item-card__subtitle\">{{subtitle}}</p>\n<dl class=\"gm-item-card__properties\">\n{{properties}}</dl>\n{{sections}}</div>\n",
+            ),
+            spell: Template::new(
+                "<div class=\"gm-spell-card\">\n<h2 class=\"gm-spell-card__name\">{{name}}</h2>\n<p class=\"gm-spell-card__subtitle\">{{subtitle}}</p>\n<dl class=\"gm-spell-card__properties\">\n{{properties}}</dl>\n<div class=\"gm-spell-card__description\">{{description}}</div>\n</div>\n",
+            ),
+            property: Template::new(
+                "<div class=\"gm-property\"><dt class=\"gm-property__label\">{{label}}</dt><dd class=\"gm-property__value\">{{value}}</dd></div>\n",
+            ),
+            ability: Template::new(
+                "<div class=\"gm-ability\"><dt class=\"gm-ability__label\">{{label}}</dt><dd class=\"gm-ability__value\">{{value}}</dd></div>\n",
+            ),
+            section: Template::new(
+                "<section class=\"gm-section\">\n<h3 class=\"gm-section__title\">{{title}}</h3>\n{{body}}</section>\n",
+            ),
+            entry: Template::new(
+                "<p class=\"gm-entry\"><strong class=\"gm-entry__name\">{{name}}.</strong> {{body}}</p>\n",
+            ),
+            paragraph: Template::new("<p class=\"gm-paragraph\">{{text}}</p>\n"),
+            list: Template::new("<ul class=\"gm-list\">\n{{items}}</ul>\n"),
+            list_item: Template::new("<li class=\"gm-list__item\">{{text}}</li>\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlRenderer {
+    theme: Theme,
+}
+
+impl HtmlRenderer {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn creature(&self, creature: &Creature) -> String {
+        let mut properties = vec![
+            ("Armor Class", creature.armor_class().to_string()),
+            (
+                "Hit Points",
+                dice_with_average(&creature.health_points().health),
+            ),
+            ("Speed", speeds(creature)),
+        ];
+        let abilities: String = creature
+            .stats()
+            .iter()
+            .map(|x| {
+                self.theme.ability.render(
+                    &Fields::new()
+                        .text("label", x.stat_type.abbreviation())
+                        .text("value", &ability_score(x)),
+                )
+            })
+            .collect();
+
+        let mut details = Vec::new();
+        if let Some(saves) = creature.saving_throws().filter(|x| !x.is_empty()) {
+            let saves: Vec<String> = saves.iter().map(saving_throw).collect();
+            details.push(("Saving Throws", saves.join(", ")));
+        }
+        if let Some(skills) = creature.skills().filter(|x| !x.is_empty()) {
+            let skills: Vec<String> = skills.iter().map(skill).collect();
+            details.push(("Skills", skills.join(", ")));
+        }
+        for (title, values) in [
+            ("Damage Vulnerabilities", creature.damage_vulnerabilities()),
+            ("Damage Resistances", creature.damage_resistances()),
+            ("Damage Immunities", creature.damage_immunities()),
+        ] {
+            if let Some(values) = values.filter(|x| !x.is_empty()) {
+                details.push((title, lowercase_list(values)));
+            }
+        }
+        if let Some(conditions) = creature.condition_immunities().filter(|x| !x.is_empty()) {
+            details.push(("Condition Immunities", lowercase_list(conditions)));
+        }
+        details.push(("Senses", senses(creature)));
+        details.push((
+            "Languages",
+            match creature.languages().filter(|x| !x.is_empty()) {
+                Some(languages) => list(languages),
+                None => String::from("—"),
+            },
+        ));
+        details.push(("Challenge", challenge(creature)));
+        if let Some(bonus) = creature.proficiency_bonus() {
+            details.push(("Proficiency Bonus", format!("{:+}", bonus)));
+        }
+        let others = extra_attributes(creature);
+        for other in &others {
+            properties.push((other.title.as_str(), other.value.clone()));
+        }
+
+        let mut sections = String::new();
+        if let Some(traits) = creature.racial_traits().filter(|x| !x.is_empty()) {
+            let body: String = traits
+                .iter()
+                .map(|x| self.entry(&x.name, &x.description))
+                .collect();
+            sections.push_str(&self.section("Traits", body));
+        }
+        let actions = action_sections(creature);
+        for (title, entries) in [
+            ("Actions", &actions.actions),
+            ("Reactions", &actions.reactions),
+        ] {
+            if !entries.is_empty() {
+                let body: String = entries.iter().map(|x| self.action(x)).collect();
+                sections.push_str(&self.section(title, body));
+            }
+        }
+        if !actions.legendary_actions.is_empty() {
+            let mut body = self.paragraph(&legendary_intro(creature));
+            body.extend(actions.legendary_actions.iter().map(|x| self.action(x)));
+            sections.push_str(&self.section("Legendary Actions", body));
+        }
+        if let Some(lair) = creature.lair() {
+            let mut body = String::new();
+            if !lair.description.is_empty() {
+                body.push_str(&self.paragraph(&lair.description));
+            }
+            body.push_str(&self.paragraphs(&lair.lair_actions));
+            sections.push_str(&self.section("Lair Actions", body));
+            if !lair.regional_effects.is_empty() {
+                let body = self.paragraphs(&lair.regional_effects);
+                sections.push_str(&self.section("Regional Effects", body));
+            }
+        }
+
+        self.theme.creature.render(
+            &Fields::new()
+                .text("name", creature.name())
+                .text("subtitle", &type_line(creature))
+                .html("properties", self.properties(&properties))
+                .html("abilities", abilities)
+                .html("details", self.properties(&details))
+                .html("sections", sections)
+                .html(
+                    "description",
+                    creature
+                        .description()
+                        .map(|x| self.paragraph(&x))
+                        .unwrap_or_default(),
+                ),
+        )
+    }
+
+    pub fn item(&self, item: &Item) -> String {
+        let mut subtitle = item.item_type.to_string();
+        if let Some(weapon_type) = &item.weapon_type {
+            subtitle.push_str(&format!(" ({})", weapon_type.to_string().to_lowercase()));
+        }
+        if let Some(armor_type) = &item.armor_type {
+            subtitle.push_str(&format!(" ({})", armor_type.to_string().to_lowercase()));
+        }
+        subtitle.push_str(&format!(", {}", item.rarity.to_string().to_lowercase()));
+        if let Some(attunement) = &item.attunement {
+            subtitle.push_str(&format!(" {}", attunement));
+        }
+
+        let mut properties = Vec::new();
+        if let Some(charges) = &item.has_charges {
+            properties.push((
+                "Charges",
+                format!(
+                    "{} per {}",
+                    charges.num,
+                    charges.time.to_string().to_lowercase()
+                ),
+            ));
+        }
+        if let Some(conditions) = item.conditions.as_ref().filter(|x| !x.is_empty()) {
+            properties.push(("Conditions", lowercase_list(conditions)));
+        }
+        if let Some(spell) = &item.attached_spell {
+            properties.push(("Spell", spell.name.clone()));
+        }
+        if let Some(inventory) = item.inventory.as_ref().filter(|x| !x.is_empty()) {
+            let names: Vec<&str> = inventory.iter().map(|x| x.name.as_str()).collect();
+            properties.push(("Contents", names.join(", ")));
+        }
+        for other in item.others.iter().flatten() {
+            properties.push((other.title.as_str(), other.value.clone()));
+        }
+
+        let mut sections = String::new();
+        if let Some(actions) = item.actions.as_ref().filter(|x| !x.is_empty()) {
+            let body: String = actions.iter().map(|x| self.action(&x.action)).collect();
+            sections.push_str(&self.section("Actions", body));
+        }
+        let descriptions: String = item
+            .others
+            .iter()
+            .flatten()
+            .filter(|x| !x.description.is_empty())
+            .map(|x| self.entry(&x.title, &x.description))
+            .collect();
+        if !descriptions.is_empty() {
+            sections.push_str(&self.section("Properties", descriptions));
+        }
+
+        self.theme.item.render(
+            &Fields::new()
+                .text("name", &item.name)
+                .text("subtitle", &subtitle)
+                .html("properties", self.properties(&properties))
+                .html("sections", sections),
+        )
+    }
+
+    pub fn spell(&self, spell: &Spell) -> String {
+        let subtitle = match spell.level {
+            crate::spell::SpellLevel::Cantrip => String::from("Cantrip"),
+            ref level => format!("{} spell", level),
+        };
+        let mut range = spell.range.to_string();
+        if let crate::spell::SpellRange::Range(_) = spell.range {
+            range.push_str(" feet");
+        }
+        if let Some(area) = &spell.area {
+            range.push_str(&format!(" ({})", area));
+        }
+        let mut properties = vec![
+            ("Casting Time", spell.casting_time.to_string()),
+            ("Range", range),
+            ("Components", spell.components.to_string()),
+            ("Duration", spell.duration.to_string()),
+        ];
+        if let Some(damage) = &spell.damage {
+            properties.push((
+                "Damage",
+                match &spell.damage_type {
+                    Some(damage_type) => {
+                        format!(
+                            "{} {}",
+                            dice(damage),
+                            damage_type.to_string().to_lowercase()
+                        )
+                    }
+                    None => dice(damage),
+                },
+            ));
+        }
+        if let Some(bonus) = spell.attack_bonus {
+            properties.push(("Attack Bonus", format!("{:+}", bonus)));
+        }
+        if let Some(save) = &spell.save {
+            properties.push(("Saving Throw", save.to_string()));
+        }
+
+        self.theme.spell.render(
+            &Fields::new()
+                .text("name", &spell.name)
+                .text("subtitle", &subtitle)
+                .html("properties", self.properties(&properties))
+                .html(
+                    "description",
+                    spell
+                        .description
+                        .split("\n\n")
+                        .filter(|x| !x.trim().is_empty())
+                        .map(|x| self.paragraph(x.trim()))
+                        .collect(),
+                ),
+        )
+    }
+
+    fn properties(&self, properties: &[(&str, String)]) -> String {
+        properties
+            .iter()
+            .map(|(label, value)| {
+                self.theme
+                    .property
+                    .render(&Fields::new().text("label", label).text("value", value))
+            })
+            .collect()
+    }
+
+    fn section(&self, title: &str, body: String) -> String {
+        self.theme
+            .section
+            .render(&Fields::new().text("title", title).html("body", body))
+    }
+
+    fn entry(&self, name: &str, body: &str) -> String {
+        self.theme
+            .entry
+            .render(&Fields::new().text("name", name).text("body", body))
+    }
+
+    fn paragraph(&self, text: &str) -> String {
+        self.theme
+            .paragraph
+            .render(&Fields::new().text("text", text))
+    }
+
+    /// Renders paragraphs, grouping consecutive bullets into a single list.
+    fn paragraphs(&self, paragraphs: &[Paragraph]) -> String {
+        let mut output = String::new();
+        let mut items = String::new();
+        for paragraph in paragraphs {
+            if paragraph.bullet {
+                items.push_str(
+                    &self
+                        .theme
+                        .list_item
+                        .render(&Fields::new().text("text", &paragraph.paragraph)),
+                );
+                continue;
+            }
+            if !items.is_empty() {
+                output.push_str(
+                    &self
+                        .theme
+                        .list
+                        .render(&Fields::new().html("items", std::mem::take(&mut items))),
+                );
+            }
+            output.push_str(&self.paragraph(&paragraph.paragraph));
+        }
+        if !items.is_empty() {
+            output.push_str(&self.theme.list.render(&Fields::new().html("items", items)));
+        }
+        output
+    }
+
+    fn action(&self, action: &ActionType) -> String {
+        match &action {
+            ActionType::Attack(attack) => {
+                let attack = attack_text(attack);
+                let mut body = format!("{}: {} Hit: {}.", attack.kind, attack.details, attack.hit);
+                if !attack.description.is_empty() {
+                    body.push(' ');
+                    body.push_str(&attack.description);
+                }
+                self.entry(&attack.name, &body)
+            }
+            ActionType::Special(x) | ActionType::Reaction(x) | ActionType::LegendaryAction(x) => {
+                self.entry(&x.name, &x.description)
+            }
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_template_render() {
+        let template = super::Template::new("<b>{{ name }}</b>{{missing}}{{raw}}");
+        assert_eq!(
+            template.render(
+                &super::Fields::new()
+                    .text("name", "<script>")
+                    .html("raw", String::from("<i>ok</i>"))
+            ),
+            "<b>&lt;script&gt;</b><i>ok</i>"
+        );
+    }
+
+    #[test]
+    fn test_spell_card() {
+        use crate::spell::{CastingTime, Components, Duration, Save, SpellLevel, SpellRange};
+
+        let spell = crate::spell::Spell {
+            id: String::from("fireball"),
+            name: String::from("Fireball"),
+            description: String::from("A bright streak flashes & blossoms."),
+            level: SpellLevel::Level3,
+            casting_time: CastingTime::Action,
+            duration: Duration::Instantaneous,
+            damage: Some(crate::DieStat {
+                die_count: 8,
+                die_type: crate::Die::D6,
+                extra: 0,
+            }),
+            range: SpellRange::Range(150),
+            area: Some(crate::spell::Area::Sphere(20)),
+            damage_type: Some(crate::DamageType::Fire),
+            components: Components::VSM,
+            attack_bonus: None,
+            save: Some(Save::Dexterity(None)),
+        };
+        assert_eq!(
+            super::HtmlRenderer::default().spell(&spell),
+            "<div class=\"gm-spell-card\">
+<h2 class=\"gm-spell-card__name\">Fireball</h2>
+<p class=\"gm-spell-card__subtitle\">3rd Level spell</p>
+<dl class=\"gm-spell-card__properties\">
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Casting Time</dt><dd class=\"gm-property__value\">Action</dd></div>
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Range</dt><dd class=\"gm-property__value\">150 feet (20 ft. Sphere)</dd></div>
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Components</dt><dd class=\"gm-property__value\">V, S, M</dd></div>
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Duration</dt><dd class=\"gm-property__value\">Instantaneous</dd></div>
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Damage</dt><dd class=\"gm-property__value\">8d6 fire</dd></div>
+<div class=\"gm-property\"><dt class=\"gm-property__label\">Saving Throw</dt><dd class=\"gm-property__value\">Dexterity</dd></div>
+</dl>
+<div class=\"gm-spell-card__description\"><p class=\"gm-paragraph\">A bright streak flashes &amp; blossoms.</p>
+</div>
+</div>
+"
+        );
+    }
+
+    #[test]
+    fn test_item_card_and_theme() {
+        use crate::item::{Attuneable, Charge, Item, ItemRarity, ItemType, TimeDivision};
+
+        let item = Item {
+            id: String::from("staff-of-frost"),
+            name: String::from("Staff of <Frost>"),
+            item_type: ItemType::Staff,
+            rarity: ItemRarity::VeryRare,
+            attunement: Some(Attuneable {
+                alignments: Some(vec![crate::Alignment::AnyGood, crate::Alignment::AnyLawful]),
+            }),
+            weapon_type: None,
+            armor_type: None,
+            conditions: None,
+            attached_spell: None,
+            has_charges: Some(Charge {
+                num: 10,
+                time: TimeDivision::Day,
+            }),
+            inventory: None,
+            others: None,
+            actions: None,
+        };
+        let html = super::HtmlRenderer::default().item(&item);
+        assert!(html.starts_with("<div class=\"gm-item-card\">\n<h2 class=\"gm-item-card__name\">Staff of &lt;Frost&gt;</h2>\n"));
+        assert!(html.contains("<p class=\"gm-item-card__subtitle\">Staff, very rare (requires attunement, any good alignment,any lawful alignment)</p>"));
+        assert!(html.contains("<dt class=\"gm-property__label\">Charges</dt><dd class=\"gm-property__value\">10 per day</dd>"));
+
+        let themed = super::HtmlRenderer::new(super::Theme {
+            item: super::Template::new(
+                "<article>{{name}}: {{subtitle}}{{properties}}{{unknown}}</article>",
+            ),
+            ..Default::default()
+        });
+        let item = Item {
+            attunement: None,
+            has_charges: None,
+            ..item
+        };
+        assert_eq!(
+            themed.item(&item),
+            "<article>Staff of &lt;Frost&gt;: Staff, very rare</article>"
+        );
+    }
+
+    #[test]
+    fn test_creature_escapes_and_themes() {
+        let (mut creature, _) = crate::creature::parse::parse_stat_block(
+            "Goblin <Boss>\nSmall humanoid, neutral evil\nArmor Class 17\nHit Points 21 (6d6)\nSpeed 30 ft.\nSTR DEX CON INT WIS CHA\n10 14 10 10 8 10\nChallenge 1\nActions\nMultiattack. The goblin makes two \"attacks\".\n",
+        );
+        creature.set_description(Some(String::from("Leads <b>raids</b>.")));
+        let renderer = super::HtmlRenderer::default();
+        let html = renderer.creature(&creature);
+        assert!(html.contains("<h2 class=\"gm-stat-block__name\">Goblin &lt;Boss&gt;</h2>"));
+        assert!(html.contains("two &quot;attacks&quot;."));
+        assert!(html.contains("Leads &lt;b&gt;raids&lt;/b&gt;."));
+        assert_eq!(html, renderer.creature(&creature));
+
+        let themed = super::HtmlRenderer::new(super::Theme {
+            creature: super::Template::new("<article>{{name}}</article>"),
+            ..Default::default()
+        });
+        assert_eq!(
+            themed.creature(&creature),
+            "<article>Goblin &lt;Boss&gt;</article>"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! Markdown stat blocks in the Homebrewery / GM Binder layout, and session plans and recaps
+//! as plain Markdown documents.
+
+use crate::{
+    action::ActionType,
+    creature::{Creature, Lair, Paragraph},
+    world::campaign::session::{Plan, Recap},
+};
+
+use super::{
+    ability_score, action_sections, attack_text, challenge, dice_with_average, extra_attributes,
+    legendary_intro, list, lowercase_list, saving_throw, senses, skill, speeds, type_line,
+};
+
+/// Renders a complete stat block for `creature`.
+pub fn creature(creature: &Creature) -> String {
+    let mut lines = vec![
+        String::from("___"),
+        format!("> ## {}", creature.name()),
+        format!(">*{}*", type_line(creature)),
+        String::from("> ___"),
+        format!("> - **Armor Class** {}", creature.armor_class()),
+        format!(
+            "> - **Hit Points** {}",
+            dice_with_average(&creature.health_points().health)
+        ),
+        format!("> - **Speed** {}", speeds(creature)),
+        String::from(">___"),
+    ];
+
+    let stats = creature.stats();
+    lines.push(format!(
+        ">|{}|",
+        stats
+            .iter()
+            .map(|x| x.stat_type.abbreviation())
+            .collect::<Vec<_>>()
+            .join("|")
+    ));
+    lines.push(format!(">|{}|", vec![":---:"; stats.len()].join("|")));
+    lines.push(format!(
+        ">|{}|",
+        stats
+            .iter()
+            .map(ability_score)
+            .collect::<Vec<_>>()
+            .join("|")
+    ));
+    lines.push(String::from(">___"));
+
+    if let Some(saves) = creature.saving_throws().filter(|x| !x.is_empty()) {
+        let saves: Vec<String> = saves.iter().map(saving_throw).collect();
+        lines.push(format!("> - **Saving Throws** {}", saves.join(", ")));
+    }
+    if let Some(skills) = creature.skills().filter(|x| !x.is_empty()) {
+        let skills: Vec<String> = skills.iter().map(skill).collect();
+        lines.push(format!("> - **Skills** {}", skills.join(", ")));
+    }
+    for (title, values) in [
+        ("Damage Vulnerabilities", creature.damage_vulnerabilities()),
+        ("Damage Resistances", creature.damage_resistances()),
+        ("Damage Immunities", creature.damage_immunities()),
+    ] {
+        if let Some(values) = values.filter(|x| !x.is_empty()) {
+            lines.push(format!("> - **{}** {}", title, lowercase_list(values)));
+        }
+    }
+    if let Some(conditions) = creature.condition_immunities().filter(|x| !x.is_empty()) {
+        lines.push(format!(
+            "> - **Condition Immunities** {}",
+            lowercase_list(conditions)
+        ));
+    }
+    lines.push(format!("> - **Senses** {}", senses(creature)));
+    lines.push(format!(
+        "> - **Languages** {}",
+        match creature.languages().filter(|x| !x.is_empty()) {
+            Some(languages) => list(languages),
+            None => String::from("—"),
+        }
+    ));
+    lines.push(format!("> - **Challenge** {}", challenge(creature)));
+    if let Some(bonus) = creature.proficiency_bonus() {
+        lines.push(format!("> - **Proficiency Bonus** {:+}", bonus));
+    }
+    for other in extra_attributes(creature) {
+        lines.push(format!("> - **{}** {}", other.title, other.value));
+    }
+    lines.push(String::from("> ___"));
+
+    for racial_trait in creature.racial_traits().into_iter().flatten() {
+        lines.push(format!(
+            "> ***{}.*** {}",
+            racial_trait.name, racial_trait.description
+        ));
+        lines.push(String::from(">"));
+    }
+
+    let sections = action_sections(creature);
+    for (title, actions) in [
+        ("Actions", &sections.actions),
+        ("Reactions", &sections.reactions),
+    ] {
+        if actions.is_empty() {
+            continue;
+        }
+        lines.push(format!("> ### {}", title));
+        for action in actions {
+            lines.push(format!("> {}", action_line(action)));
+            lines.push(String::from(">"));
+        }
+    }
+    if !sections.legendary_actions.is_empty() {
+        lines.push(String::from("> ### Legendary Actions"));
+        lines.push(format!("> {}", legendary_intro(creature)));
+        lines.push(String::from(">"));
+        for action in &sections.legendary_actions {
+            lines.push(format!("> {}", action_line(action)));
+            lines.push(String::from(">"));
+        }
+    }
+    if let Some(lair) = creature.lair() {
+        lines.extend(lair_lines(lair));
+    }
+    while lines.last().map(|x| x == ">") == Some(true) {
+        lines.pop();
+    }
+
+    if let Some(description) = creature.description() {
+        lines.push(String::new());
+        lines.push(description);
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Renders a session plan as a summary followed by a section for each kind of content. Scenes
+/// get a heading each; the other sections are bulleted lists.
+pub fn plan(plan: &Plan) -> String {
+    let mut blocks = vec![plan.summary.trim().to_string()];
+    if !plan.scenes.is_empty() {
+        blocks.push(String::from("## Scenes"));
+        for scene in &plan.scenes {
+            blocks.push(format!("### {}", scene.title));
+            blocks.push(scene.description.trim().to_string());
+        }
+    }
+    blocks.push(bullets("Encounters", &plan.encounters));
+    blocks.push(bullets("NPCs", &plan.npcs));
+    blocks.push(bullets("Locations", &plan.locations));
+    document(blocks)
+}
+
+/// Renders a session recap as a summary followed by a bulleted list for each kind of content.
+/// Loot names the id of the player who took it in parentheses.
+pub fn recap(recap: &Recap) -> String {
+    let loot: Vec<String> = recap
+        .loot
+        .iter()
+        .map(|x| match &x.player_id {
+            Some(player_id) => format!("{} ({})", x.item, player_id),
+            None => x.item.clone(),
+        })
+        .collect();
+    let experience: Vec<String> = recap
+        .experience
+        .iter()
+        .map(|x| format!("{}: {} XP", x.player_id, x.experience))
+        .collect();
+    document(vec![
+        recap.summary.trim().to_string(),
+        bullets("Events", &recap.events),
+        bullets("Loot", &loot),
+        bullets("Experience", &experience),
+        bullets("Open threads", &recap.open_threads),
+    ])
+}
+
+/// A `## ` section listing `values`, or nothing when there are none.
+fn bullets<T: std::fmt::Display>(title: &str, values: &[T]) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = values.iter().map(|x| format!("- {}", x)).collect();
+    format!("## {}\n\n{}", title, items.join("\n"))
+}
+
+/// Joins the non-empty blocks of a document with blank lines.
+fn document(blocks: Vec<String>) -> String {
+    let blocks: Vec<String> = blocks.into_iter().filter(|x| !x.is_empty()).collect();
+    match blocks.is_empty() {
+        true => String::new(),
+        false => format!("{}\n", blocks.join("\n\n")),
+    }
+}
+
+fn action_line(action: &ActionType) -> String {
+    match &action {
+        ActionType::Attack(attack) => {
+            let attack = attack_text(attack);
+            let mut line = format!(
+                "***{}.*** *{}:* {} *Hit:* {}.",
+                attack.name, attack.kind, attack.details, attack.hit
+            );
+            if !attack.description.is_empty() {
+                line.push(' ');
+                line.push_str(&attack.description);
+            }
+            line
+        }
+        ActionType::Special(x) | ActionType::Reaction(x) | ActionType::LegendaryAction(x) => {
+            format!("***{}.*** {}", x.name, x.description)
+        }
+    }
+}
+
+fn lair_lines(lair: &Lair) -> Vec<String> {
+    let mut lines = vec![String::from("> ### Lair Actions")];
+    if !lair.description.is_empty() {
+        lines.push(format!("> {}", lair.description));
+        lines.push(String::from(">"));
+    }
+    lines.extend(paragraph_lines(&lair.lair_actions));
+    if !lair.regional_effects.is_empty() {
+        lines.push(String::from("> ### Regional Effects"));
+        lines.extend(paragraph_lines(&lair.regional_effects));
+    }
+    lines
+}
+
+fn paragraph_lines(paragraphs: &[Paragraph]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        if paragraph.bullet {
+            lines.push(format!("> - {}", paragraph.paragraph));
+            if paragraphs.get(i + 1).map(|x| x.bullet) != Some(true) {
+                lines.push(String::from(">"));
+            }
+        } else {
+            lines.push(format!("> {}", paragraph.paragraph));
+            lines.push(String::from(">"));
+        }
+    }
+    lines
+}
+
+mod test {
+    #[test]
+    fn test_creature_markdown() {
+        use crate::creature::{Lair, Paragraph};
+
+        let (mut creature, _) = crate::creature::parse::parse_stat_block(
+            "Goblin
+Small humanoid (goblinoid), neutral evil
+Armor Class 15 (leather armor, shield)
+Hit Points 7 (2d6)
+Speed 30 ft.
+STR DEX CON INT WIS CHA
+8 (-1) 14 (+2) 10 (+0) 10 (+0) 8 (-1) 8 (-1)
+Skills Stealth +6
+Senses darkvision 60 ft., passive Perception 9
+Languages Common, Goblin
+Challenge 1/4 (50 XP)
+Nimble Escape. The goblin can take the Disengage or Hide action as a bonus action on each of its turns.
+Actions
+Scimitar. Melee Weapon Attack: +4 to hit, reach 5 ft., one target. Hit: 5 (1d6 + 2) slashing damage.
+",
+        );
+        creature.set_lair(Some(Lair {
+            name: String::from("Goblin Warren"),
+            description: String::from("Goblins fight dirty in their warrens."),
+            lair_actions: vec![
+                Paragraph {
+                    paragraph: String::from("Caltrops scatter across the floor."),
+                    bullet: true,
+                },
+                Paragraph {
+                    paragraph: String::from("A rope trap springs."),
+                    bullet: true,
+                },
+            ],
+            regional_effects: vec![],
+        }));
+
+        assert_eq!(
+            super::creature(&creature),
+            "___
+> ## Goblin
+>*Small humanoid, neutral evil*
+> ___
+> - **Armor Class** 15
+> - **Hit Points** 7 (2d6)
+> - **Speed** 30 ft.
+>___
+>|STR|DEX|CON|INT|WIS|CHA|
+>|:---:|:---:|:---:|:---:|:---:|:---:|
+>|8 (-1)|14 (+2)|10 (+0)|10 (+0)|8 (-1)|8 (-1)|
+>___
+> - **Skills** Stealth +6
+> - **Senses** darkvision 60 ft., passive Perception 9
+> - **Languages** Common, Goblin
+> - **Challenge** 1/4 (50 XP)
+> - **Proficiency Bonus** +2
+> ___
+> ***Nimble Escape.*** The goblin can take the Disengage or Hide action as a bonus action on each of its turns.
+>
+> ### Actions
+> ***Scimitar.*** *Melee Weapon Attack:* +4 to hit, reach 5 ft., one target. *Hit:* 5 (1d6 + 2) slashing damage.
+>
+> ### Lair Actions
+> Goblins fight dirty in their warrens.
+>
+> - Caltrops scatter across the floor.
+> - A rope trap springs.
+"
+        );
+
+        let (parsed, warnings) =
+            crate::creature::parse::parse_stat_block(&super::creature(&creature));
+        assert_eq!(warnings, vec![]);
+        assert_eq!(parsed.name(), creature.name());
+        assert_eq!(parsed.actions().unwrap().len(), 1);
+        assert_eq!(parsed.lair().unwrap().lair_actions.len(), 2);
+    }
+
+    #[test]
+    fn test_session_markdown() {
+        use crate::{
+            id::{CreatureId, LocationId},
+            world::campaign::session::{Award, Loot, Plan, Recap, Scene},
+        };
+
+        let location: LocationId = "00000000-0000-0000-0000-000000000001".parse().unwrap();
+        let plan = Plan {
+            summary: String::from("The party reaches the citadel."),
+            scenes: vec![Scene {
+                title: String::from("Ravine"),
+                description: String::from("Goblins wait in ambush.\n\nOne tries to flee."),
+            }],
+            encounters: vec![String::from("4 goblins")],
+            npcs: vec![String::from("Meepo"), String::from("Yusdrayl")],
+            locations: vec![location],
+        };
+        let text = super::plan(&plan);
+        assert_eq!(
+            text,
+            "The party reaches the citadel.
+
+## Scenes
+
+### Ravine
+
+Goblins wait in ambush.
+
+One tries to flee.
+
+## Encounters
+
+- 4 goblins
+
+## NPCs
+
+- Meepo
+- Yusdrayl
+
+## Locations
+
+- 00000000-0000-0000-0000-000000000001
+"
+        );
+        assert_eq!(text.parse::<Plan>().unwrap(), plan);
+
+        let player_id = CreatureId::new();
+        let recap = Recap {
+            summary: String::new(),
+            events: vec![String::from("Freed Meepo")],
+            loot: vec![
+                Loot {
+                    item: String::from("Potion of Healing (greater)"),
+                    player_id: Some(player_id),
+                },
+                Loot {
+                    item: String::from("50 gp"),
+                    player_id: None,
+                },
+            ],
+            experience: vec![Award {
+                player_id,
+                experience: 300,
+            }],
+            open_threads: vec![String::from("Who hired the goblins?")],
+        };
+        let parsed: Recap = super::recap(&recap).parse().unwrap();
+        assert_eq!(parsed, recap);
+        assert_eq!(parsed.experience_of(&player_id), 300);
+        assert!("## Rumours\n\n- none".parse::<Recap>().is_err());
+        assert!("## Experience\n\n- Meepo: lots".parse::<Recap>().is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! JSON Schema documents for the serialized types, available with the `schema` feature.
+//!
+//! The schemas are derived from the same serde attributes as the serialization itself, so enum
+//! tagging, optional fields and defaults are described the way serde reads and writes them.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use schemars::{schema::RootSchema, schema_for};
+
+use crate::{
+    action::Action,
+    creature::Creature,
+    item::Item,
+    spell::Spell,
+    world::{
+        campaign::{player::Player, session::Session, Campaign},
+        map::Map,
+        World,
+    },
+};
+
+/// The schema of every top-level type, keyed by a file-friendly name.
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("action", schema_for!(Action)),
+        ("campaign", schema_for!(Campaign)),
+        ("creature", schema_for!(Creature)),
+        ("item", schema_for!(Item)),
+        ("map", schema_for!(Map)),
+        ("player", schema_for!(Player)),
+        ("session", schema_for!(Session)),
+        ("spell", schema_for!(Spell)),
+        ("world", schema_for!(World)),
+    ]
+}
+
+/// Writes every schema to `<name>.schema.json` in `dir`, returning the written files.
+pub fn write_schemas(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut files = Vec::new();
+    for (name, schema) in schemas() {
+        let file = dir.join(format!("{}.schema.json", name));
+        fs::write(&file, serde_json::to_string_pretty(&schema)?)?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+mod test {
+    #[test]
+    fn test_schemas_follow_serde() {
+        use serde_json::Value;
+
+        let schemas: Vec<(&str, Value)> = super::schemas()
+            .into_iter()
+            .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap()))
+            .collect();
+        let schema = |name: &str| schemas.iter().find(|(x, _)| *x == name).unwrap().1.clone();
+
+        // Externally tagged enums: unit variants are strings, the others single-key objects.
+        let action = schema("action");
+        let action_type = &action["definitions"]["ActionType"]["oneOf"];
+        assert_eq!(action_type[0]["required"], serde_json::json!(["Attack"]));
+        let creature = schema("creature");
+        let creature_type = creature["definitions"]["CreatureType"].to_string();
+        assert!(creature_type.contains("\"Player\""));
+        assert!(creature_type.contains("\"Monster\""));
+
+        // Fields with a serde default are optional, the others are required.
+        let required = creature["required"].as_array().unwrap();
+        assert!(!required.contains(&Value::from("size")));
+        assert!(required.contains(&Value::from("challenge_rating")));
+
+        // Attacks are described as they serialize now, with a list of damage components.
+        assert_eq!(
+            action["definitions"]["Melee"]["properties"]["damage"]["type"],
+            "array"
+        );
+
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let files = super::write_schemas(&dir).unwrap();
+        assert_eq!(files.len(), 9);
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("spell.schema.json")).unwrap())
+                .unwrap();
+        assert_eq!(written["title"], "Spell");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
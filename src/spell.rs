@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use crate::{DamageType, DieStat};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Spell {
     pub id: String,
     pub name: String,
@@ -22,6 +24,8 @@ pub struct Spell {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum SpellLevel {
     Cantrip,
     Level1,
@@ -53,6 +57,8 @@ impl fmt::Display for SpellLevel {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum CastingTime {
     Action,
     BonusAction,
@@ -74,6 +80,8 @@ impl fmt::Display for CastingTime {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Duration {
     Instantaneous,
     Concentration,
@@ -101,6 +109,8 @@ impl fmt::Display for Duration {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum SpellRange {
     S,
     Touch,
@@ -118,6 +128,8 @@ impl fmt::Display for SpellRange {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Area {
     Cube(i32),
     Sphere(i32),
@@ -141,6 +153,8 @@ impl fmt::Display for Area {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Components {
     V,
     S,
@@ -166,6 +180,8 @@ impl fmt::Display for Components {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Save {
     Strength(Option<i32>),
     Dexterity(Option<i32>),
@@ -0,0 +1,274 @@
+//! Persistence of the crate's entities, available with the `storage` feature.
+//!
+//! [`Storage`] is implemented by every backend: [`memory::MemoryStorage`] keeps entities in
+//! memory, [`directory::DirectoryStorage`] writes one JSON file per entity and, with the
+//! `sqlite` feature, `sqlite::SqliteStorage` keeps them in a SQLite database. Entities are
+//! stored in their versioned [`Envelope`](crate::migration::Envelope), so data written by an
+//! older version of the crate is migrated as it is read. Backends outside this crate can check
+//! their behavior against the same expectations with [`conformance::check`].
+
+use core::fmt;
+use std::io;
+
+use crate::{
+    creature::Creature,
+    id::{CampaignId, CreatureId, LocationId, MapId, SessionId, WorldId},
+    item::Item,
+    migration::{MigrationError, Versioned},
+    spell::Spell,
+    world::{
+        campaign::{player::Player, session::Session, Campaign, CampaignSource},
+        location::Location,
+        map::Map,
+        World,
+    },
+};
+
+pub mod conformance;
+pub mod directory;
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// A type that is stored on its own, keyed by its id.
+pub trait Entity: Versioned {
+    type Id: fmt::Display + fmt::Debug + Clone + PartialEq;
+
+    fn entity_id(&self) -> Self::Id;
+}
+
+/// An entity that belongs to another one and can be queried by the id of its parent.
+pub trait Child: Entity {
+    type Parent: Entity;
+
+    fn parent_id(&self) -> <Self::Parent as Entity>::Id;
+}
+
+/// A store of entities. Listing returns entities sorted by the string form of their ids.
+pub trait Storage {
+    fn get<T: Entity>(&self, id: &T::Id) -> Result<Option<T>, StorageError>;
+
+    /// Inserts an entity, replacing any stored one with the same id.
+    fn put<T: Entity>(&mut self, entity: &T) -> Result<(), StorageError>;
+
+    fn list<T: Entity>(&self) -> Result<Vec<T>, StorageError>;
+
+    /// Removes an entity, returning whether there was one to remove.
+    fn delete<T: Entity>(&mut self, id: &T::Id) -> Result<bool, StorageError>;
+
+    /// The entities that belong to the given parent, such as the sessions of a campaign.
+    fn children<T: Child>(
+        &self,
+        parent: &<T::Parent as Entity>::Id,
+    ) -> Result<Vec<T>, StorageError> {
+        Ok(self
+            .list::<T>()?
+            .into_iter()
+            .filter(|x| &x.parent_id() == parent)
+            .collect())
+    }
+}
+
+impl<S: Storage> CampaignSource for S {
+    type Error = StorageError;
+
+    fn campaign(&self, id: &CampaignId) -> Result<Option<Campaign>, StorageError> {
+        self.get(id)
+    }
+
+    fn player(&self, id: &CreatureId) -> Result<Option<Player>, StorageError> {
+        self.get(id)
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    /// A stored entity could not be read or migrated.
+    Migration {
+        kind: &'static str,
+        id: String,
+        error: MigrationError,
+    },
+    /// An entity that an update depends on is not stored.
+    Missing {
+        kind: &'static str,
+        id: String,
+    },
+    /// The backend has no place for this kind of entity.
+    UnsupportedKind(&'static str),
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    /// The database was written by a newer version of the crate.
+    #[cfg(feature = "sqlite")]
+    SchemaVersion {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            StorageError::Io(e) => write!(f, "{}", e),
+            StorageError::Migration { kind, id, error } => {
+                write!(f, "{} {}: {}", kind, id, error)
+            }
+            StorageError::Missing { kind, id } => write!(f, "no {} with id {}", kind, id),
+            StorageError::UnsupportedKind(kind) => write!(f, "cannot store a {}", kind),
+            #[cfg(feature = "sqlite")]
+            StorageError::Sqlite(e) => write!(f, "{}", e),
+            #[cfg(feature = "sqlite")]
+            StorageError::SchemaVersion { found, supported } => write!(
+                f,
+                "database schema version {} is newer than the supported version {}",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StorageError {
+    fn from(e: rusqlite::Error) -> Self {
+        StorageError::Sqlite(e)
+    }
+}
+
+impl StorageError {
+    pub(crate) fn migration<T: Entity>(id: &str, error: MigrationError) -> Self {
+        StorageError::Migration {
+            kind: T::KIND,
+            id: id.to_string(),
+            error,
+        }
+    }
+
+    /// The error for an update that needs an entity that is not stored.
+    pub fn missing<T: Entity>(id: &str) -> Self {
+        StorageError::Missing {
+            kind: T::KIND,
+            id: id.to_string(),
+        }
+    }
+}
+
+impl Entity for World {
+    type Id = WorldId;
+
+    fn entity_id(&self) -> WorldId {
+        self.id
+    }
+}
+
+impl Entity for Campaign {
+    type Id = CampaignId;
+
+    fn entity_id(&self) -> CampaignId {
+        self.id
+    }
+}
+
+impl Child for Campaign {
+    type Parent = World;
+
+    fn parent_id(&self) -> WorldId {
+        self.world_id
+    }
+}
+
+impl Entity for Player {
+    type Id = CreatureId;
+
+    fn entity_id(&self) -> CreatureId {
+        *self.id()
+    }
+}
+
+impl Child for Player {
+    type Parent = Campaign;
+
+    fn parent_id(&self) -> CampaignId {
+        *self.campaign_id()
+    }
+}
+
+impl Entity for Session {
+    type Id = SessionId;
+
+    fn entity_id(&self) -> SessionId {
+        self.id
+    }
+}
+
+impl Child for Session {
+    type Parent = Campaign;
+
+    fn parent_id(&self) -> CampaignId {
+        self.campaign_id
+    }
+}
+
+impl Entity for Map {
+    type Id = MapId;
+
+    fn entity_id(&self) -> MapId {
+        self.id
+    }
+}
+
+impl Child for Map {
+    type Parent = World;
+
+    fn parent_id(&self) -> WorldId {
+        self.world_id
+    }
+}
+
+impl Entity for Location {
+    type Id = LocationId;
+
+    fn entity_id(&self) -> LocationId {
+        self.id
+    }
+}
+
+impl Child for Location {
+    type Parent = Map;
+
+    fn parent_id(&self) -> MapId {
+        self.map_coordinates.map_id
+    }
+}
+
+impl Entity for Creature {
+    type Id = CreatureId;
+
+    fn entity_id(&self) -> CreatureId {
+        *self.id()
+    }
+}
+
+impl Entity for Item {
+    type Id = String;
+
+    fn entity_id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+impl Entity for Spell {
+    type Id = String;
+
+    fn entity_id(&self) -> String {
+        self.id.clone()
+    }
+}
@@ -0,0 +1,312 @@
+//! The behavior every [`Storage`] backend is expected to have.
+//!
+//! Backends run [`check`] from their tests against an empty store; it panics with a
+//! description of the first expectation the backend does not meet.
+
+use serde::Serialize;
+
+use crate::{
+    creature::{Creature, CreatureType, Health, MovementSpeed},
+    id::{CampaignId, CreatureId, LocationId, MapId, WorldId},
+    item::{Item, ItemRarity, ItemType},
+    spell::{CastingTime, Components, Duration, Spell, SpellLevel, SpellRange},
+    world::{
+        campaign::{
+            player::{Class, Player, Race},
+            session::Session,
+            Campaign, CampaignGraph,
+        },
+        location::Location,
+        map::{Map, MapCoordinates},
+        World,
+    },
+    Alignment, DieStat,
+};
+
+use super::Storage;
+
+/// Runs every expectation against `storage`, which must start out empty.
+pub fn check<S: Storage>(storage: &mut S) {
+    empty(storage);
+    round_trip(storage);
+    replace_and_delete(storage);
+    children(storage);
+    kinds_are_separate(storage);
+    free_form_ids(storage);
+}
+
+fn empty<S: Storage>(storage: &mut S) {
+    assert!(
+        storage.list::<World>().unwrap().is_empty(),
+        "a new storage should be empty"
+    );
+    assert!(
+        storage.get::<World>(&WorldId::new()).unwrap().is_none(),
+        "getting an unknown id should return nothing"
+    );
+    assert!(
+        !storage.delete::<World>(&WorldId::new()).unwrap(),
+        "deleting an unknown id should report that nothing was deleted"
+    );
+}
+
+fn round_trip<S: Storage>(storage: &mut S) {
+    let world = World::new(String::from("Faerûn"), String::from("The Forgotten Realms"));
+    storage.put(&world).unwrap();
+    same(
+        &storage.get::<World>(&world.id).unwrap(),
+        &Some(&world),
+        "get",
+    );
+
+    let mut map = Map::new(
+        MapId::new(),
+        String::from("Sword Coast"),
+        String::new(),
+        world.id,
+        2,
+        1,
+    );
+    map.locations.push(location(&map, "Waterdeep"));
+    storage.put(&map).unwrap();
+    same(&storage.get::<Map>(&map.id).unwrap(), &Some(&map), "get");
+
+    let creature = creature("Goblin", CreatureType::NPC);
+    storage.put(&creature).unwrap();
+    same(
+        &storage.get::<Creature>(creature.id()).unwrap(),
+        &Some(&creature),
+        "get",
+    );
+    storage.delete::<Creature>(creature.id()).unwrap();
+    storage.delete::<Map>(&map.id).unwrap();
+    storage.delete::<World>(&world.id).unwrap();
+}
+
+fn replace_and_delete<S: Storage>(storage: &mut S) {
+    let mut worlds: Vec<World> = (0..3)
+        .map(|i| World::new(format!("World {}", i), String::new()))
+        .collect();
+    for world in &worlds {
+        storage.put(world).unwrap();
+    }
+    worlds[1].name = String::from("Renamed");
+    storage.put(&worlds[1]).unwrap();
+
+    worlds.sort_by_key(|x| x.id.to_string());
+    same(&storage.list::<World>().unwrap(), &worlds, "list");
+
+    let deleted = worlds.remove(0);
+    assert!(
+        storage.delete::<World>(&deleted.id).unwrap(),
+        "deleting a stored entity should report that it was deleted"
+    );
+    assert!(
+        storage.get::<World>(&deleted.id).unwrap().is_none(),
+        "a deleted entity should be gone"
+    );
+    same(
+        &storage.list::<World>().unwrap(),
+        &worlds,
+        "list after delete",
+    );
+    for world in &worlds {
+        storage.delete::<World>(&world.id).unwrap();
+    }
+}
+
+fn children<S: Storage>(storage: &mut S) {
+    let world = World::new(String::from("Eberron"), String::new());
+    let mut first = Campaign::new(String::from("First"), String::new(), world.id);
+    let second = Campaign::new(String::from("Second"), String::new(), world.id);
+    let mut sessions: Vec<Session> = (0..3)
+        .map(|i| Session::new(format!("Session {}", i), String::new(), first.id))
+        .collect();
+    sessions.push(Session::new(
+        String::from("Elsewhere"),
+        String::new(),
+        second.id,
+    ));
+    let mut player = player("Tessa");
+    first.add_player(&mut player);
+
+    storage.put(&world).unwrap();
+    storage.put(&first).unwrap();
+    storage.put(&second).unwrap();
+    storage.put(&player).unwrap();
+    for session in &sessions {
+        storage.put(session).unwrap();
+    }
+
+    let mut expected: Vec<&Session> = sessions.iter().take(3).collect();
+    expected.sort_by_key(|x| x.id.to_string());
+    same(
+        &storage.children::<Session>(&first.id).unwrap(),
+        &expected,
+        "children",
+    );
+    assert!(
+        storage
+            .children::<Session>(&CampaignId::new())
+            .unwrap()
+            .is_empty(),
+        "an unknown parent should have no children"
+    );
+    assert_eq!(
+        storage.children::<Campaign>(&world.id).unwrap().len(),
+        2,
+        "both campaigns should belong to the world"
+    );
+
+    let graph = CampaignGraph::resolve(storage, &first.id).unwrap();
+    same(&graph.players, &vec![&player], "resolved players");
+
+    let map = Map::new(MapId::new(), String::new(), String::new(), world.id, 1, 1);
+    let locations = [location(&map, "Sharn"), location(&map, "Fairhaven")];
+    for location in &locations {
+        storage.put(location).unwrap();
+    }
+    assert_eq!(
+        storage.children::<Location>(&map.id).unwrap().len(),
+        2,
+        "locations should belong to the map of their coordinates"
+    );
+
+    for session in &sessions {
+        storage.delete::<Session>(&session.id).unwrap();
+    }
+    for location in &locations {
+        storage.delete::<Location>(&location.id).unwrap();
+    }
+    storage.delete::<Player>(player.id()).unwrap();
+    storage.delete::<Campaign>(&first.id).unwrap();
+    storage.delete::<Campaign>(&second.id).unwrap();
+    storage.delete::<World>(&world.id).unwrap();
+}
+
+fn kinds_are_separate<S: Storage>(storage: &mut S) {
+    // A player and its creature share an id but are stored as different kinds.
+    let player = player("Tessa");
+    storage.put(&player).unwrap();
+    storage.put(player.creature()).unwrap();
+    storage.delete::<Creature>(player.id()).unwrap();
+    same(
+        &storage.get::<Player>(player.id()).unwrap(),
+        &Some(&player),
+        "get after deleting another kind",
+    );
+    assert!(
+        storage.list::<Session>().unwrap().is_empty(),
+        "other kinds should not be listed"
+    );
+    storage.delete::<Player>(player.id()).unwrap();
+}
+
+fn free_form_ids<S: Storage>(storage: &mut S) {
+    let item = Item {
+        id: String::from("Bag of Holding/2"),
+        name: String::from("Bag of Holding"),
+        item_type: ItemType::WondrousItem,
+        rarity: ItemRarity::Uncommon,
+        attunement: None,
+        weapon_type: None,
+        armor_type: None,
+        conditions: None,
+        attached_spell: None,
+        has_charges: None,
+        inventory: None,
+        others: None,
+        actions: None,
+    };
+    let spell = Spell {
+        id: String::new(),
+        name: String::from("Light"),
+        description: String::new(),
+        level: SpellLevel::Cantrip,
+        casting_time: CastingTime::Action,
+        duration: Duration::Hour,
+        damage: None,
+        range: SpellRange::Touch,
+        area: None,
+        damage_type: None,
+        components: Components::VM,
+        attack_bonus: None,
+        save: None,
+    };
+    storage.put(&item).unwrap();
+    storage.put(&spell).unwrap();
+    assert_eq!(
+        storage.get::<Item>(&item.id).unwrap(),
+        Some(item.clone()),
+        "ids with spaces and slashes should round trip"
+    );
+    assert_eq!(
+        storage.list::<Spell>().unwrap(),
+        vec![spell.clone()],
+        "the empty id should round trip"
+    );
+    storage.delete::<Item>(&item.id).unwrap();
+    storage.delete::<Spell>(&spell.id).unwrap();
+}
+
+/// Compares entities by their serialized form, since not all of them implement `PartialEq`.
+fn same<A: Serialize, B: Serialize>(found: &A, expected: &B, what: &str) {
+    assert_eq!(
+        serde_json::to_value(found).unwrap(),
+        serde_json::to_value(expected).unwrap(),
+        "{} returned something other than what was stored",
+        what
+    );
+}
+
+fn location(map: &Map, name: &str) -> Location {
+    Location {
+        id: LocationId::new(),
+        name: name.to_string(),
+        description: String::new(),
+        world_id: map.world_id,
+        map_coordinates: MapCoordinates::new(map.id, 0, 0),
+        npcs: vec![creature("Guard", CreatureType::NPC)],
+    }
+}
+
+fn creature(name: &str, creature_type: CreatureType) -> Creature {
+    Creature::new(
+        CreatureId::new(),
+        name.to_string(),
+        creature_type,
+        Alignment::TrueNeutral,
+        10,
+        Health {
+            health: DieStat::default(),
+        },
+        MovementSpeed::Walk(30),
+        Vec::new(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        String::new(),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+fn player(name: &str) -> Player {
+    Player::try_new(
+        creature(name, CreatureType::Player),
+        Race::Human,
+        Class::Fighter,
+        1,
+        0,
+        CampaignId::new(),
+    )
+    .unwrap()
+}
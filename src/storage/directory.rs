@@ -0,0 +1,133 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::migration::{self, Envelope};
+
+use super::{Entity, Storage, StorageError};
+
+/// Stores each entity as `<root>/<kind>/<id>.json`, for example `campaign/<uuid>.json`.
+///
+/// Files are replaced atomically, so a reader never sees a half-written entity. Characters of
+/// an id that are not safe in file names are percent-encoded.
+#[derive(Debug, Clone)]
+pub struct DirectoryStorage {
+    root: PathBuf,
+}
+
+impl DirectoryStorage {
+    /// Uses `root` as the storage directory, creating it when it does not exist.
+    pub fn open(root: &Path) -> Result<Self, StorageError> {
+        fs::create_dir_all(root)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn file<T: Entity>(&self, id: &str) -> PathBuf {
+        self.root.join(T::KIND).join(file_name(id))
+    }
+}
+
+fn file_name(id: &str) -> String {
+    let mut name = String::new();
+    for byte in id.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => name.push(byte as char),
+            _ => name.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    // The empty id would otherwise be a hidden file named `.json`.
+    if name.is_empty() {
+        name.push('%');
+    }
+    name + ".json"
+}
+
+fn read<T: Entity>(file: &Path) -> Result<T, StorageError> {
+    let text = fs::read_to_string(file)?;
+    let id = file.file_stem().unwrap_or_default().to_string_lossy();
+    migration::from_json(&text).map_err(|e| StorageError::migration::<T>(&id, e))
+}
+
+impl Storage for DirectoryStorage {
+    fn get<T: Entity>(&self, id: &T::Id) -> Result<Option<T>, StorageError> {
+        match read(&self.file::<T>(&id.to_string())) {
+            Err(StorageError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            x => x.map(Some),
+        }
+    }
+
+    fn put<T: Entity>(&mut self, entity: &T) -> Result<(), StorageError> {
+        let id = entity.entity_id().to_string();
+        let envelope = Envelope::new(entity).map_err(|e| StorageError::migration::<T>(&id, e))?;
+        let text = serde_json::to_string_pretty(&envelope)
+            .map_err(|e| StorageError::migration::<T>(&id, e.into()))?;
+        let file = self.file::<T>(&id);
+        fs::create_dir_all(self.root.join(T::KIND))?;
+        let temporary = file.with_extension("json.tmp");
+        fs::write(&temporary, text)?;
+        fs::rename(temporary, file)?;
+        Ok(())
+    }
+
+    fn list<T: Entity>(&self) -> Result<Vec<T>, StorageError> {
+        let entries = match fs::read_dir(self.root.join(T::KIND)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            x => x?,
+        };
+        let mut entities = Vec::new();
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension().is_some_and(|x| x == "json") {
+                entities.push(read::<T>(&file)?);
+            }
+        }
+        entities.sort_by_cached_key(|x| x.entity_id().to_string());
+        Ok(entities)
+    }
+
+    fn delete<T: Entity>(&mut self, id: &T::Id) -> Result<bool, StorageError> {
+        match fs::remove_file(self.file::<T>(&id.to_string())) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_conformance() {
+        use crate::{
+            id::{CreatureId, WorldId},
+            storage::{Storage, StorageError},
+            world::campaign::{player::Player, Campaign, CampaignGraph, ResolveError},
+        };
+
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let mut storage = super::DirectoryStorage::open(&dir).unwrap();
+        super::super::conformance::check(&mut storage);
+
+        // Ids that are not safe in file names are encoded, and the files stay readable.
+        assert_eq!(super::file_name("a/b c"), "a%2Fb%20c.json");
+        assert_eq!(super::file_name(""), "%.json");
+
+        // A player that fails to load is reported as such, not as missing.
+        let mut campaign = Campaign::new(String::from("Eberron"), String::new(), WorldId::new());
+        let player_id = CreatureId::new();
+        campaign.player_ids.push(player_id);
+        storage.put(&campaign).unwrap();
+        let file = storage.file::<Player>(&player_id.to_string());
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(file, "{not json").unwrap();
+        let error = CampaignGraph::resolve(&storage, &campaign.id).unwrap_err();
+        assert!(matches!(&error, ResolveError::Source(e) if e.is::<StorageError>()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use crate::migration::Envelope;
+
+use super::{Entity, Storage, StorageError};
+
+/// Keeps entities in memory, in the same envelopes the other backends store.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: BTreeMap<&'static str, BTreeMap<String, Envelope>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn read<T: Entity>(id: &str, envelope: &Envelope) -> Result<T, StorageError> {
+    envelope
+        .clone()
+        .into_inner()
+        .map_err(|e| StorageError::migration::<T>(id, e))
+}
+
+impl Storage for MemoryStorage {
+    fn get<T: Entity>(&self, id: &T::Id) -> Result<Option<T>, StorageError> {
+        let id = id.to_string();
+        self.entries
+            .get(T::KIND)
+            .and_then(|x| x.get(&id))
+            .map(|x| read(&id, x))
+            .transpose()
+    }
+
+    fn put<T: Entity>(&mut self, entity: &T) -> Result<(), StorageError> {
+        let id = entity.entity_id().to_string();
+        let envelope = Envelope::new(entity).map_err(|e| StorageError::migration::<T>(&id, e))?;
+        self.entries
+            .entry(T::KIND)
+            .or_default()
+            .insert(id, envelope);
+        Ok(())
+    }
+
+    fn list<T: Entity>(&self) -> Result<Vec<T>, StorageError> {
+        self.entries
+            .get(T::KIND)
+            .into_iter()
+            .flatten()
+            .map(|(id, x)| read(id, x))
+            .collect()
+    }
+
+    fn delete<T: Entity>(&mut self, id: &T::Id) -> Result<bool, StorageError> {
+        Ok(self
+            .entries
+            .get_mut(T::KIND)
+            .and_then(|x| x.remove(&id.to_string()))
+            .is_some())
+    }
+}
+
+mod test {
+    #[test]
+    fn test_conformance() {
+        super::super::conformance::check(&mut super::MemoryStorage::new());
+    }
+}
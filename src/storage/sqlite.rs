@@ -0,0 +1,432 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde_json::Value;
+
+use crate::{
+    id::{LocationId, MapId},
+    migration::Envelope,
+    world::{location::Location, map::Map},
+};
+
+use super::{Child, Entity, Storage, StorageError};
+
+/// The schema changes from each version of the database to the next, starting at version 0
+/// for a new database. The version a database is at is kept in `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE worlds (id TEXT PRIMARY KEY, version INTEGER NOT NULL, data TEXT NOT NULL);
+    CREATE TABLE campaigns (
+        id TEXT PRIMARY KEY, world_id TEXT NOT NULL, version INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE INDEX campaigns_world_id ON campaigns (world_id);
+    CREATE TABLE players (
+        id TEXT PRIMARY KEY, campaign_id TEXT NOT NULL, version INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX players_campaign_id ON players (campaign_id);
+    CREATE TABLE sessions (
+        id TEXT PRIMARY KEY, campaign_id TEXT NOT NULL, version INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX sessions_campaign_id ON sessions (campaign_id);
+    CREATE TABLE maps (
+        id TEXT PRIMARY KEY, world_id TEXT NOT NULL, version INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE INDEX maps_world_id ON maps (world_id);
+    CREATE TABLE locations (
+        id TEXT PRIMARY KEY, map_id TEXT NOT NULL, version INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE INDEX locations_map_id ON locations (map_id);
+    CREATE TABLE creatures (id TEXT PRIMARY KEY, version INTEGER NOT NULL, data TEXT NOT NULL);
+    CREATE TABLE items (id TEXT PRIMARY KEY, version INTEGER NOT NULL, data TEXT NOT NULL);
+    CREATE TABLE spells (id TEXT PRIMARY KEY, version INTEGER NOT NULL, data TEXT NOT NULL);
+"];
+
+/// Where entities of one kind are stored.
+struct Table {
+    name: &'static str,
+    /// The column holding the id of the parent, and where that id is in the entity's JSON.
+    parent: Option<(&'static str, &'static str)>,
+}
+
+fn table<T: Entity>() -> Result<Table, StorageError> {
+    let (name, parent) = match T::KIND {
+        "world" => ("worlds", None),
+        "campaign" => ("campaigns", Some(("world_id", "/world_id"))),
+        "player" => ("players", Some(("campaign_id", "/campaign_id"))),
+        "session" => ("sessions", Some(("campaign_id", "/campaign_id"))),
+        "map" => ("maps", Some(("world_id", "/world_id"))),
+        "location" => ("locations", Some(("map_id", "/map_coordinates/map_id"))),
+        "creature" => ("creatures", None),
+        "item" => ("items", None),
+        "spell" => ("spells", None),
+        kind => return Err(StorageError::UnsupportedKind(kind)),
+    };
+    Ok(Table { name, parent })
+}
+
+/// Stores worlds, campaigns, players, sessions, maps, locations, creatures, items and spells
+/// in a SQLite database, with one table per kind and the ids of parents in indexed columns.
+///
+/// Opening a database brings its schema up to date. Several changes can be made atomically
+/// with [`SqliteStorage::transaction`].
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &std::path::Path) -> Result<Self, StorageError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an open connection, migrating its schema to the current version.
+    pub fn from_connection(mut connection: Connection) -> Result<Self, StorageError> {
+        let version: u32 = connection.pragma_query_value(None, "user_version", |x| x.get(0))?;
+        if version as usize > MIGRATIONS.len() {
+            return Err(StorageError::SchemaVersion {
+                found: version,
+                supported: MIGRATIONS.len() as u32,
+            });
+        }
+        let transaction = connection.transaction()?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", i as u32 + 1)?;
+        }
+        transaction.commit()?;
+        Ok(Self { connection })
+    }
+
+    /// The schema version of the database.
+    pub fn schema_version(&self) -> Result<u32, StorageError> {
+        Ok(self
+            .connection
+            .pragma_query_value(None, "user_version", |x| x.get(0))?)
+    }
+
+    /// Runs `f` in a transaction, which is committed when `f` succeeds and rolled back when it
+    /// fails.
+    pub fn transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut SqliteTransaction) -> Result<R, StorageError>,
+    ) -> Result<R, StorageError> {
+        let mut transaction = SqliteTransaction {
+            transaction: self.connection.transaction()?,
+        };
+        let result = f(&mut transaction)?;
+        transaction.transaction.commit()?;
+        Ok(result)
+    }
+
+    /// Moves a location to other coordinates, possibly on another map. The location's own
+    /// entry and the `locations` of both maps, when they are stored, change together.
+    pub fn move_location(
+        &mut self,
+        id: &LocationId,
+        to: &MapId,
+        x: u32,
+        y: u32,
+    ) -> Result<(), StorageError> {
+        self.transaction(|transaction| {
+            let mut location = transaction
+                .get::<Location>(id)?
+                .ok_or_else(|| StorageError::missing::<Location>(&id.to_string()))?;
+            let from = location.map_coordinates.map_id;
+            let mut target = transaction
+                .get::<Map>(to)?
+                .ok_or_else(|| StorageError::missing::<Map>(&to.to_string()))?;
+
+            location.map_coordinates.map_id = *to;
+            location.map_coordinates.x = x;
+            location.map_coordinates.y = y;
+            transaction.put(&location)?;
+
+            if from != *to {
+                if let Some(mut source) = transaction.get::<Map>(&from)? {
+                    source.locations.retain(|x| &x.id != id);
+                    transaction.put(&source)?;
+                }
+            }
+            target.locations.retain(|x| &x.id != id);
+            target.locations.push(location);
+            transaction.put(&target)
+        })
+    }
+}
+
+/// A transaction on a [`SqliteStorage`], which is itself a [`Storage`].
+pub struct SqliteTransaction<'a> {
+    transaction: Transaction<'a>,
+}
+
+fn get<T: Entity>(connection: &Connection, id: &T::Id) -> Result<Option<T>, StorageError> {
+    let table = table::<T>()?;
+    let id = id.to_string();
+    connection
+        .query_row(
+            &format!("SELECT version, data FROM {} WHERE id = ?1", table.name),
+            params![id],
+            |x| Ok((x.get(0)?, x.get(1)?)),
+        )
+        .optional()?
+        .map(|(version, data)| read(&id, version, data))
+        .transpose()
+}
+
+fn put<T: Entity>(connection: &Connection, entity: &T) -> Result<(), StorageError> {
+    let table = table::<T>()?;
+    let id = entity.entity_id().to_string();
+    let envelope = Envelope::new(entity).map_err(|e| StorageError::migration::<T>(&id, e))?;
+    let data = envelope.data.to_string();
+    match table.parent {
+        Some((column, pointer)) => {
+            let parent = match envelope.data.pointer(pointer) {
+                Some(Value::String(x)) => x.clone(),
+                x => x.map(Value::to_string).unwrap_or_default(),
+            };
+            connection.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} (id, {}, version, data) VALUES (?1, ?2, ?3, ?4)",
+                    table.name, column
+                ),
+                params![id, parent, envelope.version, data],
+            )?
+        }
+        None => connection.execute(
+            &format!(
+                "INSERT OR REPLACE INTO {} (id, version, data) VALUES (?1, ?2, ?3)",
+                table.name
+            ),
+            params![id, envelope.version, data],
+        )?,
+    };
+    Ok(())
+}
+
+/// Reads the entities selected by `query`, which yields the id, version and data of each.
+fn select<T: Entity>(
+    connection: &Connection,
+    query: &str,
+    parameters: impl rusqlite::Params,
+) -> Result<Vec<T>, StorageError> {
+    let mut statement = connection.prepare(query)?;
+    let rows = statement
+        .query_map(parameters, |x| Ok((x.get(0)?, x.get(1)?, x.get(2)?)))?
+        .collect::<Result<Vec<(String, u32, String)>, _>>()?;
+    rows.into_iter()
+        .map(|(id, version, data)| read(&id, version, data))
+        .collect()
+}
+
+fn list<T: Entity>(connection: &Connection) -> Result<Vec<T>, StorageError> {
+    let table = table::<T>()?;
+    select(
+        connection,
+        &format!("SELECT id, version, data FROM {} ORDER BY id", table.name),
+        [],
+    )
+}
+
+fn delete<T: Entity>(connection: &Connection, id: &T::Id) -> Result<bool, StorageError> {
+    let table = table::<T>()?;
+    let deleted = connection.execute(
+        &format!("DELETE FROM {} WHERE id = ?1", table.name),
+        params![id.to_string()],
+    )?;
+    Ok(deleted > 0)
+}
+
+fn children<T: Child>(
+    connection: &Connection,
+    parent: &<T::Parent as Entity>::Id,
+) -> Result<Vec<T>, StorageError> {
+    let table = table::<T>()?;
+    let Some((column, _)) = table.parent else {
+        return Err(StorageError::UnsupportedKind(T::KIND));
+    };
+    select(
+        connection,
+        &format!(
+            "SELECT id, version, data FROM {} WHERE {} = ?1 ORDER BY id",
+            table.name, column
+        ),
+        params![parent.to_string()],
+    )
+}
+
+fn read<T: Entity>(id: &str, version: u32, data: String) -> Result<T, StorageError> {
+    let data =
+        serde_json::from_str(&data).map_err(|e| StorageError::migration::<T>(id, e.into()))?;
+    Envelope {
+        kind: T::KIND.to_string(),
+        version,
+        data,
+    }
+    .into_inner()
+    .map_err(|e| StorageError::migration::<T>(id, e))
+}
+
+impl Storage for SqliteStorage {
+    fn get<T: Entity>(&self, id: &T::Id) -> Result<Option<T>, StorageError> {
+        get(&self.connection, id)
+    }
+
+    fn put<T: Entity>(&mut self, entity: &T) -> Result<(), StorageError> {
+        put(&self.connection, entity)
+    }
+
+    fn list<T: Entity>(&self) -> Result<Vec<T>, StorageError> {
+        list(&self.connection)
+    }
+
+    fn delete<T: Entity>(&mut self, id: &T::Id) -> Result<bool, StorageError> {
+        delete::<T>(&self.connection, id)
+    }
+
+    fn children<T: Child>(
+        &self,
+        parent: &<T::Parent as Entity>::Id,
+    ) -> Result<Vec<T>, StorageError> {
+        children(&self.connection, parent)
+    }
+}
+
+impl Storage for SqliteTransaction<'_> {
+    fn get<T: Entity>(&self, id: &T::Id) -> Result<Option<T>, StorageError> {
+        get(&self.transaction, id)
+    }
+
+    fn put<T: Entity>(&mut self, entity: &T) -> Result<(), StorageError> {
+        put(&self.transaction, entity)
+    }
+
+    fn list<T: Entity>(&self) -> Result<Vec<T>, StorageError> {
+        list(&self.transaction)
+    }
+
+    fn delete<T: Entity>(&mut self, id: &T::Id) -> Result<bool, StorageError> {
+        delete::<T>(&self.transaction, id)
+    }
+
+    fn children<T: Child>(
+        &self,
+        parent: &<T::Parent as Entity>::Id,
+    ) -> Result<Vec<T>, StorageError> {
+        children(&self.transaction, parent)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_conformance() {
+        let mut storage = super::SqliteStorage::open_in_memory().unwrap();
+        super::super::conformance::check(&mut storage);
+        storage
+            .transaction(|transaction| {
+                super::super::conformance::check(transaction);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_schema_migrations() {
+        use super::{SqliteStorage, MIGRATIONS};
+        use crate::storage::StorageError;
+
+        let file = std::env::temp_dir().join(format!("{}.sqlite", uuid::Uuid::new_v4()));
+        let storage = SqliteStorage::open(&file).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), MIGRATIONS.len() as u32);
+        drop(storage);
+
+        // Reopening leaves a current database alone, and a newer one is refused.
+        let storage = SqliteStorage::open(&file).unwrap();
+        storage
+            .connection
+            .pragma_update(None, "user_version", 99)
+            .unwrap();
+        drop(storage);
+        assert!(matches!(
+            SqliteStorage::open(&file),
+            Err(StorageError::SchemaVersion { found: 99, .. })
+        ));
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_move_location() {
+        use super::SqliteStorage;
+        use crate::{
+            id::{LocationId, MapId, WorldId},
+            storage::{Storage, StorageError},
+            world::{
+                location::Location,
+                map::{Map, MapCoordinates},
+            },
+        };
+
+        let world_id = WorldId::new();
+        let map = |name: &str| {
+            Map::new(
+                MapId::new(),
+                name.to_string(),
+                String::new(),
+                world_id,
+                4,
+                4,
+            )
+        };
+        let mut from = map("Surface");
+        let to = map("Underdark");
+        let location = Location {
+            id: LocationId::new(),
+            name: String::from("Blingdenstone"),
+            description: String::new(),
+            world_id,
+            map_coordinates: MapCoordinates::new(from.id, 1, 1),
+            npcs: Vec::new(),
+        };
+        from.locations.push(location.clone());
+
+        let mut storage = SqliteStorage::open_in_memory().unwrap();
+        storage.put(&from).unwrap();
+        storage.put(&to).unwrap();
+        storage.put(&location).unwrap();
+
+        storage.move_location(&location.id, &to.id, 2, 3).unwrap();
+        let moved = storage.get::<Location>(&location.id).unwrap().unwrap();
+        assert_eq!(moved.map_coordinates.map_id, to.id);
+        assert_eq!(moved.map_coordinates.y, 3);
+        assert!(storage
+            .get::<Map>(&from.id)
+            .unwrap()
+            .unwrap()
+            .locations
+            .is_empty());
+        assert_eq!(
+            storage.get::<Map>(&to.id).unwrap().unwrap().locations[0].name,
+            "Blingdenstone"
+        );
+        assert_eq!(storage.children::<Location>(&to.id).unwrap().len(), 1);
+        assert!(storage.children::<Location>(&from.id).unwrap().is_empty());
+
+        // Moving to a map that is not stored changes nothing.
+        let error = storage
+            .move_location(&location.id, &MapId::new(), 0, 0)
+            .unwrap_err();
+        assert!(matches!(error, StorageError::Missing { kind: "map", .. }));
+        let unchanged = storage.get::<Location>(&location.id).unwrap().unwrap();
+        assert_eq!(unchanged.map_coordinates.map_id, to.id);
+
+        // A failing transaction is rolled back as a whole.
+        let result: Result<(), _> = storage.transaction(|transaction| {
+            transaction.delete::<Location>(&location.id)?;
+            Err(StorageError::missing::<Map>("nowhere"))
+        });
+        assert!(result.is_err());
+        assert!(storage.get::<Location>(&location.id).unwrap().is_some());
+    }
+}
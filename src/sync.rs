@@ -0,0 +1,697 @@
+//! The message protocol of a live table, where the people playing a campaign share dice rolls,
+//! combat turns, hit points, token positions and session notes as they happen.
+//!
+//! Clients send [`ClientMessage`]s and the server answers with [`ServerMessage`]s. The server
+//! is the authority on the [`TableState`]: it checks each requested [`Change`], numbers the
+//! accepted ones and sends them to every client as an [`Update`]. A client that notices a gap
+//! in the numbers, for example after reconnecting, asks to be brought up to date and receives
+//! either the missed updates or a [`Snapshot`] of the whole state.
+//!
+//! [`Server`] and [`Client`] only turn messages into other messages, so they can run over any
+//! transport. [`loopback::Loopback`] connects them in memory for tests.
+
+use core::fmt;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crdt::{CrdtError, SharedSession, Text, TextOp},
+    id::{CampaignId, CreatureId, NoteId, SessionId},
+    world::{
+        campaign::session::{Note, Session},
+        map::MapCoordinates,
+    },
+    DieStat,
+};
+
+pub mod loopback;
+
+/// Identifies a connection to the server. It is also the replica id of the client's text edits.
+pub type ClientId = u32;
+
+/// The replica id of text the server adds to the table itself.
+pub const SERVER: ClientId = 0;
+
+/// How many dice rolls the table keeps for people who join later.
+pub const ROLL_LOG: usize = 20;
+
+/// How many updates the server keeps to replay to clients that fell behind.
+pub const UPDATE_LOG: usize = 256;
+
+/// Someone seated at the table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    /// The player character they play, or `None` for the game master and spectators.
+    pub player_id: Option<CreatureId>,
+}
+
+impl Member {
+    pub fn new(name: String, player_id: Option<CreatureId>) -> Self {
+        Self { name, player_id }
+    }
+}
+
+/// The dice someone rolled and what each die came up.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Roll {
+    pub dice: DieStat,
+    pub results: Vec<i32>,
+}
+
+impl Roll {
+    pub fn new(dice: DieStat, results: Vec<i32>) -> Self {
+        Self { dice, results }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.results
+            .iter()
+            .fold(self.dice.extra, |total, x| total.saturating_add(*x))
+    }
+}
+
+/// Whose turn it is in combat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub round: u32,
+    pub creature_id: CreatureId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum NoteField {
+    Name,
+    Description,
+}
+
+/// A change to the table. Clients request every change except [`Change::Joined`] and
+/// [`Change::Left`], which the server makes as people come and go.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Change {
+    Joined(Member),
+    Left,
+    DiceRolled(Roll),
+    TurnChanged(Turn),
+    HitPointsChanged {
+        creature_id: CreatureId,
+        hit_points: i32,
+    },
+    TokenMoved {
+        creature_id: CreatureId,
+        coordinates: MapCoordinates,
+    },
+    NoteAdded {
+        session_id: SessionId,
+        note: Note,
+    },
+    /// Edits the name or description of a note, as made with the author's replica id.
+    NoteEdited {
+        session_id: SessionId,
+        note_id: NoteId,
+        field: NoteField,
+        operations: Vec<TextOp>,
+    },
+}
+
+/// Everything the table shares.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableState {
+    pub campaign_id: CampaignId,
+    pub members: BTreeMap<ClientId, Member>,
+    /// The latest dice rolls, oldest first, with who rolled them.
+    pub rolls: VecDeque<(ClientId, Roll)>,
+    pub turn: Option<Turn>,
+    /// The current hit points of the creatures in play.
+    pub hit_points: BTreeMap<CreatureId, i32>,
+    pub tokens: BTreeMap<CreatureId, MapCoordinates>,
+    pub sessions: BTreeMap<SessionId, SharedSession>,
+}
+
+impl TableState {
+    pub fn new(campaign_id: CampaignId) -> Self {
+        Self {
+            campaign_id,
+            members: BTreeMap::new(),
+            rolls: VecDeque::new(),
+            turn: None,
+            hit_points: BTreeMap::new(),
+            tokens: BTreeMap::new(),
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    /// Shares the notes, plan and recap of a session with the table.
+    pub fn add_session(&mut self, session: &Session) {
+        self.sessions
+            .insert(session.id, SharedSession::from_session(SERVER, session));
+    }
+
+    /// Applies a change made by `author`, or leaves the state as it was if the change is not
+    /// valid.
+    pub fn apply(&mut self, author: ClientId, change: &Change) -> Result<(), SyncError> {
+        match change {
+            Change::Joined(member) => {
+                self.members.insert(author, member.clone());
+            }
+            Change::Left => {
+                self.members.remove(&author);
+            }
+            Change::DiceRolled(roll) => {
+                let sides = roll.dice.die_type.to_i32();
+                if !(0..=DieStat::MAX_COUNT).contains(&roll.dice.die_count)
+                    || !(-DieStat::MAX_EXTRA..=DieStat::MAX_EXTRA).contains(&roll.dice.extra)
+                    || roll.results.len() != roll.dice.die_count as usize
+                    || roll.results.iter().any(|x| !(1..=sides).contains(x))
+                {
+                    return Err(SyncError::InvalidRoll);
+                }
+                self.rolls.push_back((author, roll.clone()));
+                if self.rolls.len() > ROLL_LOG {
+                    self.rolls.pop_front();
+                }
+            }
+            Change::TurnChanged(turn) => {
+                if let Some(current) = &self.turn {
+                    if turn.round < current.round {
+                        return Err(SyncError::TurnRewound {
+                            round: turn.round,
+                            current: current.round,
+                        });
+                    }
+                }
+                self.turn = Some(turn.clone());
+            }
+            Change::HitPointsChanged {
+                creature_id,
+                hit_points,
+            } => {
+                self.hit_points.insert(*creature_id, *hit_points);
+            }
+            Change::TokenMoved {
+                creature_id,
+                coordinates,
+            } => {
+                self.tokens.insert(*creature_id, coordinates.clone());
+            }
+            Change::NoteAdded { session_id, note } => {
+                self.sessions
+                    .get_mut(session_id)
+                    .ok_or(SyncError::UnknownSession(*session_id))?
+                    .notes
+                    .add(author, note);
+            }
+            Change::NoteEdited {
+                session_id,
+                note_id,
+                field,
+                operations,
+            } => {
+                let text = self.note_text(session_id, note_id, *field)?;
+                if let Some(TextOp::Insert { id, .. }) = operations
+                    .iter()
+                    .find(|x| matches!(x, TextOp::Insert { id, .. } if id.replica != author))
+                {
+                    return Err(SyncError::ForeignEdit(id.replica));
+                }
+                let mut edited = text.clone();
+                for operation in operations {
+                    edited.apply(operation)?;
+                }
+                *text = edited;
+            }
+        }
+        Ok(())
+    }
+
+    /// The name or description of a note, for editing.
+    pub fn note_text(
+        &mut self,
+        session_id: &SessionId,
+        note_id: &NoteId,
+        field: NoteField,
+    ) -> Result<&mut Text, SyncError> {
+        let note = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or(SyncError::UnknownSession(*session_id))?
+            .notes
+            .get_mut(note_id)
+            .ok_or(SyncError::UnknownNote(*note_id))?;
+        Ok(match field {
+            NoteField::Name => &mut note.name,
+            NoteField::Description => &mut note.description,
+        })
+    }
+}
+
+/// The state of the table after the update with the given sequence number.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    pub sequence: u64,
+    pub state: TableState,
+}
+
+/// An accepted change, numbered in the order the server applied it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Update {
+    pub sequence: u64,
+    pub author: ClientId,
+    pub change: Change,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ClientMessage {
+    Join {
+        campaign_id: CampaignId,
+        member: Member,
+    },
+    /// Requests a change. The sequence number is chosen by the client and only used to tell
+    /// it which request was rejected.
+    Request {
+        sequence: u64,
+        change: Change,
+    },
+    /// Asks for the updates after the given sequence number.
+    Resync {
+        since: u64,
+    },
+    Leave,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ServerMessage {
+    /// Answers a join with the client's id and the state of the table, including the client.
+    Welcome {
+        client_id: ClientId,
+        snapshot: Snapshot,
+    },
+    Update(Update),
+    /// Answers a resync when the missed updates are no longer kept.
+    Snapshot(Snapshot),
+    /// A message was not accepted. The sequence number is that of the rejected request.
+    Rejected {
+        sequence: Option<u64>,
+        error: SyncError,
+    },
+}
+
+/// A message for one client.
+#[derive(Debug, Clone)]
+pub struct Outgoing {
+    pub to: ClientId,
+    pub message: ServerMessage,
+}
+
+/// The server side of a table. The transport assigns each connection an id with
+/// [`Server::connect`] and passes the client's messages to [`Server::handle`].
+#[derive(Debug, Clone)]
+pub struct Server {
+    state: TableState,
+    sequence: u64,
+    log: VecDeque<Update>,
+    next_client: ClientId,
+    joined: BTreeSet<ClientId>,
+}
+
+impl Server {
+    pub fn new(state: TableState) -> Self {
+        Self {
+            state,
+            sequence: 0,
+            log: VecDeque::new(),
+            next_client: SERVER + 1,
+            joined: BTreeSet::new(),
+        }
+    }
+
+    pub fn state(&self) -> &TableState {
+        &self.state
+    }
+
+    /// The sequence number of the latest update.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn connect(&mut self) -> ClientId {
+        let client = self.next_client;
+        self.next_client += 1;
+        client
+    }
+
+    /// Removes a client whose connection closed, telling the others if it had joined.
+    pub fn disconnect(&mut self, client: ClientId) -> Vec<Outgoing> {
+        match self.joined.remove(&client) {
+            true => self.commit(client, Change::Left),
+            false => Vec::new(),
+        }
+    }
+
+    pub fn handle(&mut self, client: ClientId, message: ClientMessage) -> Vec<Outgoing> {
+        let joined = self.joined.contains(&client);
+        match message {
+            ClientMessage::Join {
+                campaign_id,
+                member,
+            } => {
+                if joined {
+                    return reject(client, None, SyncError::AlreadyJoined);
+                }
+                if campaign_id != self.state.campaign_id {
+                    let error = SyncError::WrongCampaign {
+                        expected: self.state.campaign_id,
+                        found: campaign_id,
+                    };
+                    return reject(client, None, error);
+                }
+                let mut outgoing = self.commit(client, Change::Joined(member));
+                self.joined.insert(client);
+                outgoing.push(Outgoing {
+                    to: client,
+                    message: ServerMessage::Welcome {
+                        client_id: client,
+                        snapshot: self.snapshot(),
+                    },
+                });
+                outgoing
+            }
+            _ if !joined => reject(client, None, SyncError::NotJoined),
+            ClientMessage::Request { sequence, change } => {
+                if matches!(change, Change::Joined(_) | Change::Left) {
+                    return reject(client, Some(sequence), SyncError::Forbidden);
+                }
+                if let Err(e) = self.state.apply(client, &change) {
+                    return reject(client, Some(sequence), e);
+                }
+                self.commit(client, change)
+            }
+            ClientMessage::Resync { since } => {
+                let kept = self.log.front().map_or(self.sequence + 1, |x| x.sequence);
+                if since > self.sequence || since + 1 < kept {
+                    return vec![Outgoing {
+                        to: client,
+                        message: ServerMessage::Snapshot(self.snapshot()),
+                    }];
+                }
+                self.log
+                    .iter()
+                    .filter(|x| x.sequence > since)
+                    .map(|x| Outgoing {
+                        to: client,
+                        message: ServerMessage::Update(x.clone()),
+                    })
+                    .collect()
+            }
+            ClientMessage::Leave => self.disconnect(client),
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            sequence: self.sequence,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Numbers a change that has been applied and sends it to everyone at the table.
+    fn commit(&mut self, author: ClientId, change: Change) -> Vec<Outgoing> {
+        if matches!(change, Change::Joined(_) | Change::Left) {
+            // Joining and leaving cannot fail.
+            let _ = self.state.apply(author, &change);
+        }
+        self.sequence += 1;
+        let update = Update {
+            sequence: self.sequence,
+            author,
+            change,
+        };
+        self.log.push_back(update.clone());
+        if self.log.len() > UPDATE_LOG {
+            self.log.pop_front();
+        }
+        self.joined
+            .iter()
+            .map(|x| Outgoing {
+                to: *x,
+                message: ServerMessage::Update(update.clone()),
+            })
+            .collect()
+    }
+}
+
+fn reject(client: ClientId, sequence: Option<u64>, error: SyncError) -> Vec<Outgoing> {
+    vec![Outgoing {
+        to: client,
+        message: ServerMessage::Rejected { sequence, error },
+    }]
+}
+
+/// The client side of a table. Messages from the server are passed to [`Client::handle`],
+/// which keeps the local copy of the state current.
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    id: Option<ClientId>,
+    state: Option<TableState>,
+    /// The sequence number of the latest update applied to the state.
+    applied: u64,
+    /// The sequence number of the latest request.
+    requested: u64,
+    resyncing: bool,
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id the server gave this client, once it has joined.
+    pub fn id(&self) -> Option<ClientId> {
+        self.id
+    }
+
+    pub fn state(&self) -> Option<&TableState> {
+        self.state.as_ref()
+    }
+
+    /// The sequence number of the latest update applied to the state.
+    pub fn sequence(&self) -> u64 {
+        self.applied
+    }
+
+    pub fn join(&self, campaign_id: CampaignId, member: Member) -> ClientMessage {
+        ClientMessage::Join {
+            campaign_id,
+            member,
+        }
+    }
+
+    /// Requests a change. It shows in the state once the server has accepted it.
+    pub fn request(&mut self, change: Change) -> ClientMessage {
+        self.requested += 1;
+        ClientMessage::Request {
+            sequence: self.requested,
+            change,
+        }
+    }
+
+    /// Edits the name or description of a note. The edit shows in the state at once, and the
+    /// returned request shares it with the table.
+    pub fn edit_note<F>(
+        &mut self,
+        session_id: SessionId,
+        note_id: NoteId,
+        field: NoteField,
+        edit: F,
+    ) -> Result<ClientMessage, SyncError>
+    where
+        F: FnOnce(&mut Text, ClientId) -> Vec<TextOp>,
+    {
+        let (Some(id), Some(state)) = (self.id, self.state.as_mut()) else {
+            return Err(SyncError::NotJoined);
+        };
+        let operations = edit(state.note_text(&session_id, &note_id, field)?, id);
+        Ok(self.request(Change::NoteEdited {
+            session_id,
+            note_id,
+            field,
+            operations,
+        }))
+    }
+
+    /// Applies a message from the server, returning a message to send back if the client fell
+    /// behind, or the error of a rejected request.
+    pub fn handle(&mut self, message: ServerMessage) -> Result<Option<ClientMessage>, SyncError> {
+        match message {
+            ServerMessage::Welcome {
+                client_id,
+                snapshot,
+            } => {
+                self.id = Some(client_id);
+                self.restore(snapshot);
+            }
+            ServerMessage::Snapshot(snapshot) => self.restore(snapshot),
+            ServerMessage::Update(update) => {
+                let Some(state) = self.state.as_mut() else {
+                    // Updates that arrive before the welcome are part of its snapshot.
+                    return Ok(None);
+                };
+                if update.sequence <= self.applied {
+                    return Ok(None);
+                }
+                if update.sequence > self.applied + 1 {
+                    return Ok(match self.resyncing {
+                        true => None,
+                        false => {
+                            self.resyncing = true;
+                            Some(ClientMessage::Resync {
+                                since: self.applied,
+                            })
+                        }
+                    });
+                }
+                state.apply(update.author, &update.change)?;
+                self.applied = update.sequence;
+                self.resyncing = false;
+            }
+            ServerMessage::Rejected { error, .. } => return Err(error),
+        }
+        Ok(None)
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.applied = snapshot.sequence;
+        self.state = Some(snapshot.state);
+        self.resyncing = false;
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SyncError {
+    NotJoined,
+    AlreadyJoined,
+    WrongCampaign {
+        expected: CampaignId,
+        found: CampaignId,
+    },
+    /// Only the server makes this change.
+    Forbidden,
+    /// The results do not match the dice rolled.
+    InvalidRoll,
+    TurnRewound {
+        round: u32,
+        current: u32,
+    },
+    UnknownSession(SessionId),
+    UnknownNote(NoteId),
+    /// A text edit carries the replica id of another client.
+    ForeignEdit(ClientId),
+    Text(CrdtError),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            SyncError::NotJoined => write!(f, "not joined to the table"),
+            SyncError::AlreadyJoined => write!(f, "already joined to the table"),
+            SyncError::WrongCampaign { expected, found } => {
+                write!(f, "the table plays campaign {}, not {}", expected, found)
+            }
+            SyncError::Forbidden => write!(f, "only the server can make this change"),
+            SyncError::InvalidRoll => write!(f, "the results do not match the dice"),
+            SyncError::TurnRewound { round, current } => write!(
+                f,
+                "cannot go back to round {} from round {}",
+                round, current
+            ),
+            SyncError::UnknownSession(id) => write!(f, "no session with id {}", id),
+            SyncError::UnknownNote(id) => write!(f, "no note with id {}", id),
+            SyncError::ForeignEdit(replica) => {
+                write!(f, "text edit made as client {}", replica)
+            }
+            SyncError::Text(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<CrdtError> for SyncError {
+    fn from(e: CrdtError) -> Self {
+        SyncError::Text(e)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_table_state_rejects_invalid_changes() {
+        use super::{Change, Roll, SyncError, TableState, Turn};
+        use crate::{id::CampaignId, id::CreatureId, Die, DieStat};
+
+        let mut state = TableState::new(CampaignId::new());
+        let dice = DieStat {
+            die_count: 2,
+            die_type: Die::D6,
+            extra: 3,
+        };
+        let roll = Roll::new(dice.clone(), vec![4, 6]);
+        assert_eq!(roll.total(), 13);
+        state.apply(1, &Change::DiceRolled(roll)).unwrap();
+        for results in [vec![4], vec![0, 6], vec![4, 7]] {
+            let roll = Change::DiceRolled(Roll::new(dice.clone(), results));
+            assert_eq!(state.apply(1, &roll), Err(SyncError::InvalidRoll));
+        }
+        let huge = DieStat {
+            die_count: 1,
+            die_type: Die::D6,
+            extra: i32::MAX,
+        };
+        assert_eq!(Roll::new(huge.clone(), vec![3]).total(), i32::MAX);
+        let roll = Change::DiceRolled(Roll::new(huge, vec![3]));
+        assert_eq!(state.apply(1, &roll), Err(SyncError::InvalidRoll));
+        assert_eq!(state.rolls.len(), 1);
+
+        let creature_id = CreatureId::new();
+        let turn = |round| Change::TurnChanged(Turn { round, creature_id });
+        state.apply(1, &turn(2)).unwrap();
+        assert_eq!(
+            state.apply(1, &turn(1)),
+            Err(SyncError::TurnRewound {
+                round: 1,
+                current: 2
+            })
+        );
+
+        // Messages are plain JSON, so clients need not be written in Rust.
+        let json = serde_json::to_value(turn(3)).unwrap();
+        let expected = serde_json::json!({
+            "TurnChanged": {"round": 3, "creature_id": creature_id.to_string()}
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_server_resync_from_any_sequence() {
+        use super::{ClientMessage, Member, Server, ServerMessage, TableState};
+        use crate::id::CampaignId;
+
+        let campaign_id = CampaignId::new();
+        let mut server = Server::new(TableState::new(campaign_id));
+        let client = server.connect();
+        let member = Member::new(String::from("Alice"), None);
+        server.handle(
+            client,
+            ClientMessage::Join {
+                campaign_id,
+                member,
+            },
+        );
+
+        // A client claiming to be ahead of the server gets a snapshot rather than a panic.
+        let outgoing = server.handle(client, ClientMessage::Resync { since: u64::MAX });
+        assert!(matches!(
+            &outgoing[..],
+            [x] if x.to == client && matches!(x.message, ServerMessage::Snapshot(_))
+        ));
+    }
+}
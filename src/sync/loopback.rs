@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{Client, ClientId, ClientMessage, Member, Outgoing, Server, ServerMessage, SyncError};
+
+/// A server and its clients connected in memory, for testing code built on the protocol.
+///
+/// Messages are queued rather than delivered at once, so tests can interleave requests from
+/// several clients and drop messages to simulate a bad connection.
+#[derive(Debug, Clone)]
+pub struct Loopback {
+    pub server: Server,
+    pub clients: BTreeMap<ClientId, Client>,
+    to_server: VecDeque<(ClientId, ClientMessage)>,
+    to_clients: VecDeque<Outgoing>,
+    /// Errors of rejected requests, with the client that made them.
+    pub rejected: Vec<(ClientId, SyncError)>,
+}
+
+impl Loopback {
+    pub fn new(server: Server) -> Self {
+        Self {
+            server,
+            clients: BTreeMap::new(),
+            to_server: VecDeque::new(),
+            to_clients: VecDeque::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    /// Connects a new client and queues its message to join the table.
+    pub fn join(&mut self, member: Member) -> ClientId {
+        let id = self.server.connect();
+        let client = Client::new();
+        let join = client.join(self.server.state().campaign_id, member);
+        self.clients.insert(id, client);
+        self.send(id, join);
+        id
+    }
+
+    /// Queues a message from a client to the server.
+    pub fn send(&mut self, from: ClientId, message: ClientMessage) {
+        self.to_server.push_back((from, message));
+    }
+
+    /// Closes the connection of a client.
+    pub fn disconnect(&mut self, id: ClientId) {
+        self.clients.remove(&id);
+        let outgoing = self.server.disconnect(id);
+        self.to_clients.extend(outgoing);
+    }
+
+    /// Delivers queued messages until there are none left.
+    pub fn run(&mut self) {
+        self.run_dropping(|_, _| false);
+    }
+
+    /// Delivers queued messages until there are none left, losing the messages to clients for
+    /// which `drop` returns true.
+    pub fn run_dropping<F>(&mut self, mut drop: F)
+    where
+        F: FnMut(ClientId, &ServerMessage) -> bool,
+    {
+        loop {
+            if let Some((from, message)) = self.to_server.pop_front() {
+                let outgoing = self.server.handle(from, message);
+                self.to_clients.extend(outgoing);
+            } else if let Some(Outgoing { to, message }) = self.to_clients.pop_front() {
+                let Some(client) = self.clients.get_mut(&to) else {
+                    continue;
+                };
+                if drop(to, &message) {
+                    continue;
+                }
+                match client.handle(message) {
+                    Ok(Some(reply)) => self.to_server.push_back((to, reply)),
+                    Ok(None) => {}
+                    Err(e) => self.rejected.push((to, e)),
+                }
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_clients_converge() {
+        use super::Loopback;
+        use crate::{
+            id::{CampaignId, CreatureId, MapId},
+            sync::{Change, Member, NoteField, Server, ServerMessage, SyncError, TableState},
+            world::{
+                campaign::session::{Note, Session},
+                map::MapCoordinates,
+            },
+        };
+
+        let campaign_id = CampaignId::new();
+        let mut session = Session::new(String::from("Session 1"), String::new(), campaign_id);
+        session.notes = vec![Note::new(String::from("Loot"), String::from("A key"))];
+        let (session_id, note_id) = (session.id, session.notes[0].id);
+        let mut state = TableState::new(campaign_id);
+        state.add_session(&session);
+        let mut table = Loopback::new(Server::new(state));
+
+        let dm = table.join(Member::new(String::from("Dungeon Master"), None));
+        let hero_id = CreatureId::new();
+        let hero = table.join(Member::new(String::from("Alice"), Some(hero_id)));
+        table.run();
+
+        let request = table
+            .clients
+            .get_mut(&dm)
+            .unwrap()
+            .request(Change::HitPointsChanged {
+                creature_id: hero_id,
+                hit_points: 7,
+            });
+        table.send(dm, request);
+        let request = table
+            .clients
+            .get_mut(&hero)
+            .unwrap()
+            .request(Change::TokenMoved {
+                creature_id: hero_id,
+                coordinates: MapCoordinates::new(MapId::new(), 3, 4),
+            });
+        table.send(hero, request);
+        // Both edit the description of the note at the same time.
+        for (id, text) in [(dm, " (silver)"), (hero, " and a map")] {
+            let client = table.clients.get_mut(&id).unwrap();
+            let request = client
+                .edit_note(session_id, note_id, NoteField::Description, |x, replica| {
+                    let end = x.len();
+                    x.insert(replica, end, text)
+                })
+                .unwrap();
+            table.send(id, request);
+        }
+        let request = table.clients.get_mut(&hero).unwrap().request(Change::Left);
+        table.send(hero, request);
+
+        // The hero misses the updates to the hit points and the note, and catches up by resync.
+        let mut dropped = 0;
+        table.run_dropping(|to, message| {
+            let lose = to == hero && matches!(message, ServerMessage::Update(_)) && dropped < 2;
+            dropped += lose as u32;
+            lose
+        });
+        assert_eq!(table.rejected, vec![(hero, SyncError::Forbidden)]);
+
+        let server = table.server.state();
+        assert_eq!(server.hit_points[&hero_id], 7);
+        assert_eq!(server.tokens[&hero_id].x, 3);
+        for client in table.clients.values() {
+            assert_eq!(client.sequence(), table.server.sequence());
+            let state = client.state().unwrap();
+            assert_eq!(state.hit_points, server.hit_points);
+            assert_eq!(state.members, server.members);
+            assert_eq!(state.sessions, server.sessions);
+        }
+        let mut session = session.clone();
+        server.sessions[&session_id].update(&mut session);
+        assert_eq!(session.notes[0].description, "A key and a map (silver)");
+
+        table.disconnect(hero);
+        table.run();
+        let members = &table.clients[&dm].state().unwrap().members;
+        assert_eq!(members.keys().collect::<Vec<_>>(), vec![&dm]);
+    }
+}
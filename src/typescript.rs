@@ -0,0 +1,83 @@
+//! TypeScript declarations for the serialized types, available with the `typescript` feature.
+//!
+//! The declarations follow the serde attributes of every type, so externally tagged enums such
+//! as `ActionType` become unions of single-key objects and unit variants become string literals.
+//! The generated file is checked in at `bindings/gamemstr.d.ts`; regenerate it with
+//! `cargo run --example dump_typescript --features typescript`.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use ts_rs::{TypeVisitor, TS};
+
+use crate::{
+    action::Action,
+    creature::Creature,
+    item::Item,
+    spell::Spell,
+    world::{
+        campaign::{player::Player, session::Session, Campaign},
+        map::Map,
+        World,
+    },
+};
+
+/// Collects the declaration of every type reachable from the visited ones.
+#[derive(Default)]
+struct Declarations(BTreeMap<String, String>);
+
+impl TypeVisitor for Declarations {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        if T::output_path().is_none() || self.0.contains_key(&T::ident()) {
+            return;
+        }
+        self.0.insert(T::ident(), format!("export {}", T::decl()));
+        T::visit_dependencies(self);
+    }
+}
+
+/// The contents of a `.d.ts` file declaring every serialized type, sorted by name.
+pub fn declarations() -> String {
+    let mut declarations = Declarations::default();
+    declarations.visit::<Action>();
+    declarations.visit::<Campaign>();
+    declarations.visit::<Creature>();
+    declarations.visit::<Item>();
+    declarations.visit::<Map>();
+    declarations.visit::<Player>();
+    declarations.visit::<Session>();
+    declarations.visit::<Spell>();
+    declarations.visit::<World>();
+
+    let mut output = String::from(
+        "// Generated by gamemstr-common with the `typescript` feature. Do not edit.\n",
+    );
+    for declaration in declarations.0.values() {
+        output.push('\n');
+        output.push_str(declaration);
+        output.push('\n');
+    }
+    output
+}
+
+/// Writes the declarations to `gamemstr.d.ts` in `dir`.
+pub fn write_declarations(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("gamemstr.d.ts"), declarations())
+}
+
+mod test {
+    #[test]
+    fn test_declarations_are_current() {
+        let declarations = super::declarations();
+        assert!(
+            declarations == include_str!("../bindings/gamemstr.d.ts"),
+            "bindings/gamemstr.d.ts is out of date, regenerate it with \
+             `cargo run --example dump_typescript --features typescript`"
+        );
+
+        assert!(declarations.contains(
+            "export type ActionType = { \"Attack\": Attack } | { \"Special\": SpecialAction }"
+        ));
+        assert!(declarations.contains("export type MovementSpeed = { \"Walk\": number }"));
+    }
+}
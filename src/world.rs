@@ -1,13 +1,16 @@
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+
+use crate::id::WorldId;
 
 pub mod campaign;
 pub mod location;
 pub mod map;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct World {
-    pub id: String,
+    pub id: WorldId,
     pub name: String,
     pub description: String,
 }
@@ -15,7 +18,7 @@ pub struct World {
 impl World {
     pub fn new(name: String, description: String) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: WorldId::new(),
             name,
             description,
         }
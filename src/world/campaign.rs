@@ -1,28 +1,421 @@
+use core::{fmt, time::Duration};
+
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-use self::player::Player;
+use crate::id::{CampaignId, CreatureId, WorldId};
+
+use self::{player::Player, session::Session};
 
+pub mod history;
 pub mod player;
 pub mod session;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Campaign {
-    pub id: String,
+    pub id: CampaignId,
     pub name: String,
     pub description: String,
-    pub world_id: String,
-    pub players: Option<Vec<Player>>,
+    pub world_id: WorldId,
+    /// The ids of the players in the campaign, each of which refers back by its `campaign_id`.
+    pub player_ids: Vec<CreatureId>,
 }
 
 impl Campaign {
-    pub fn new(name: String, description: String, world_id: String) -> Self {
+    pub fn new(name: String, description: String, world_id: WorldId) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: CampaignId::new(),
             name,
             description,
             world_id,
-            players: None,
+            player_ids: Vec::new(),
+        }
+    }
+
+    /// Adds a player to the campaign, pointing the player at it.
+    pub fn add_player(&mut self, player: &mut Player) {
+        player.set_campaign_id(self.id);
+        if !self.player_ids.contains(player.id()) {
+            self.player_ids.push(*player.id());
+        }
+    }
+
+    pub fn remove_player(&mut self, id: &CreatureId) {
+        self.player_ids.retain(|x| x != id);
+    }
+
+    /// The sessions of this campaign among `sessions`, which may include other campaigns'.
+    pub fn sessions<'a>(&self, sessions: &'a [Session]) -> impl Iterator<Item = &'a Session> {
+        let id = self.id;
+        sessions.iter().filter(move |x| x.campaign_id == id)
+    }
+
+    /// The sessions a player was recorded as absent from.
+    pub fn missed_sessions<'a>(
+        &self,
+        sessions: &'a [Session],
+        player_id: &CreatureId,
+    ) -> Vec<&'a Session> {
+        self.sessions(sessions)
+            .filter(|x| x.missed(player_id))
+            .collect()
+    }
+
+    /// The total length of the sessions that have started and ended.
+    pub fn time_played(&self, sessions: &[Session]) -> Duration {
+        self.sessions(sessions)
+            .filter_map(|x| x.schedule.duration())
+            .fold(Duration::ZERO, Duration::saturating_add)
+    }
+
+    /// What each player of the campaign attended and received, for comparing how experience
+    /// and loot were shared out.
+    pub fn player_reports(&self, sessions: &[Session]) -> Vec<PlayerReport> {
+        self.player_ids
+            .iter()
+            .map(|player_id| {
+                let mut report = PlayerReport {
+                    player_id: *player_id,
+                    attended: 0,
+                    missed: 0,
+                    time_played: Duration::ZERO,
+                    experience: 0,
+                    loot: Vec::new(),
+                };
+                for session in self.sessions(sessions) {
+                    if session.attended(player_id) {
+                        report.attended += 1;
+                        report.time_played = report
+                            .time_played
+                            .saturating_add(session.schedule.duration().unwrap_or_default());
+                    } else if session.missed(player_id) {
+                        report.missed += 1;
+                    }
+                    report.experience = report
+                        .experience
+                        .saturating_add(session.recap.experience_of(player_id));
+                    report.loot.extend(
+                        session
+                            .recap
+                            .loot
+                            .iter()
+                            .filter(|x| x.player_id.as_ref() == Some(player_id))
+                            .map(|x| x.item.clone()),
+                    );
+                }
+                report
+            })
+            .collect()
+    }
+}
+
+/// A player's share of a campaign, as summed up by [`Campaign::player_reports`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerReport {
+    pub player_id: CreatureId,
+    pub attended: usize,
+    pub missed: usize,
+    /// The length of the sessions the player attended.
+    pub time_played: Duration,
+    pub experience: u32,
+    pub loot: Vec<String>,
+}
+
+/// Where [`CampaignGraph::resolve`] looks up the entities a campaign refers to by id. Lookups
+/// return `Ok(None)` for entities that do not exist and an error for ones that could not be
+/// read.
+pub trait CampaignSource {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn campaign(&self, id: &CampaignId) -> Result<Option<Campaign>, Self::Error>;
+    fn player(&self, id: &CreatureId) -> Result<Option<Player>, Self::Error>;
+}
+
+/// A campaign together with its players, as assembled from their references.
+#[derive(Debug, Clone)]
+pub struct CampaignGraph {
+    pub campaign: Campaign,
+    pub players: Vec<Player>,
+}
+
+impl CampaignGraph {
+    /// Looks up a campaign and every player it lists, checking that each of them refers back to
+    /// the campaign.
+    pub fn resolve(source: &impl CampaignSource, id: &CampaignId) -> Result<Self, ResolveError> {
+        let campaign = source
+            .campaign(id)
+            .map_err(ResolveError::source)?
+            .ok_or(ResolveError::MissingCampaign(*id))?;
+        let players = campaign
+            .player_ids
+            .iter()
+            .map(|player_id| {
+                let player = source
+                    .player(player_id)
+                    .map_err(ResolveError::source)?
+                    .ok_or(ResolveError::MissingPlayer {
+                        campaign: *id,
+                        player: *player_id,
+                    })?;
+                if player.campaign_id() != id {
+                    return Err(ResolveError::WrongCampaign {
+                        campaign: *id,
+                        player: *player_id,
+                        found: *player.campaign_id(),
+                    });
+                }
+                Ok(player)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { campaign, players })
+    }
+
+    /// Resolves the campaign a player belongs to.
+    pub fn of_player(source: &impl CampaignSource, player: &Player) -> Result<Self, ResolveError> {
+        Self::resolve(source, player.campaign_id())
+    }
+
+    pub fn player(&self, id: &CreatureId) -> Option<&Player> {
+        self.players.iter().find(|x| x.id() == id)
+    }
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    /// The source failed to look up an entity.
+    Source(Box<dyn std::error::Error + Send + Sync>),
+    MissingCampaign(CampaignId),
+    MissingPlayer {
+        campaign: CampaignId,
+        player: CreatureId,
+    },
+    /// A campaign lists a player whose `campaign_id` names another campaign.
+    WrongCampaign {
+        campaign: CampaignId,
+        player: CreatureId,
+        found: CampaignId,
+    },
+}
+
+impl ResolveError {
+    fn source(e: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ResolveError::Source(Box::new(e))
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            ResolveError::Source(e) => write!(f, "{}", e),
+            ResolveError::MissingCampaign(id) => write!(f, "unknown campaign {}", id),
+            ResolveError::MissingPlayer { campaign, player } => {
+                write!(f, "campaign {} lists unknown player {}", campaign, player)
+            }
+            ResolveError::WrongCampaign {
+                campaign,
+                player,
+                found,
+            } => write!(
+                f,
+                "campaign {} lists player {}, which belongs to campaign {}",
+                campaign, player, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self {
+            ResolveError::Source(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_resolve_campaign_graph() {
+        use std::collections::BTreeMap;
+
+        use super::{Campaign, CampaignGraph, CampaignSource, ResolveError};
+        use crate::{
+            creature::{Creature, CreatureType, Health, MovementSpeed},
+            id::{CampaignId, CreatureId, WorldId},
+            world::campaign::player::{Class, Player, Race},
+            Alignment, DieStat,
+        };
+
+        #[derive(Default)]
+        struct Source {
+            campaigns: BTreeMap<CampaignId, Campaign>,
+            players: BTreeMap<CreatureId, Player>,
+        }
+
+        impl CampaignSource for Source {
+            type Error = core::convert::Infallible;
+
+            fn campaign(&self, id: &CampaignId) -> Result<Option<Campaign>, Self::Error> {
+                Ok(self.campaigns.get(id).cloned())
+            }
+
+            fn player(&self, id: &CreatureId) -> Result<Option<Player>, Self::Error> {
+                Ok(self.players.get(id).cloned())
+            }
+        }
+
+        let player = |name: &str| {
+            let creature = Creature::new(
+                CreatureId::new(),
+                name.to_string(),
+                CreatureType::Player,
+                Alignment::TrueNeutral,
+                10,
+                Health {
+                    health: DieStat::default(),
+                },
+                MovementSpeed::Walk(30),
+                Vec::new(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                String::new(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
+            Player::try_new(
+                creature,
+                Race::Human,
+                Class::Fighter,
+                1,
+                0,
+                CampaignId::new(),
+            )
+            .unwrap()
+        };
+
+        let mut campaign = Campaign::new(String::from("Curse"), String::new(), WorldId::new());
+        let mut ada = player("Ada");
+        let mut bo = player("Bo");
+        campaign.add_player(&mut ada);
+        campaign.add_player(&mut bo);
+        campaign.add_player(&mut bo);
+        assert_eq!(campaign.player_ids, vec![*ada.id(), *bo.id()]);
+        assert_eq!(bo.campaign_id(), &campaign.id);
+
+        let mut source = Source::default();
+        source.campaigns.insert(campaign.id, campaign.clone());
+        source.players.insert(*ada.id(), ada.clone());
+        let missing = CampaignGraph::resolve(&source, &campaign.id).unwrap_err();
+        assert!(matches!(
+            missing,
+            ResolveError::MissingPlayer { campaign: c, player: p } if c == campaign.id && p == *bo.id()
+        ));
+
+        source.players.insert(*bo.id(), bo.clone());
+        let graph = CampaignGraph::of_player(&source, &ada).unwrap();
+        assert_eq!(graph.campaign.name, "Curse");
+        assert_eq!(graph.player(bo.id()).unwrap().name(), "Bo");
+
+        bo.set_campaign_id(CampaignId::new());
+        source.players.insert(*bo.id(), bo.clone());
+        assert!(matches!(
+            CampaignGraph::resolve(&source, &campaign.id),
+            Err(ResolveError::WrongCampaign { .. })
+        ));
+        assert!(matches!(
+            CampaignGraph::of_player(&source, &bo),
+            Err(ResolveError::MissingCampaign(_))
+        ));
+    }
+
+    #[test]
+    fn test_attendance_queries() {
+        use super::{
+            session::{Attendance, Award, Loot, Schedule, Session},
+            Campaign,
+        };
+        use crate::id::{CampaignId, CreatureId, WorldId};
+        use core::time::Duration;
+
+        let mut campaign = Campaign::new(String::from("Curse"), String::new(), WorldId::new());
+        let (ada, bo) = (CreatureId::new(), CreatureId::new());
+        campaign.player_ids = vec![ada, bo];
+        let hour = 3600;
+        let session = |start: u64, hours: u64, absent: &CreatureId| {
+            let mut session = Session::new(String::new(), String::new(), campaign.id);
+            session.schedule.set_started(start).unwrap();
+            session.schedule.set_ended(start + hours * hour).unwrap();
+            for player in [&ada, &bo] {
+                let attendance = match player == absent {
+                    true => Attendance::Absent,
+                    false => Attendance::Present,
+                };
+                session.attendance.insert(*player, attendance);
+            }
+            session
+        };
+        let mut sessions = vec![
+            session(0, 3, &bo),
+            session(7 * 24 * hour, 4, &ada),
+            Session::new(String::new(), String::new(), campaign.id),
+            session(0, 10, &bo),
+        ];
+        sessions[3].campaign_id = CampaignId::new();
+        sessions[1].recap.experience.push(Award {
+            player_id: bo,
+            experience: 450,
+        });
+        sessions[1].recap.loot.push(Loot {
+            item: String::from("Wand of Web"),
+            player_id: Some(bo),
+        });
+
+        let missed = campaign.missed_sessions(&sessions, &ada);
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].id, sessions[1].id);
+        assert!(!sessions[2].is_held());
+        assert_eq!(
+            campaign.time_played(&sessions),
+            Duration::from_secs(7 * hour)
+        );
+
+        let reports = campaign.player_reports(&sessions);
+        assert_eq!((reports[0].attended, reports[0].missed), (1, 1));
+        assert_eq!(reports[0].time_played, Duration::from_secs(3 * hour));
+        assert_eq!(reports[1].experience, 450);
+        assert_eq!(reports[1].loot, vec![String::from("Wand of Web")]);
+
+        // Stored sessions may hold the latest times and any awards, which must not overflow
+        // the totals.
+        for session in &mut sessions {
+            session.schedule.started = Some(0);
+            session.schedule.ended = Some(Schedule::MAX_TIME);
+            session.recap.experience.push(Award {
+                player_id: bo,
+                experience: u32::MAX,
+            });
         }
+        assert_eq!(
+            campaign.time_played(&sessions),
+            Duration::from_secs(3 * Schedule::MAX_TIME)
+        );
+        assert_eq!(campaign.player_reports(&sessions)[1].experience, u32::MAX);
+        assert!(sessions[0].schedule.set_ended(u64::MAX).is_err());
+        assert!(sessions[0].schedule.set_planned(Schedule::MAX_TIME).is_ok());
+        let stored = r#"{"started":0,"ended":18446744073709551615,"timezone":""}"#;
+        assert!(serde_json::from_str::<Schedule>(stored)
+            .unwrap_err()
+            .to_string()
+            .contains("after the year 9999"));
     }
 }
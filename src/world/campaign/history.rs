@@ -0,0 +1,574 @@
+//! The change history of a campaign, kept as a log of typed events.
+//!
+//! Every edit to a campaign, its sessions, their notes and its players is recorded as an
+//! [`Entry`] naming who made it and when. The current state is the result of applying the
+//! applied entries in order, so any earlier state can be rebuilt from the log, and undo and
+//! redo move through it. Recording a new change after an undo discards the undone entries.
+
+use core::fmt;
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    creature::Health,
+    error::ValidationError,
+    id::{CreatureId, NoteId, SessionId},
+};
+
+use super::{
+    player::Player,
+    session::{Note, Session},
+    Campaign,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub enum Event {
+    /// Starts the log of a campaign.
+    Created(Campaign),
+    Renamed {
+        name: String,
+    },
+    Described {
+        description: String,
+    },
+    SessionAdded(Box<Session>),
+    SessionRemoved(SessionId),
+    SessionRenamed {
+        session_id: SessionId,
+        name: String,
+    },
+    NoteAdded {
+        session_id: SessionId,
+        note: Note,
+    },
+    NoteEdited {
+        session_id: SessionId,
+        note_id: NoteId,
+        name: String,
+        description: String,
+    },
+    NoteRemoved {
+        session_id: SessionId,
+        note_id: NoteId,
+    },
+    PlayerJoined(Box<Player>),
+    PlayerLeft(CreatureId),
+    HitPointsChanged {
+        player_id: CreatureId,
+        hit_points: Health,
+    },
+    ExperienceChanged {
+        player_id: CreatureId,
+        experience: u32,
+    },
+    LevelChanged {
+        player_id: CreatureId,
+        level: u8,
+    },
+}
+
+impl Event {
+    /// The player the event changes, if it changes one.
+    pub fn player_id(&self) -> Option<&CreatureId> {
+        match &self {
+            Event::PlayerJoined(player) => Some(player.id()),
+            Event::PlayerLeft(player_id)
+            | Event::HitPointsChanged { player_id, .. }
+            | Event::ExperienceChanged { player_id, .. }
+            | Event::LevelChanged { player_id, .. } => Some(player_id),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded event, with who made the change and when, in seconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Entry {
+    pub author: String,
+    pub timestamp: u64,
+    pub event: Event,
+}
+
+/// A campaign with its sessions and players, as built from its events.
+#[derive(Debug, Clone)]
+pub struct CampaignState {
+    pub campaign: Campaign,
+    pub sessions: BTreeMap<SessionId, Session>,
+    pub players: BTreeMap<CreatureId, Player>,
+}
+
+impl CampaignState {
+    fn new(campaign: Campaign) -> Self {
+        Self {
+            campaign,
+            sessions: BTreeMap::new(),
+            players: BTreeMap::new(),
+        }
+    }
+
+    fn session(&mut self, id: &SessionId) -> Result<&mut Session, HistoryError> {
+        self.sessions
+            .get_mut(id)
+            .ok_or_else(|| HistoryError::unknown("session", id))
+    }
+
+    fn player(&mut self, id: &CreatureId) -> Result<&mut Player, HistoryError> {
+        self.players
+            .get_mut(id)
+            .ok_or_else(|| HistoryError::unknown("player", id))
+    }
+
+    /// Applies an event, leaving the state unchanged when it does not fit.
+    fn apply(&mut self, event: &Event) -> Result<(), HistoryError> {
+        match event {
+            Event::Created(_) => return Err(HistoryError::AlreadyCreated),
+            Event::Renamed { name } => self.campaign.name = name.clone(),
+            Event::Described { description } => self.campaign.description = description.clone(),
+            Event::SessionAdded(session) => {
+                if self.sessions.contains_key(&session.id) {
+                    return Err(HistoryError::duplicate("session", &session.id));
+                }
+                let mut session = session.as_ref().clone();
+                session.campaign_id = self.campaign.id;
+                self.sessions.insert(session.id, session);
+            }
+            Event::SessionRemoved(id) => {
+                self.sessions
+                    .remove(id)
+                    .ok_or_else(|| HistoryError::unknown("session", id))?;
+            }
+            Event::SessionRenamed { session_id, name } => {
+                self.session(session_id)?.name = name.clone();
+            }
+            Event::NoteAdded { session_id, note } => {
+                let session = self.session(session_id)?;
+                if session.notes.iter().any(|x| x.id == note.id) {
+                    return Err(HistoryError::duplicate("note", &note.id));
+                }
+                session.notes.push(note.clone());
+            }
+            Event::NoteEdited {
+                session_id,
+                note_id,
+                name,
+                description,
+            } => {
+                let note = self
+                    .session(session_id)?
+                    .notes
+                    .iter_mut()
+                    .find(|x| &x.id == note_id)
+                    .ok_or_else(|| HistoryError::unknown("note", note_id))?;
+                note.name = name.clone();
+                note.description = description.clone();
+            }
+            Event::NoteRemoved {
+                session_id,
+                note_id,
+            } => {
+                let notes = &mut self.session(session_id)?.notes;
+                let index = notes
+                    .iter()
+                    .position(|x| &x.id == note_id)
+                    .ok_or_else(|| HistoryError::unknown("note", note_id))?;
+                notes.remove(index);
+            }
+            Event::PlayerJoined(player) => {
+                if self.players.contains_key(player.id()) {
+                    return Err(HistoryError::duplicate("player", player.id()));
+                }
+                let mut player = player.as_ref().clone();
+                self.campaign.add_player(&mut player);
+                self.players.insert(*player.id(), player);
+            }
+            Event::PlayerLeft(id) => {
+                self.players
+                    .remove(id)
+                    .ok_or_else(|| HistoryError::unknown("player", id))?;
+                self.campaign.remove_player(id);
+            }
+            Event::HitPointsChanged {
+                player_id,
+                hit_points,
+            } => self.player(player_id)?.set_hit_points(hit_points.clone()),
+            Event::ExperienceChanged {
+                player_id,
+                experience,
+            } => self.player(player_id)?.set_experience(*experience),
+            Event::LevelChanged { player_id, level } => {
+                self.player(player_id)?.set_level(*level)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The event log of one campaign and the state it leads to.
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: Vec<Entry>,
+    /// How many entries are applied; the ones after it have been undone.
+    position: usize,
+    state: CampaignState,
+}
+
+impl History {
+    /// Starts the history of a new campaign.
+    pub fn new(author: String, campaign: Campaign) -> Self {
+        Self {
+            state: CampaignState::new(campaign.clone()),
+            entries: vec![Entry {
+                author,
+                timestamp: now(),
+                event: Event::Created(campaign),
+            }],
+            position: 1,
+        }
+    }
+
+    /// Rebuilds a campaign from its log, with every entry applied.
+    pub fn from_entries(entries: Vec<Entry>) -> Result<Self, HistoryError> {
+        let state = replay(&entries)?;
+        Ok(Self {
+            position: entries.len(),
+            entries,
+            state,
+        })
+    }
+
+    /// Records a change made now.
+    pub fn record(&mut self, author: String, event: Event) -> Result<(), HistoryError> {
+        self.record_at(author, now(), event)
+    }
+
+    /// Records a change made at `timestamp`. Changes that do not fit the current state, such
+    /// as editing a note that does not exist, are refused and not recorded.
+    pub fn record_at(
+        &mut self,
+        author: String,
+        timestamp: u64,
+        event: Event,
+    ) -> Result<(), HistoryError> {
+        let mut state = self.state.clone();
+        state.apply(&event)?;
+        self.state = state;
+        self.entries.truncate(self.position);
+        self.entries.push(Entry {
+            author,
+            timestamp,
+            event,
+        });
+        self.position += 1;
+        Ok(())
+    }
+
+    pub fn state(&self) -> &CampaignState {
+        &self.state
+    }
+
+    /// Every entry, including undone ones that can still be redone.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The number of applied entries.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The state after the first `position` entries.
+    pub fn state_at(&self, position: usize) -> Result<CampaignState, HistoryError> {
+        if position == 0 || position > self.entries.len() {
+            return Err(HistoryError::OutOfRange(position));
+        }
+        replay(&self.entries[..position])
+    }
+
+    /// Undoes the last applied change, returning whether there was one. The creation of the
+    /// campaign cannot be undone.
+    pub fn undo(&mut self) -> Result<bool, HistoryError> {
+        if self.position <= 1 {
+            return Ok(false);
+        }
+        self.state = self.state_at(self.position - 1)?;
+        self.position -= 1;
+        Ok(true)
+    }
+
+    /// Reapplies the last undone change, returning whether there was one.
+    pub fn redo(&mut self) -> Result<bool, HistoryError> {
+        let Some(entry) = self.entries.get(self.position) else {
+            return Ok(false);
+        };
+        self.state.apply(&entry.event)?;
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// The applied changes to one player, oldest first, such as who changed its hit points.
+    pub fn player_changes<'a>(
+        &'a self,
+        player_id: &'a CreatureId,
+    ) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries[..self.position]
+            .iter()
+            .filter(move |x| x.event.player_id() == Some(player_id))
+    }
+}
+
+fn replay(entries: &[Entry]) -> Result<CampaignState, HistoryError> {
+    let Some(Entry {
+        event: Event::Created(campaign),
+        ..
+    }) = entries.first()
+    else {
+        return Err(HistoryError::NotCreated);
+    };
+    let mut state = CampaignState::new(campaign.clone());
+    for entry in &entries[1..] {
+        state.apply(&entry.event)?;
+    }
+    Ok(state)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryError {
+    /// The log does not start with the creation of the campaign.
+    NotCreated,
+    /// The creation of the campaign appears after the start of the log.
+    AlreadyCreated,
+    Unknown {
+        kind: &'static str,
+        id: String,
+    },
+    Duplicate {
+        kind: &'static str,
+        id: String,
+    },
+    OutOfRange(usize),
+    /// An event sets a value the campaign does not allow.
+    Invalid(ValidationError),
+}
+
+impl HistoryError {
+    fn unknown(kind: &'static str, id: &impl fmt::Display) -> Self {
+        HistoryError::Unknown {
+            kind,
+            id: id.to_string(),
+        }
+    }
+
+    fn duplicate(kind: &'static str, id: &impl fmt::Display) -> Self {
+        HistoryError::Duplicate {
+            kind,
+            id: id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            HistoryError::NotCreated => write!(f, "the log does not start with a campaign"),
+            HistoryError::AlreadyCreated => write!(f, "the campaign was already created"),
+            HistoryError::Unknown { kind, id } => write!(f, "unknown {} {}", kind, id),
+            HistoryError::Duplicate { kind, id } => write!(f, "duplicate {} {}", kind, id),
+            HistoryError::OutOfRange(position) => {
+                write!(f, "position {} is outside the history", position)
+            }
+            HistoryError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<ValidationError> for HistoryError {
+    fn from(e: ValidationError) -> Self {
+        HistoryError::Invalid(e)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_history() {
+        use super::{Event, History, HistoryError};
+        use crate::{
+            creature::{Creature, CreatureType, Health, MovementSpeed},
+            id::{CampaignId, CreatureId, WorldId},
+            world::campaign::{
+                player::{Class, Player, Race},
+                session::{Note, Session},
+                Campaign,
+            },
+            Alignment, Die, DieStat,
+        };
+
+        let creature = Creature::new(
+            CreatureId::new(),
+            String::from("Tessa"),
+            CreatureType::Player,
+            Alignment::ChaoticGood,
+            12,
+            Health {
+                health: DieStat::default(),
+            },
+            MovementSpeed::Walk(30),
+            Vec::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let player =
+            Player::try_new(creature, Race::Elf, Class::Wizard, 1, 0, CampaignId::new()).unwrap();
+        let player_id = *player.id();
+        let campaign = Campaign::new(String::from("Curse"), String::new(), WorldId::new());
+        let session = Session::new(String::from("Session 1"), String::new(), campaign.id);
+        let note = Note::new(String::from("Loot"), String::from("A silver key"));
+
+        let gm = String::from("gm");
+        let mut history = History::new(gm.clone(), campaign);
+        history
+            .record_at(gm.clone(), 10, Event::PlayerJoined(Box::new(player)))
+            .unwrap();
+        history
+            .record_at(
+                gm.clone(),
+                20,
+                Event::SessionAdded(Box::new(session.clone())),
+            )
+            .unwrap();
+        history
+            .record_at(
+                String::from("tessa"),
+                30,
+                Event::NoteAdded {
+                    session_id: session.id,
+                    note: note.clone(),
+                },
+            )
+            .unwrap();
+        let hit_points = Health {
+            health: DieStat {
+                die_count: 1,
+                die_type: Die::D6,
+                extra: 4,
+            },
+        };
+        history
+            .record_at(
+                gm.clone(),
+                40,
+                Event::HitPointsChanged {
+                    player_id,
+                    hit_points: hit_points.clone(),
+                },
+            )
+            .unwrap();
+        history
+            .record_at(
+                gm.clone(),
+                50,
+                Event::ExperienceChanged {
+                    player_id,
+                    experience: 300,
+                },
+            )
+            .unwrap();
+
+        let state = history.state();
+        assert_eq!(state.campaign.player_ids, vec![player_id]);
+        assert_eq!(state.players[&player_id].experience(), &300);
+        assert_eq!(
+            state.players[&player_id].hit_points().health,
+            hit_points.health
+        );
+        assert_eq!(
+            state.sessions[&session.id].notes.last().unwrap().name,
+            "Loot"
+        );
+
+        // Changes that do not fit the state are refused.
+        let refused = history.record(
+            gm.clone(),
+            Event::NoteRemoved {
+                session_id: session.id,
+                note_id: crate::id::NoteId::new(),
+            },
+        );
+        assert!(matches!(
+            refused,
+            Err(HistoryError::Unknown { kind: "note", .. })
+        ));
+        assert_eq!(history.entries().len(), 6);
+
+        // The audit trail of a player, and the state at an earlier point.
+        let changes: Vec<(u64, &str)> = history
+            .player_changes(&player_id)
+            .map(|x| (x.timestamp, x.author.as_str()))
+            .collect();
+        assert_eq!(changes, vec![(10, "gm"), (40, "gm"), (50, "gm")]);
+        let before = history.state_at(3).unwrap();
+        assert_eq!(before.players[&player_id].experience(), &0);
+        assert_eq!(before.sessions[&session.id].notes.len(), 1);
+
+        assert!(history.undo().unwrap());
+        assert_eq!(history.state().players[&player_id].experience(), &0);
+        assert!(history.redo().unwrap());
+        assert!(!history.redo().unwrap());
+        assert_eq!(history.state().players[&player_id].experience(), &300);
+
+        // A new change after an undo discards the undone one.
+        history.undo().unwrap();
+        history
+            .record_at(
+                gm.clone(),
+                60,
+                Event::LevelChanged {
+                    player_id,
+                    level: 2,
+                },
+            )
+            .unwrap();
+        assert!(!history.redo().unwrap());
+        assert_eq!(history.state().players[&player_id].experience(), &0);
+
+        // The log alone rebuilds the campaign.
+        let log = serde_json::to_string(history.entries()).unwrap();
+        let rebuilt = History::from_entries(serde_json::from_str(&log).unwrap()).unwrap();
+        assert_eq!(rebuilt.state().players[&player_id].level(), &2);
+        while history.undo().unwrap() {}
+        assert_eq!(history.position(), 1);
+        assert!(history.state().players.is_empty());
+        assert!(matches!(
+            History::from_entries(Vec::new()),
+            Err(HistoryError::NotCreated)
+        ));
+    }
+}
@@ -8,13 +8,17 @@ use crate::{
         Creature, CreatureType, Health, Lair, Language, MovementSpeed, RacialTrait, Sense, Skill,
         Stat,
     },
+    error::ValidationError,
+    id::{CampaignId, CreatureId},
     item::Item,
     spell::Spell,
-    world::campaign::Campaign,
     Alignment, ConditionType, DamageType, OtherAttribute,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "PlayerData")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Player {
     creature: Creature,
     race: Race,
@@ -23,29 +27,76 @@ pub struct Player {
     experience: u32,
     inventory: Vec<Item>,
     spells: Vec<Spell>,
-    campaign: Campaign,
+    campaign_id: CampaignId,
 }
 
+/// The serialized form of a [`Player`], checked by [`Player::try_new`] when deserializing.
+#[derive(Deserialize)]
+struct PlayerData {
+    creature: Creature,
+    race: Race,
+    class: Class,
+    level: u8,
+    experience: u32,
+    inventory: Vec<Item>,
+    spells: Vec<Spell>,
+    campaign_id: CampaignId,
+}
+
+impl TryFrom<PlayerData> for Player {
+    type Error = ValidationError;
+
+    fn try_from(data: PlayerData) -> Result<Self, Self::Error> {
+        let mut player = Player::try_new(
+            data.creature,
+            data.race,
+            data.class,
+            data.level,
+            data.experience,
+            data.campaign_id,
+        )?;
+        player.inventory = data.inventory;
+        player.spells = data.spells;
+        Ok(player)
+    }
+}
+
+/// The levels a player character can have.
+pub const LEVELS: core::ops::RangeInclusive<u8> = 1..=20;
+
 impl Player {
-    pub fn new(
+    /// Creates a player from a creature of type [`CreatureType::Player`] at a level in
+    /// [`LEVELS`].
+    pub fn try_new(
         creature: Creature,
         race: Race,
         class: Class,
         level: u8,
         experience: u32,
-        campaign: Campaign,
-    ) -> Self {
-        assert_eq!(creature.creature_type(), &CreatureType::Player);
-        Self {
+        campaign_id: CampaignId,
+    ) -> Result<Self, ValidationError> {
+        if creature.creature_type() != &CreatureType::Player {
+            return Err(ValidationError::new(
+                "creature_type",
+                format!("expected a player, found {:?}", creature.creature_type()),
+            ));
+        }
+        let mut player = Self {
             creature,
             race,
             class,
-            level,
+            level: *LEVELS.start(),
             experience,
             inventory: Vec::new(),
             spells: Vec::new(),
-            campaign,
-        }
+            campaign_id,
+        };
+        player.set_level(level)?;
+        Ok(player)
+    }
+
+    pub fn creature(&self) -> &Creature {
+        &self.creature
     }
 
     pub fn race(&self) -> &Race {
@@ -64,8 +115,8 @@ impl Player {
         &self.experience
     }
 
-    pub fn campaign(&self) -> &Campaign {
-        &self.campaign
+    pub fn campaign_id(&self) -> &CampaignId {
+        &self.campaign_id
     }
 
     pub fn inventory(&self) -> &Vec<Item> {
@@ -100,15 +151,27 @@ impl Player {
         self.experience = experience;
     }
 
-    pub fn set_level(&mut self, level: u8) {
+    pub fn set_level(&mut self, level: u8) -> Result<(), ValidationError> {
+        if !LEVELS.contains(&level) {
+            return Err(ValidationError::new(
+                "level",
+                format!(
+                    "{} is not between {} and {}",
+                    level,
+                    LEVELS.start(),
+                    LEVELS.end()
+                ),
+            ));
+        }
         self.level = level;
+        Ok(())
     }
 
-    pub fn set_campaign(&mut self, campaign: Campaign) {
-        self.campaign = campaign;
+    pub fn set_campaign_id(&mut self, campaign_id: CampaignId) {
+        self.campaign_id = campaign_id;
     }
 
-    pub fn id(&self) -> &str {
+    pub fn id(&self) -> &CreatureId {
         self.creature.id()
     }
 
@@ -204,8 +267,8 @@ impl Player {
         self.creature.set_alignment(alignment);
     }
 
-    pub fn set_armor_class(&mut self, armor_class: i32) {
-        self.creature.set_armor_class(armor_class);
+    pub fn set_armor_class(&mut self, armor_class: i32) -> Result<(), ValidationError> {
+        self.creature.set_armor_class(armor_class)
     }
 
     pub fn set_hit_points(&mut self, hit_points: Health) {
@@ -274,6 +337,8 @@ impl Player {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Race {
     Human,
     Elf,
@@ -292,6 +357,8 @@ impl fmt::Display for Race {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum Class {
     Barbarian,
     Bard,
@@ -312,3 +379,41 @@ impl fmt::Display for Class {
         write!(f, "{:?}", self)
     }
 }
+
+mod test {
+    #[test]
+    fn test_deserialize_validates() {
+        use super::{Class, Player, Race};
+        use crate::{creature::parse::parse_stat_block, creature::CreatureType, id::CampaignId};
+
+        let (mut creature, _) = parse_stat_block("Ada\nArmor Class 16\n");
+        creature.set_creature_type(CreatureType::Player);
+        let player = Player::try_new(
+            creature,
+            Race::Human,
+            Class::Fighter,
+            3,
+            900,
+            CampaignId::new(),
+        )
+        .unwrap();
+        let json = serde_json::to_value(&player).unwrap();
+        let loaded: Player = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(*loaded.level(), 3);
+
+        let mut invalid = json.clone();
+        invalid["level"] = serde_json::json!(0);
+        let error = serde_json::from_value::<Player>(invalid).unwrap_err();
+        assert!(error.to_string().contains("invalid level"));
+
+        let mut invalid = json.clone();
+        invalid["creature"]["creature_type"] = serde_json::json!("NPC");
+        let error = serde_json::from_value::<Player>(invalid).unwrap_err();
+        assert!(error.to_string().contains("invalid creature_type"));
+
+        let mut invalid = json;
+        invalid["creature"]["armor_class"] = serde_json::json!(-1);
+        let error = serde_json::from_value::<Player>(invalid).unwrap_err();
+        assert!(error.to_string().contains("invalid armor_class"));
+    }
+}
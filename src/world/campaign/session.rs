@@ -1,34 +1,145 @@
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use core::{str::FromStr, time::Duration};
+use std::collections::BTreeMap;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    error::ValidationError,
+    id::{CampaignId, CreatureId, LocationId, NoteId, SessionId},
+    ParseError,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Session {
-    pub id: String,
+    pub id: SessionId,
     pub name: String,
     pub description: String,
-    pub campaign_id: String,
+    pub campaign_id: CampaignId,
     pub notes: Vec<Note>,
     pub plan: Plan,
     pub recap: Recap,
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Whether each player of the campaign was at the session, once it has been recorded.
+    #[serde(default)]
+    pub attendance: BTreeMap<CreatureId, Attendance>,
 }
 
 impl Session {
-    pub fn new(name: String, description: String, campaign_id: String) -> Self {
+    pub fn new(name: String, description: String, campaign_id: CampaignId) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: SessionId::new(),
             name,
             description,
             campaign_id,
             notes: vec![Note::default()],
             plan: Plan::default(),
             recap: Recap::default(),
+            schedule: Schedule::default(),
+            attendance: BTreeMap::new(),
         }
     }
+
+    /// Whether the session has been played, or at least started.
+    pub fn is_held(&self) -> bool {
+        self.schedule.started.is_some()
+    }
+
+    pub fn attended(&self, player_id: &CreatureId) -> bool {
+        self.attendance.get(player_id) == Some(&Attendance::Present)
+    }
+
+    pub fn missed(&self, player_id: &CreatureId) -> bool {
+        self.attendance.get(player_id) == Some(&Attendance::Absent)
+    }
+}
+
+/// When a session was planned and played. Times are seconds since the Unix epoch, in UTC.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Schedule {
+    #[serde(default, deserialize_with = "time")]
+    pub planned: Option<u64>,
+    #[serde(default, deserialize_with = "time")]
+    pub started: Option<u64>,
+    #[serde(default, deserialize_with = "time")]
+    pub ended: Option<u64>,
+    /// The IANA name of the time zone the table plays in, such as `Europe/Berlin`, for
+    /// showing the times in local time.
+    pub timezone: String,
+}
+
+impl Schedule {
+    /// The last second of the year 9999, the latest time a schedule accepts.
+    pub const MAX_TIME: u64 = 253_402_300_799;
+
+    pub fn set_planned(&mut self, at: u64) -> Result<(), ValidationError> {
+        self.planned = Some(check_time("planned", at)?);
+        Ok(())
+    }
+
+    pub fn set_started(&mut self, at: u64) -> Result<(), ValidationError> {
+        self.started = Some(check_time("started", at)?);
+        Ok(())
+    }
+
+    pub fn set_ended(&mut self, at: u64) -> Result<(), ValidationError> {
+        let at = check_time("ended", at)?;
+        if let Some(started) = self.started.filter(|x| *x > at) {
+            return Err(ValidationError::new(
+                "ended",
+                format!("{} is before the start at {}", at, started),
+            ));
+        }
+        self.ended = Some(at);
+        Ok(())
+    }
+
+    /// How long the session ran, once it has both started and ended within the times a
+    /// schedule accepts.
+    pub fn duration(&self) -> Option<Duration> {
+        match (self.started, self.ended) {
+            (Some(started), Some(ended)) if ended <= Self::MAX_TIME => {
+                ended.checked_sub(started).map(Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn check_time(field: &'static str, at: u64) -> Result<u64, ValidationError> {
+    match at <= Schedule::MAX_TIME {
+        true => Ok(at),
+        false => Err(ValidationError::new(
+            field,
+            format!("{} is after the year 9999", at),
+        )),
+    }
+}
+
+fn time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<u64>::deserialize(deserializer)?
+        .map(|x| check_time("time", x))
+        .transpose()
+        .map_err(de::Error::custom)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub enum Attendance {
+    Present,
+    Absent,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Note {
-    pub id: String,
+    pub id: NoteId,
     pub name: String,
     pub description: String,
 }
@@ -36,7 +147,7 @@ pub struct Note {
 impl Note {
     pub fn new(name: String, description: String) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: NoteId::new(),
             name,
             description,
         }
@@ -46,15 +157,236 @@ impl Note {
 impl Default for Note {
     fn default() -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: NoteId::new(),
             name: String::default(),
             description: String::default(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-pub struct Plan(String);
+/// What the game master prepared for a session. Its Markdown form is written by
+/// [`render::markdown::plan`](crate::render::markdown::plan) and read back with [`str::parse`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Plan {
+    pub summary: String,
+    pub scenes: Vec<Scene>,
+    pub encounters: Vec<String>,
+    /// The non-player characters to introduce.
+    pub npcs: Vec<String>,
+    pub locations: Vec<LocationId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Scene {
+    pub title: String,
+    pub description: String,
+}
+
+/// What happened in a session. Its Markdown form is written by
+/// [`render::markdown::recap`](crate::render::markdown::recap) and read back with
+/// [`str::parse`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Recap {
+    pub summary: String,
+    pub events: Vec<String>,
+    pub loot: Vec<Loot>,
+    pub experience: Vec<Award>,
+    pub open_threads: Vec<String>,
+}
+
+/// Treasure found in a session, with the player who took it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Loot {
+    pub item: String,
+    pub player_id: Option<CreatureId>,
+}
+
+/// Experience awarded to a player in a session.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Award {
+    pub player_id: CreatureId,
+    pub experience: u32,
+}
+
+impl Plan {
+    /// A plan with only a summary.
+    pub fn new(summary: String) -> Self {
+        Self {
+            summary,
+            ..Self::default()
+        }
+    }
+}
+
+impl Recap {
+    /// A recap with only a summary.
+    pub fn new(summary: String) -> Self {
+        Self {
+            summary,
+            ..Self::default()
+        }
+    }
+
+    /// The experience awarded to a player.
+    pub fn experience_of(&self, player_id: &CreatureId) -> u32 {
+        self.experience
+            .iter()
+            .filter(|x| &x.player_id == player_id)
+            .fold(0, |total, x| total.saturating_add(x.experience))
+    }
+}
+
+/// Parses the Markdown written by [`render::markdown::plan`](crate::render::markdown::plan):
+/// a summary followed by `## Scenes`, `## Encounters`, `## NPCs` and `## Locations` sections.
+impl FromStr for Plan {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (summary, sections) = sections(s);
+        let mut plan = Self::new(summary);
+        for (heading, body) in sections {
+            match heading.to_lowercase().as_str() {
+                "scenes" => plan.scenes = scenes(&body)?,
+                "encounters" => plan.encounters = items(&body)?,
+                "npcs" => plan.npcs = items(&body)?,
+                "locations" => {
+                    plan.locations = items(&body)?
+                        .iter()
+                        .map(|x| x.parse())
+                        .collect::<Result<_, _>>()?
+                }
+                _ => return Err(ParseError::new(&heading, "unknown plan section")),
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// Parses the Markdown written by [`render::markdown::recap`](crate::render::markdown::recap):
+/// a summary followed by `## Events`, `## Loot`, `## Experience` and `## Open threads`
+/// sections.
+impl FromStr for Recap {
+    type Err = ParseError;
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-pub struct Recap(String);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (summary, sections) = sections(s);
+        let mut recap = Self::new(summary);
+        for (heading, body) in sections {
+            match heading.to_lowercase().as_str() {
+                "events" => recap.events = items(&body)?,
+                "loot" => recap.loot = items(&body)?.iter().map(|x| loot(x)).collect(),
+                "experience" => {
+                    recap.experience = items(&body)?
+                        .iter()
+                        .map(|x| award(x))
+                        .collect::<Result<_, _>>()?
+                }
+                "open threads" => recap.open_threads = items(&body)?,
+                _ => return Err(ParseError::new(&heading, "unknown recap section")),
+            }
+        }
+        Ok(recap)
+    }
+}
+
+/// Splits a document into the text before its first `## ` heading and its sections.
+fn sections(text: &str) -> (String, Vec<(String, String)>) {
+    let mut summary = Vec::new();
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix("## "), sections.last_mut()) {
+            (Some(heading), _) => sections.push((heading.trim().to_string(), Vec::new())),
+            (None, Some((_, body))) => body.push(line),
+            (None, None) => summary.push(line),
+        }
+    }
+    let sections = sections
+        .into_iter()
+        .map(|(heading, body)| (heading, body.join("\n")))
+        .collect();
+    (summary.join("\n").trim().to_string(), sections)
+}
+
+/// The items of a bulleted list. Lines that do not start a new item continue the last one.
+fn items(body: &str) -> Result<Vec<String>, ParseError> {
+    let mut items: Vec<String> = Vec::new();
+    for line in body.lines().map(str::trim).filter(|x| !x.is_empty()) {
+        match (
+            line.strip_prefix("- ").or(line.strip_prefix("* ")),
+            items.last_mut(),
+        ) {
+            (Some(item), _) => items.push(item.trim().to_string()),
+            (None, Some(item)) => {
+                item.push(' ');
+                item.push_str(line);
+            }
+            (None, None) => return Err(ParseError::new(line, "expected a list item")),
+        }
+    }
+    Ok(items)
+}
+
+fn scenes(body: &str) -> Result<Vec<Scene>, ParseError> {
+    let mut scenes: Vec<Scene> = Vec::new();
+    for line in body.lines() {
+        match (line.strip_prefix("### "), scenes.last_mut()) {
+            (Some(title), _) => scenes.push(Scene {
+                title: title.trim().to_string(),
+                description: String::new(),
+            }),
+            (None, Some(scene)) => {
+                scene.description.push_str(line);
+                scene.description.push('\n');
+            }
+            (None, None) if line.trim().is_empty() => {}
+            (None, None) => return Err(ParseError::new(line, "expected a scene heading")),
+        }
+    }
+    for scene in &mut scenes {
+        scene.description = scene.description.trim().to_string();
+    }
+    Ok(scenes)
+}
+
+/// Reads loot written as `item` or `item (player id)`.
+fn loot(text: &str) -> Loot {
+    let player = text
+        .strip_suffix(')')
+        .and_then(|x| x.rsplit_once(" ("))
+        .and_then(|(item, id)| Some((item, id.parse().ok()?)));
+    match player {
+        Some((item, player_id)) => Loot {
+            item: item.trim().to_string(),
+            player_id: Some(player_id),
+        },
+        None => Loot {
+            item: text.to_string(),
+            player_id: None,
+        },
+    }
+}
+
+/// Reads an award written as `player id: 300 XP`.
+fn award(text: &str) -> Result<Award, ParseError> {
+    let (player_id, experience) = text
+        .split_once(':')
+        .ok_or_else(|| ParseError::new(text, "expected a player id and experience"))?;
+    let experience = experience.trim();
+    let experience = experience.strip_suffix("XP").unwrap_or(experience).trim();
+    Ok(Award {
+        player_id: player_id.trim().parse()?,
+        experience: experience
+            .parse()
+            .map_err(|_| ParseError::new(text, "invalid experience"))?,
+    })
+}
@@ -1,15 +1,20 @@
 use serde::{Deserialize, Serialize};
 
-use crate::creature::Creature;
+use crate::{
+    creature::Creature,
+    id::{LocationId, WorldId},
+};
 
 use super::map::MapCoordinates;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Location {
-    pub id: String,
+    pub id: LocationId,
     pub name: String,
     pub description: String,
-    pub world_id: String,
+    pub world_id: WorldId,
     pub map_coordinates: MapCoordinates,
     pub npcs: Vec<Creature>,
 }
@@ -1,13 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use crate::id::{MapId, WorldId};
+
 use super::location::Location;
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Map {
-    pub id: String,
+    pub id: MapId,
     pub name: String,
     pub description: String,
-    pub world_id: String,
+    pub world_id: WorldId,
     pub width: u32,
     pub height: u32,
     pub map: Vec<Vec<String>>,
@@ -16,10 +20,10 @@ pub struct Map {
 
 impl Map {
     pub fn new(
-        id: String,
+        id: MapId,
         name: String,
         description: String,
-        world_id: String,
+        world_id: WorldId,
         width: u32,
         height: u32,
     ) -> Self {
@@ -37,14 +41,16 @@ impl Map {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct MapCoordinates {
-    pub map_id: String,
+    pub map_id: MapId,
     pub x: u32,
     pub y: u32,
 }
 
 impl MapCoordinates {
-    pub fn new(map_id: String, x: u32, y: u32) -> Self {
+    pub fn new(map_id: MapId, x: u32, y: u32) -> Self {
         Self { map_id, x, y }
     }
 }